@@ -1,6 +1,10 @@
-use io::Scanner;
+use dopt::bin_packing::{heuristic_packing, l2_bound_sorted, l3_bound, reduce, Heuristic};
+use dopt::dzn::{DznData, DznError};
+use dopt::io::{Scanner, ScanError};
 use std::fmt;
 use std::fmt::Display;
+use std::io::BufRead;
+use std::time::{Duration, Instant};
 
 struct BBProblem {
     pub capacity: usize,
@@ -13,34 +17,8 @@ struct Packing {
 }
 
 impl Packing {
-    pub fn with_capacity(size: usize) -> Self {
-        Self { indices: Vec::with_capacity(size) }
-    }
-
-    pub fn len(&self) -> usize {
-        self.indices.len()
-    }
-
-    pub fn put_item(&mut self, bin_idx: usize) {
-        self.indices.push(bin_idx);
-    }
-
-    pub fn truncate(&mut self, len: usize) {
-        self.indices.truncate(len);
-    }
-
     pub fn num_bins(&self) -> usize {
-        *self.indices.iter().max().unwrap_or(&0) + 1
-    }
-
-    pub fn is_correct_packing(&self, problem: &BBProblem) -> bool {
-        let mut bins = vec![0_usize; self.num_bins()];
-        for (item_idx, bin_idx) in self.indices.iter().enumerate() {
-            let bin_weight = &mut bins[*bin_idx];
-            *bin_weight += problem.weights[item_idx];
-            if *bin_weight > problem.capacity { return false; }
-        }
-        true
+        self.indices.iter().max().map_or(0, |bin_idx| bin_idx + 1)
     }
 }
 
@@ -54,110 +32,370 @@ impl Display for Packing {
     }
 }
 
-fn first_fit(problem: &BBProblem) -> Packing {
-    let mut indices: Vec<usize> = vec![std::usize::MAX; problem.weights.len()];
-    let mut bin_spaces: Vec<usize> = Vec::with_capacity(indices.len());
-
-    for (item_idx, weight) in problem.weights.iter().enumerate() {
-        let bin = {
-                bin_spaces.iter_mut()
-                .enumerate()
-                .find_map(|(bin_idx, x)| {
-                    if *x > *weight {
-                        *x -= *weight;
-                        indices[item_idx] = bin_idx;
-                        Some(*x)
-                    } else {
-                        None
-                    }
-                })
-            };
-
-        if bin == None {
-            bin_spaces.push(problem.capacity - weight);
-            indices[item_idx] = bin_spaces.len() - 1;
-        }
-    }
-    Packing { indices }
+#[derive(Default)]
+struct SearchStats {
+    fixed_items: usize, // предметы в контейнерах, зафиксированных редукцией
+    fixed_bins: usize,
+    heuristic: Option<Heuristic>,
+    start_bins: usize,
+    lower_bound: usize,
+    explored: u64,
+    pruned: u64,
+    complete: bool,
+    elapsed: Duration,
 }
 
-fn lower_bound(problem: &BBProblem) -> usize {
-    let total_weight = problem.weights.iter().sum::<usize>() as f64;
-    (total_weight / problem.capacity as f64).ceil() as usize
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "heuristic: {}, start bins: {}, lower bound: {}, nodes explored: {}, pruned: {}, optimal: {}, time: {} ms",
+            self.heuristic.map_or("-", Heuristic::name), self.start_bins, self.lower_bound, self.explored, self.pruned, self.complete, self.elapsed.as_millis())
+    }
 }
 
-fn bin_packing_impl(
+// Ветви и границы: предметы раскладываются по убыванию веса, загрузки
+// открытых контейнеров меняются при спуске и откате, а не пересчитываются.
+struct Search<'a> {
+    problem: &'a BBProblem,
+    order: Vec<usize>,  // номера предметов по убыванию веса
+    bins: Vec<usize>,   // bins[pos] - контейнер предмета order[pos]
+    floors: Vec<usize>, // наименьший контейнер для следующего такого же предмета
+    loads: Vec<usize>,  // загрузки открытых контейнеров
+    weight_left: usize, // вес ещё не разложенных предметов
+    free_space: usize,  // свободное место в открытых контейнерах
+    best: Packing,
+    best_bins: usize,
     lower_bound: usize,
-    curr_packing: &mut Packing,
-    best_packing: &mut Packing,
-    problem: &BBProblem
-) {
-    if best_packing.num_bins() == lower_bound { return; }
-    else if !curr_packing.is_correct_packing(problem) { return; }
-    else if curr_packing.num_bins() >= best_packing.num_bins() { return;}
-    else if curr_packing.len() == problem.weights.len() { *best_packing = curr_packing.clone(); }
-    else {
-        let packing_len = curr_packing.len();
-        for bin_idx in 0..best_packing.num_bins() {
-            curr_packing.truncate(packing_len);
-            curr_packing.put_item(bin_idx);
-            bin_packing_impl(lower_bound, curr_packing, best_packing, problem);
+    stats: SearchStats,
+    node_limit: Option<u64>,
+    scratch: Vec<usize>,
+}
+
+impl Search<'_> {
+    fn out_of_nodes(&self) -> bool {
+        self.node_limit.is_some_and(|limit| self.stats.explored >= limit)
+    }
+
+    fn place(&mut self, pos: usize, bin: usize, weight: usize) {
+        self.bins[pos] = bin;
+        self.floors[pos] = bin;
+        self.loads[bin] += weight;
+        self.weight_left -= weight;
+        self.free_space -= weight;
+    }
+
+    fn unplace(&mut self, bin: usize, weight: usize) {
+        self.loads[bin] -= weight;
+        self.weight_left += weight;
+        self.free_space += weight;
+    }
+
+    // L2 для оставшихся предметов вместе с загрузками открытых
+    // контейнеров: содержимое контейнера - неделимый предмет
+    fn node_bound(&mut self, pos: usize) -> usize {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(&self.loads);
+        self.scratch.extend(self.order[pos..].iter().map(|&item_idx| self.problem.weights[item_idx]));
+        self.scratch.sort_unstable();
+        l2_bound_sorted(self.problem.capacity, &self.scratch)
+    }
+
+    fn search(&mut self, pos: usize) {
+        if self.best_bins == self.lower_bound || self.out_of_nodes() { return; }
+        self.stats.explored += 1;
+
+        if pos == self.order.len() {
+            for (&item_idx, &bin) in self.order.iter().zip(&self.bins) {
+                self.best.indices[item_idx] = bin;
+            }
+            self.best_bins = self.loads.len();
+            return;
+        }
+        // вес, не помещающийся в свободное место, требует новых контейнеров
+        let capacity = self.problem.capacity;
+        let new_bins = self.weight_left.saturating_sub(self.free_space).div_ceil(capacity);
+        if self.loads.len() + new_bins >= self.best_bins || self.node_bound(pos) >= self.best_bins {
+            self.stats.pruned += 1;
+            return;
+        }
+
+        let weight = self.problem.weights[self.order[pos]];
+        // одинаковые предметы кладутся в неубывающие номера контейнеров
+        let first_bin = match pos {
+            0 => 0,
+            _ if self.problem.weights[self.order[pos - 1]] == weight => self.floors[pos - 1],
+            _ => 0,
+        };
+        // предмет, дополняющий контейнер до полного, кладётся туда: предметы,
+        // занявшие бы это место в другом решении, весят не больше и
+        // меняются с ним местами. Обмен нарушает порядок одинаковых
+        // предметов, поэтому граница для следующих остаётся прежней.
+        let exact_fit = (first_bin..self.loads.len()).find(|&bin| self.loads[bin] + weight == capacity);
+        if let Some(bin) = exact_fit {
+            self.place(pos, bin, weight);
+            self.floors[pos] = first_bin;
+            self.search(pos + 1);
+            self.unplace(bin, weight);
+            return;
+        }
+        // из контейнеров с одинаковой загрузкой пробуется только первый
+        let mut tried_loads = Vec::new();
+        for bin in first_bin..self.loads.len() {
+            let load = self.loads[bin];
+            if load + weight > capacity || tried_loads.contains(&load) { continue; }
+            tried_loads.push(load);
+            self.place(pos, bin, weight);
+            self.search(pos + 1);
+            self.unplace(bin, weight);
+        }
+
+        // пустых контейнеров открывается не больше одного
+        if self.loads.len() + 1 < self.best_bins {
+            let bin = self.loads.len();
+            self.loads.push(0);
+            self.free_space += capacity;
+            self.place(pos, bin, weight);
+            self.search(pos + 1);
+            self.unplace(bin, weight);
+            self.free_space -= capacity;
+            self.loads.pop();
         }
     }
 }
 
-fn bin_packing(problem: &BBProblem) -> Packing {
-    let lower = lower_bound(problem);
-    let mut best_packing = first_fit(problem);
-    let mut curr_packing = Packing::with_capacity(best_packing.len());
+// Редукция фиксирует доминирующие контейнеры, перебор идёт только по
+// оставшимся предметам; границы и число контейнеров - для всей задачи.
+fn bin_packing(problem: &BBProblem, options: &Options) -> (Packing, SearchStats) {
+    let start_time = Instant::now();
+    let reduction = reduce(problem.capacity, &problem.weights);
+    let residual = BBProblem {
+        capacity: problem.capacity,
+        weights: reduction.residual_weights(&problem.weights),
+    };
+    let (packing, mut stats) = search_residual(&residual, options);
+    stats.fixed_items = reduction.fixed_items();
+    stats.fixed_bins = reduction.fixed.len();
+    stats.start_bins += stats.fixed_bins;
+    stats.lower_bound += stats.fixed_bins;
+    stats.elapsed = start_time.elapsed();
+    (Packing { indices: reduction.merge(&problem.weights, &packing.indices) }, stats)
+}
+
+fn search_residual(problem: &BBProblem, options: &Options) -> (Packing, SearchStats) {
+    let (heuristic, indices) = heuristic_packing(problem.capacity, &problem.weights, options.heuristic);
+    let best = Packing { indices };
+    let mut order: Vec<usize> = (0..problem.weights.len()).collect();
+    order.sort_by_key(|&item_idx| std::cmp::Reverse(problem.weights[item_idx]));
+
+    let mut search = Search {
+        problem,
+        bins: vec![0; order.len()],
+        floors: vec![0; order.len()],
+        order,
+        loads: Vec::new(),
+        weight_left: problem.weights.iter().sum(),
+        free_space: 0,
+        best_bins: best.num_bins(),
+        best,
+        lower_bound: l3_bound(problem.capacity, &problem.weights),
+        stats: SearchStats::default(),
+        node_limit: options.node_limit,
+        scratch: Vec::new(),
+    };
+    search.stats.heuristic = Some(heuristic);
+    search.stats.start_bins = search.best_bins;
+    search.stats.lower_bound = search.lower_bound;
+    search.search(0);
+    search.stats.complete = !search.out_of_nodes();
+    (search.best, search.stats)
+}
+
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<BBProblem, ScanError> {
+    let n: usize = scan.token()?;
+    let capacity: usize = scan.token()?;
+    let weights = scan.tokens(n)?;
+    Ok(BBProblem { capacity, weights })
+}
+
+// данные MiniZinc: n_items, capacity, weight
+fn read_problem_dzn(data: &DznData) -> Result<BBProblem, DznError> {
+    let n = data.usize("n_items")?;
+    let capacity = data.usize("capacity")?;
+    let weights = data.usize_array_of_len("weight", n)?;
+    Ok(BBProblem { capacity, weights })
+}
 
-    bin_packing_impl(lower, &mut curr_packing, &mut best_packing, problem);
+struct Options {
+    dzn: Option<String>,
+    stats: bool,
+    node_limit: Option<u64>,
+    heuristic: Option<Heuristic>, // None - лучшая из всех эвристик
+}
 
-    best_packing
+// --dzn FILE - данные MiniZinc вместо stdin; --node-limit N - остановить
+// перебор после N узлов; --heuristic NAME|all - начальная упаковка;
+// --stats - статистика в stderr
+fn read_options() -> Result<Options, String> {
+    let mut options = Options { dzn: None, stats: false, node_limit: None, heuristic: None };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dzn" => options.dzn = Some(args.next().ok_or("missing value for --dzn")?),
+            "--stats" => options.stats = true,
+            "--node-limit" => {
+                let value = args.next().ok_or("missing value for --node-limit")?;
+                let limit = value.parse().map_err(|_| format!("invalid value '{}' for --node-limit", value))?;
+                options.node_limit = Some(limit);
+            }
+            "--heuristic" => {
+                let value = args.next().ok_or("missing value for --heuristic")?;
+                options.heuristic = match value.as_str() {
+                    "all" => None,
+                    name => Some(name.parse()?),
+                };
+            }
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(options)
 }
 
 fn main() {
-    let stdin = std::io::stdin();
-    let mut scan = Scanner::new(stdin.lock());
+    let options = read_options().unwrap_or_else(|err| {
+        eprintln!("Options error: {}", err);
+        std::process::exit(2)
+    });
 
-    let n: usize = scan.token();
-    let capacity: usize = scan.token();
-    let weights: Vec<usize> = (0..n).map(|_| scan.token()).collect();
-    let problem = BBProblem { capacity, weights };
+    let problem = match &options.dzn {
+        Some(path) => DznData::from_file(path)
+            .and_then(|data| read_problem_dzn(&data))
+            .unwrap_or_else(|err| err.exit()),
+        None => {
+            let stdin = std::io::stdin();
+            let mut scan = Scanner::new(stdin.lock());
+            read_problem(&mut scan).unwrap_or_else(|err| err.exit())
+        }
+    };
+    if problem.capacity == 0 {
+        eprintln!("Input error: bin capacity must be positive");
+        std::process::exit(1)
+    }
+    if let Some(item_idx) = problem.weights.iter().position(|&weight| weight > problem.capacity) {
+        eprintln!("Input error: item {} is heavier than the bin capacity", item_idx + 1);
+        std::process::exit(1)
+    }
 
-    let packing = bin_packing(&problem);
+    let (packing, stats) = bin_packing(&problem, &options);
+    if options.stats { eprintln!("{}", stats); }
+    // если перебор не закончен, оптимум где-то между границей и найденным
+    eprintln!("fixed by reduction: {} items in {} bins", stats.fixed_items, stats.fixed_bins);
+    let bins = packing.num_bins();
+    let lower = if stats.complete { bins } else { stats.lower_bound };
+    eprintln!("bins: {}, lower bound: {}, gap: {}", bins, lower, bins - lower);
     println!("{}", packing);
 }
 
-mod io {
-    use std::io::BufRead;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dopt::rng::Xoshiro256ss;
 
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
+    // веса из небольшого набора: много одинаковых предметов,
+    // одинаковых загрузок и точных дополнений
+    fn random_problem(rng: &mut Xoshiro256ss) -> BBProblem {
+        let capacity = rng.gen_range(1..100);
+        let palette: Vec<usize> = (0..rng.gen_range(1..13)).map(|_| rng.gen_range(0..capacity + 1)).collect();
+        let n_items = rng.gen_range(0..13);
+        let weights = (0..n_items).map(|_| *rng.choose(&palette).unwrap()).collect();
+        BBProblem { capacity, weights }
     }
 
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
+    // Наименьшее число контейнеров перебором порядков: для множества mask
+    // лексикографически наименьшая пара (контейнеров, загрузка последнего)
+    fn brute_force(problem: &BBProblem) -> usize {
+        let n = problem.weights.len();
+        if n == 0 { return 0; }
+        let mut best = vec![(usize::MAX, 0); 1 << n];
+        best[0] = (1, 0);
+        for mask in 0..1usize << n {
+            let (bins, load) = best[mask];
+            for item_idx in (0..n).filter(|item_idx| mask >> item_idx & 1 == 0) {
+                let weight = problem.weights[item_idx];
+                let next = if load + weight <= problem.capacity { (bins, load + weight) } else { (bins + 1, weight) };
+                best[mask | 1 << item_idx] = best[mask | 1 << item_idx].min(next);
             }
         }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
-                }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-            }
+        best[(1 << n) - 1].0
+    }
+
+    // контейнеры нумеруются подряд с 0 и не переполнены
+    fn check_packing(problem: &BBProblem, packing: &Packing) {
+        assert_eq!(packing.indices.len(), problem.weights.len());
+        let mut loads = vec![0; packing.num_bins()];
+        let mut used = vec![false; loads.len()];
+        for (&bin, &weight) in packing.indices.iter().zip(&problem.weights) {
+            loads[bin] += weight;
+            used[bin] = true;
         }
+        assert!(used.iter().all(|&used| used));
+        assert!(loads.iter().all(|&load| load <= problem.capacity));
+    }
+
+    // Перебор без эвристики и нижней границы: старт - по предмету
+    // в контейнере, поэтому все правила отсечения действительно работают.
+    fn exhaustive_search(problem: &BBProblem) -> (Packing, SearchStats) {
+        let mut order: Vec<usize> = (0..problem.weights.len()).collect();
+        order.sort_by_key(|&item_idx| std::cmp::Reverse(problem.weights[item_idx]));
+        let best = Packing { indices: (0..problem.weights.len()).collect() };
+        let mut search = Search {
+            problem,
+            bins: vec![0; order.len()],
+            floors: vec![0; order.len()],
+            order,
+            loads: Vec::new(),
+            weight_left: problem.weights.iter().sum(),
+            free_space: 0,
+            best_bins: best.num_bins(),
+            best,
+            lower_bound: 0,
+            stats: SearchStats::default(),
+            node_limit: None,
+            scratch: Vec::new(),
+        };
+        search.search(0);
+        (search.best, search.stats)
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn search_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(21);
+        let options = Options { dzn: None, stats: false, node_limit: None, heuristic: None };
+        // здесь нужен не первый подходящий контейнер с другой загрузкой
+        let known = BBProblem { capacity: 67, weights: vec![65, 18, 29, 0, 18, 33, 18, 30, 29, 29, 23, 26] };
+        let problems = std::iter::once(known).chain(std::iter::repeat_with(|| random_problem(&mut rng)).take(1000));
+        for problem in problems {
+            let optimum = brute_force(&problem);
+
+            let (packing, _) = exhaustive_search(&problem);
+            check_packing(&problem, &packing);
+            assert_eq!(packing.num_bins(), optimum, "{} {:?}", problem.capacity, problem.weights);
+
+            let (packing, stats) = bin_packing(&problem, &options);
+            check_packing(&problem, &packing);
+            assert_eq!(packing.num_bins(), optimum, "{} {:?}", problem.capacity, problem.weights);
+            assert!(stats.complete && stats.lower_bound <= optimum);
+        }
+    }
+
+    #[test]
+    fn node_limit_keeps_packing_valid() {
+        let mut rng = Xoshiro256ss::new(22);
+        for _ in 0..300 {
+            let problem = random_problem(&mut rng);
+            let optimum = brute_force(&problem);
+            let options = Options { dzn: None, stats: false, node_limit: Some(rng.gen_below(5)), heuristic: None };
+            let (packing, _) = bin_packing(&problem, &options);
+            check_packing(&problem, &packing);
+            assert!(packing.num_bins() >= optimum);
+        }
+    }
+}
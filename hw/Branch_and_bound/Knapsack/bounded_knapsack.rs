@@ -0,0 +1,81 @@
+// Рюкзак с копиями предметов.
+//
+// Вход: вместимость, число предметов, затем для каждого предмета
+// объём, ценность и число копий (inf - сколько угодно).
+// Если все предметы без ограничения - неограниченный рюкзак,
+// иначе ограниченный (бесконечные копии заменяются на вместимость / объём).
+use dopt::io::{Scanner, ScanError};
+use dopt::knapsack::{bounded_knapsack, unbounded_knapsack, Item, MultiKnapsackError, MultiSolution};
+use std::io::BufRead;
+use std::str::FromStr;
+
+// таблица решений больше TABLE_MAX_BYTES - компактная динамика
+const TABLE_MAX_BYTES: usize = 256 << 20;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Copies {
+    Limited(usize),
+    Unlimited,
+}
+
+impl FromStr for Copies {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        match token {
+            "inf" => Ok(Copies::Unlimited),
+            _ => token.parse().map(Copies::Limited),
+        }
+    }
+}
+
+fn read_items<B: BufRead>(scan: &mut Scanner<B>) -> Result<(usize, Vec<Item>, Vec<Copies>), ScanError> {
+    let knapsack_volume: usize = scan.token()?;
+    let num_items: usize = scan.token()?;
+
+    let mut items = Vec::with_capacity(num_items);
+    let mut copies = Vec::with_capacity(num_items);
+    for index in 0..num_items {
+        let (volume, value) = (scan.token()?, scan.token()?);
+        // тяжёлые предметы остаются, чтобы вывести для них 0
+        items.push(Item { volume, value, index });
+        copies.push(scan.token()?);
+    }
+    Ok((knapsack_volume, items, copies))
+}
+
+fn solve(knapsack_volume: usize, items: &[Item], copies: &[Copies]) -> Result<MultiSolution, MultiKnapsackError> {
+    if copies.iter().all(|&c| c == Copies::Unlimited) {
+        return unbounded_knapsack(knapsack_volume, items);
+    }
+
+    let limits: Vec<usize> = items.iter()
+        .zip(copies)
+        .map(|(item, &copies)| match copies {
+            Copies::Limited(count) => count,
+            Copies::Unlimited if item.volume == 0 && item.value > 0 => {
+                eprintln!("Input error: item {} has zero volume, positive value and unlimited copies", item.index + 1);
+                std::process::exit(1)
+            }
+            Copies::Unlimited if item.volume == 0 => 0,
+            Copies::Unlimited => knapsack_volume / item.volume,
+        })
+        .collect();
+    bounded_knapsack(knapsack_volume, items, &limits, TABLE_MAX_BYTES)
+}
+
+fn main() {
+    let stdin = std::io::stdin();
+    let mut scan = Scanner::new(stdin.lock());
+    let (knapsack_volume, items, copies) = read_items(&mut scan).unwrap_or_else(|err| err.exit());
+
+    let solution = solve(knapsack_volume, &items, &copies).unwrap_or_else(|err| {
+        eprintln!("Input error: {}", err);
+        std::process::exit(1)
+    });
+
+    // ценность и объём, затем число копий каждого предмета в порядке ввода
+    println!("{} {}", solution.value, solution.volume);
+    let counts: Vec<String> = solution.counts.iter().map(|count| count.to_string()).collect();
+    println!("{}", counts.join(" "));
+}
@@ -0,0 +1,461 @@
+// Рюкзак с графом конфликтов: предметы, соединённые ребром,
+// нельзя взять вместе.
+//
+// Вход: вместимость, число предметов n, объём и ценность каждого предмета,
+// затем число рёбер конфликтов и сами рёбра - пары номеров предметов (с 1).
+use dopt::graph::UndirectedGraph;
+use dopt::io::{Scanner, ScanError};
+use dopt::knapsack::{density_cmp, ratio_cmp, Item, Solution};
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+
+type ConflictGraph = UndirectedGraph<Item, ()>;
+
+// Жадное покрытие вершин кликами: предмет попадает в первую клику,
+// со всеми предметами которой он в конфликте.
+fn clique_cover(graph: &ConflictGraph, order: &[usize]) -> Vec<Vec<usize>> {
+    let mut cliques: Vec<Vec<usize>> = Vec::new();
+    for &node in order {
+        let clique = cliques.iter_mut()
+            .find(|clique| clique.iter().all(|&other| graph.has_edge(node, other)));
+        match clique {
+            Some(clique) => clique.push(node),
+            None => cliques.push(vec![node]),
+        }
+    }
+    cliques
+}
+
+// Прирост при переходе к следующей точке верхней выпуклой оболочки клики
+#[derive(Clone, Copy)]
+struct Increment {
+    volume: usize,
+    value: usize,
+}
+
+// Точки (объём, ценность) предметов клики вместе с (0, 0); из клики берётся
+// не больше одного предмета, поэтому в LP-релаксации выгодны только
+// точки верхней выпуклой оболочки. Возвращает ценность предметов нулевого
+// объёма и приросты оболочки с убывающей удельной ценностью.
+fn hull_increments(points: &mut [(usize, usize)], increments: &mut Vec<Increment>) -> usize {
+    points.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    let base = points.iter().filter(|point| point.0 == 0).map(|point| point.1).max().unwrap_or(0);
+
+    let mut hull: Vec<(usize, usize)> = vec![(0, base)];
+    for &(volume, value) in points.iter().filter(|point| point.0 > 0) {
+        let &(_, last_value) = hull.last().unwrap();
+        if value <= last_value { continue; }
+        // убираем точки, лежащие не выше отрезка до новой
+        while hull.len() >= 2 {
+            let (v1, p1) = hull[hull.len() - 2];
+            let (v2, p2) = hull[hull.len() - 1];
+            if ratio_cmp((p2 - p1, v2 - v1), (value - p1, volume - v1)) == Ordering::Greater { break; }
+            hull.pop();
+        }
+        hull.push((volume, value));
+    }
+    for pair in hull.windows(2) {
+        increments.push(Increment { volume: pair[1].0 - pair[0].0, value: pair[1].1 - pair[0].1 });
+    }
+    base
+}
+
+#[derive(Default)]
+struct SearchStats {
+    cliques: usize,
+    start_value: usize,
+    explored: u64,
+    pruned: u64,
+    complete: bool,
+    elapsed: Duration,
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cliques: {}, start value: {}, nodes explored: {}, pruned: {}, optimal: {}, time: {} ms",
+            self.cliques, self.start_value, self.explored, self.pruned, self.complete, self.elapsed.as_millis())
+    }
+}
+
+// Ветви и границы по предметам в порядке убывания удельной ценности.
+// blocked[j] - сколько взятых предметов конфликтует с j.
+struct Search<'a> {
+    graph: &'a ConflictGraph,
+    order: Vec<usize>,
+    cliques: Vec<Vec<usize>>,
+    level_of: Vec<usize>, // позиция предмета в order
+    blocked: Vec<usize>,
+    taken: Vec<usize>,
+    volume_left: usize,
+    value: usize,
+    best: (usize, Vec<usize>),
+    stats: SearchStats,
+    node_limit: Option<u64>,
+}
+
+impl Search<'_> {
+    fn item(&self, node: usize) -> &Item {
+        &self.graph[node]
+    }
+
+    fn is_candidate(&self, node: usize, level: usize) -> bool {
+        self.level_of[node] >= level && self.blocked[node] == 0 && self.item(node).volume <= self.volume_left
+    }
+
+    // LP-релаксация с ограничениями "не больше одного из клики" для покрытия
+    // кликами (рёбра вне клик отбрасываются): приросты оболочек всех клик
+    // берутся по убыванию удельной ценности, последний - дробно.
+    fn bound(&self, level: usize) -> usize {
+        let mut bound = self.value;
+        let mut increments = Vec::new();
+        let mut points = Vec::new();
+        for clique in &self.cliques {
+            points.clear();
+            points.extend(clique.iter()
+                .filter(|&&node| self.is_candidate(node, level))
+                .map(|&node| (self.item(node).volume, self.item(node).value)));
+            bound += hull_increments(&mut points, &mut increments);
+        }
+        increments.sort_unstable_by(|a, b| ratio_cmp((b.value, b.volume), (a.value, a.volume)));
+
+        let mut volume_left = self.volume_left;
+        for increment in increments {
+            if increment.volume <= volume_left {
+                volume_left -= increment.volume;
+                bound += increment.value;
+            } else {
+                bound += (volume_left as u128 * increment.value as u128 / increment.volume as u128) as usize;
+                break;
+            }
+        }
+        bound
+    }
+
+    fn set_taken(&mut self, node: usize, taken: bool) {
+        let item = self.graph[node];
+        for &(neighbor, _) in self.graph.neigh(node) {
+            if taken { self.blocked[neighbor] += 1; } else { self.blocked[neighbor] -= 1; }
+        }
+        if taken {
+            self.taken.push(node);
+            self.volume_left -= item.volume;
+            self.value += item.value;
+        } else {
+            self.taken.pop();
+            self.volume_left += item.volume;
+            self.value -= item.value;
+        }
+    }
+
+    fn out_of_nodes(&self) -> bool {
+        self.node_limit.is_some_and(|limit| self.stats.explored >= limit)
+    }
+
+    // Some(level) - узел нужно ветвить по предмету order[level]
+    fn visit(&mut self, level: usize) -> Option<usize> {
+        self.stats.explored += 1;
+        if self.value > self.best.0 {
+            self.best = (self.value, self.taken.clone());
+        }
+        // пропускаем предметы, которые уже нельзя взять
+        let level = (level..self.order.len())
+            .find(|&level| self.is_candidate(self.order[level], level))
+            .unwrap_or(self.order.len());
+        if level == self.order.len() { return None; }
+        if self.bound(level) <= self.best.0 {
+            self.stats.pruned += 1;
+            return None;
+        }
+        Some(level)
+    }
+
+    // Обход в глубину с явным стеком шагов (рекурсия была бы глубины n):
+    // сначала предмет берётся, затем пропускается.
+    fn search(&mut self) {
+        let mut steps = vec![Step::Visit(0)];
+        while let Some(step) = steps.pop() {
+            let level = match step {
+                Step::Visit(level) => level,
+                Step::Take(node) => {
+                    self.set_taken(node, true);
+                    continue;
+                }
+                Step::Untake(node) => {
+                    self.set_taken(node, false);
+                    continue;
+                }
+            };
+            if self.out_of_nodes() { return; }
+            let level = match self.visit(level) {
+                Some(level) => level,
+                None => continue,
+            };
+
+            let node = self.order[level];
+            steps.push(Step::Visit(level + 1));
+            steps.push(Step::Untake(node));
+            steps.push(Step::Visit(level + 1));
+            steps.push(Step::Take(node));
+        }
+    }
+}
+
+// шаг обхода: узел уровня или взятие и возврат предмета
+enum Step {
+    Visit(usize),
+    Take(usize),
+    Untake(usize),
+}
+
+// жадно по order: берём предмет, если он помещается и ни с чем не конфликтует
+fn greedy(graph: &ConflictGraph, capacity: usize, order: &[usize]) -> (usize, Vec<usize>) {
+    let mut blocked = vec![false; order.len()];
+    let mut taken = Vec::new();
+    let (mut volume_left, mut value) = (capacity, 0);
+    for &node in order {
+        let item = &graph[node];
+        if blocked[node] || item.volume > volume_left { continue; }
+        taken.push(node);
+        volume_left -= item.volume;
+        value += item.value;
+        for &(neighbor, _) in graph.neigh(node) {
+            blocked[neighbor] = true;
+        }
+    }
+    (value, taken)
+}
+
+fn conflict_knapsack(
+    graph: &ConflictGraph,
+    capacity: usize,
+    heuristic_only: bool,
+    node_limit: Option<u64>,
+) -> (Solution, SearchStats) {
+    let start_time = Instant::now();
+    let (n_items, _) = graph.size();
+    let mut order: Vec<usize> = (0..n_items).collect();
+    order.sort_by(|&node1, &node2| density_cmp(&graph[node2], &graph[node1]));
+
+    // жадно по удельной ценности и по ценности - лучшее из двух
+    let mut by_value = order.clone();
+    by_value.sort_by_key(|&node| std::cmp::Reverse(graph[node].value));
+    let start = [greedy(graph, capacity, &order), greedy(graph, capacity, &by_value)]
+        .into_iter()
+        .max_by_key(|(value, _)| *value)
+        .unwrap();
+
+    let mut level_of = vec![0; n_items];
+    for (level, &node) in order.iter().enumerate() {
+        level_of[node] = level;
+    }
+    let cliques = clique_cover(graph, &order);
+    let mut search = Search {
+        graph,
+        order,
+        cliques,
+        level_of,
+        blocked: vec![0; n_items],
+        taken: Vec::new(),
+        volume_left: capacity,
+        value: 0,
+        best: start,
+        stats: SearchStats::default(),
+        node_limit,
+    };
+    search.stats.cliques = search.cliques.len();
+    search.stats.start_value = search.best.0;
+    if !heuristic_only {
+        search.search();
+    }
+    search.stats.complete = !heuristic_only && !search.out_of_nodes();
+    search.stats.elapsed = start_time.elapsed();
+
+    let items: Vec<Item> = graph.nodes().map(|(_, item)| *item).collect();
+    (Solution::from_chosen(&items, search.best.1), search.stats)
+}
+
+struct Options {
+    heuristic_only: bool,
+    stats: bool,
+    node_limit: Option<u64>,
+}
+
+// --heuristic - только жадный алгоритм; --node-limit N - остановить
+// перебор после N узлов; --stats - статистика в stderr
+fn read_options() -> Result<Options, String> {
+    let mut options = Options { heuristic_only: false, stats: false, node_limit: None };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--heuristic" => options.heuristic_only = true,
+            "--stats" => options.stats = true,
+            "--node-limit" => {
+                let value = args.next().ok_or("missing value for --node-limit")?;
+                let limit = value.parse().map_err(|_| format!("invalid value '{}' for --node-limit", value))?;
+                options.node_limit = Some(limit);
+            }
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<(usize, ConflictGraph), ScanError> {
+    let knapsack_volume: usize = scan.token()?;
+    let num_items: usize = scan.token()?;
+    let mut items = Vec::with_capacity(num_items);
+    for index in 0..num_items {
+        let (volume, value) = (scan.token()?, scan.token()?);
+        items.push(Item { volume, value, index });
+    }
+
+    // повторные рёбра не дублируются
+    let mut graph = ConflictGraph::from_nodes(items).with_hashed_adjacency();
+    let num_conflicts: usize = scan.token()?;
+    for _ in 0..num_conflicts {
+        let (line, column) = scan.position();
+        let (node1, node2): (usize, usize) = (scan.token()?, scan.token()?);
+        if node1 == 0 || node2 == 0 || node1 > num_items || node2 > num_items {
+            return Err(ScanError::Parse {
+                token: format!("{} {}", node1, node2),
+                expected: "pair of item numbers",
+                line,
+                column,
+            });
+        }
+        // петля ничего не запрещает: предмет берётся не больше одного раза
+        if node1 != node2 {
+            graph.add_edge(node1 - 1, node2 - 1, ());
+        }
+    }
+    Ok((knapsack_volume, graph))
+}
+
+fn main() {
+    let options = read_options().unwrap_or_else(|err| {
+        eprintln!("Options error: {}", err);
+        std::process::exit(2)
+    });
+
+    let stdin = std::io::stdin();
+    let mut scan = Scanner::new(stdin.lock());
+    let (knapsack_volume, graph) = read_problem(&mut scan).unwrap_or_else(|err| err.exit());
+
+    let (solution, stats) = conflict_knapsack(&graph, knapsack_volume, options.heuristic_only, options.node_limit);
+    if options.stats { eprintln!("{}", stats); }
+
+    // ценность и объём, затем номера выбранных предметов (с 1) в порядке ввода
+    println!("{} {}", solution.value, solution.volume);
+    let chosen: Vec<String> = solution.items.iter().map(|index| (index + 1).to_string()).collect();
+    println!("{}", chosen.join(" "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dopt::rng::Xoshiro256ss;
+
+    // есть предметы нулевого объёма и нулевой ценности
+    fn random_problem(rng: &mut Xoshiro256ss) -> (usize, ConflictGraph) {
+        let num_items = rng.gen_range(0..12);
+        let items = (0..num_items)
+            .map(|index| Item { volume: rng.gen_range(0..10), value: rng.gen_range(0..10), index })
+            .collect();
+        let mut graph = ConflictGraph::from_nodes(items).with_hashed_adjacency();
+        let density = rng.gen_f64();
+        for node1 in 0..num_items {
+            for node2 in node1 + 1..num_items {
+                if rng.gen_bool(density) { graph.add_edge(node1, node2, ()); }
+            }
+        }
+        (rng.gen_range(0..30), graph)
+    }
+
+    fn brute_force(capacity: usize, graph: &ConflictGraph) -> usize {
+        let (n_items, _) = graph.size();
+        (0..1usize << n_items)
+            .filter(|mask| graph.edges().all(|(node1, node2, _)| mask >> node1 & mask >> node2 & 1 == 0))
+            .filter_map(|mask| {
+                let chosen = (0..n_items).filter(|node| mask >> node & 1 == 1);
+                let volume: usize = chosen.clone().map(|node| graph[node].volume).sum();
+                (volume <= capacity).then(|| chosen.map(|node| graph[node].value).sum())
+            })
+            .max()
+            .unwrap()
+    }
+
+    fn check_solution(capacity: usize, graph: &ConflictGraph, solution: &Solution) {
+        assert!(solution.volume <= capacity);
+        assert_eq!(solution.volume, solution.items.iter().map(|&node| graph[node].volume).sum::<usize>());
+        assert_eq!(solution.value, solution.items.iter().map(|&node| graph[node].value).sum::<usize>());
+        for (pos, &node1) in solution.items.iter().enumerate() {
+            for &node2 in &solution.items[pos + 1..] {
+                assert!(!graph.has_edge(node1, node2), "items {} and {} conflict", node1, node2);
+            }
+        }
+    }
+
+    #[test]
+    fn search_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(18);
+        for _ in 0..500 {
+            let (capacity, graph) = random_problem(&mut rng);
+            let optimum = brute_force(capacity, &graph);
+            let (solution, stats) = conflict_knapsack(&graph, capacity, false, None);
+            check_solution(capacity, &graph, &solution);
+            assert_eq!(solution.value, optimum);
+            assert!(stats.complete);
+
+            let (solution, _) = conflict_knapsack(&graph, capacity, true, None);
+            check_solution(capacity, &graph, &solution);
+            assert!(solution.value <= optimum);
+        }
+    }
+
+    // граница по оболочкам клик не меньше оптимума в корне
+    #[test]
+    fn clique_hull_bound_is_upper_bound() {
+        let mut rng = Xoshiro256ss::new(19);
+        for _ in 0..500 {
+            let (capacity, graph) = random_problem(&mut rng);
+            let (n_items, _) = graph.size();
+            let mut order: Vec<usize> = (0..n_items).collect();
+            order.sort_by(|&node1, &node2| density_cmp(&graph[node2], &graph[node1]));
+            let mut level_of = vec![0; n_items];
+            for (level, &node) in order.iter().enumerate() {
+                level_of[node] = level;
+            }
+            let search = Search {
+                graph: &graph,
+                cliques: clique_cover(&graph, &order),
+                order,
+                level_of,
+                blocked: vec![0; n_items],
+                taken: Vec::new(),
+                volume_left: capacity,
+                value: 0,
+                best: (0, Vec::new()),
+                stats: SearchStats::default(),
+                node_limit: None,
+            };
+            for clique in &search.cliques {
+                for (pos, &node1) in clique.iter().enumerate() {
+                    assert!(clique[pos + 1..].iter().all(|&node2| graph.has_edge(node1, node2)));
+                }
+            }
+            assert!(search.bound(0) >= brute_force(capacity, &graph));
+        }
+    }
+
+    #[test]
+    fn hull_keeps_upper_points() {
+        // (0, 2) - база, (2, 1) и (3, 3) под оболочкой (0, 2) - (4, 8)
+        let mut points = vec![(3, 3), (4, 8), (0, 2), (2, 1), (6, 9), (6, 5), (0, 1)];
+        let mut increments = Vec::new();
+        assert_eq!(hull_increments(&mut points, &mut increments), 2);
+        let increments: Vec<(usize, usize)> = increments.iter().map(|inc| (inc.volume, inc.value)).collect();
+        assert_eq!(increments, vec![(4, 6), (2, 1)]);
+    }
+}
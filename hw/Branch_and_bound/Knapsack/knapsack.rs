@@ -1,116 +1,863 @@
-use std::collections::HashMap;
+use dopt::dzn::{DznData, DznError};
+use dopt::io::{Scanner, ScanError};
+use dopt::knapsack::{
+    decision_table_bytes, density_cmp, dp_by_capacity, dp_by_capacity_compact, dp_by_value, dp_by_value_compact, Item,
+    Solution,
+};
+use dopt::numeric::{Rational, Value};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{self, Display};
+use std::io::BufRead;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-type Item = (usize, usize);
-type UpperBoundMem = HashMap<(usize, usize), usize>;
+// (уровень, оставшийся объём) -> наибольшая ценность, с которой дошли до этого состояния
+type UpperBoundMem<V> = HashMap<(usize, usize), V>;
 
-fn calc_bounds(knapsack_volume: usize, sorted_items: &[Item]) -> (usize, usize) {
+// предел записей в памяти доминирования
+const MEMO_MAX_ENTRIES: usize = 1 << 20;
+
+// динамика выбирается, если в таблице не больше DP_MAX_CELLS клеток;
+// таблица решений больше TABLE_MAX_BYTES - компактный вариант
+const DP_MAX_CELLS: usize = 500_000_000;
+const TABLE_MAX_BYTES: usize = 256 << 20;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Algorithm {
+    Auto,
+    BranchAndBound,
+    DpByCapacity,
+    DpByValue,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "auto" => Ok(Algorithm::Auto),
+            "bb" => Ok(Algorithm::BranchAndBound),
+            "dp-capacity" => Ok(Algorithm::DpByCapacity),
+            "dp-value" => Ok(Algorithm::DpByValue),
+            _ => Err(format!("unknown algorithm '{}', expected auto, bb, dp-capacity or dp-value", name)),
+        }
+    }
+}
+
+// Тип ценностей: целые до 2^64, до 2^128 или точные дроби
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ValueType {
+    U64,
+    U128,
+    Rational,
+}
+
+impl FromStr for ValueType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "u64" => Ok(ValueType::U64),
+            "u128" => Ok(ValueType::U128),
+            "rational" => Ok(ValueType::Rational),
+            _ => Err(format!("unknown value type '{}', expected u64, u128 or rational", name)),
+        }
+    }
+}
+
+struct Options {
+    algorithm: Algorithm,
+    order: SearchOrder,
+    stats: bool,
+    dzn: Option<String>,
+    values: ValueType,
+}
+
+// --algorithm NAME (или KNAPSACK_ALGORITHM), по умолчанию auto;
+// --search dfs|best|hybrid - обход для B&B, --stats - статистика в stderr;
+// --dzn FILE - данные MiniZinc вместо stdin;
+// --values u64|u128|rational - тип ценностей, по умолчанию u64
+fn read_options() -> Result<Options, String> {
+    let mut options = Options {
+        algorithm: Algorithm::Auto,
+        order: SearchOrder::DepthFirst,
+        stats: false,
+        dzn: None,
+        values: ValueType::U64,
+    };
+    if let Ok(name) = std::env::var("KNAPSACK_ALGORITHM") {
+        options.algorithm = name.parse()?;
+    }
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--algorithm" => options.algorithm = args.next().ok_or("missing value for --algorithm")?.parse()?,
+            "--search" => options.order = args.next().ok_or("missing value for --search")?.parse()?,
+            "--stats" => options.stats = true,
+            "--dzn" => options.dzn = Some(args.next().ok_or("missing value for --dzn")?),
+            "--values" => options.values = args.next().ok_or("missing value for --values")?.parse()?,
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+// динамика с меньшей таблицей, если она помещается в DP_MAX_CELLS, иначе B&B;
+// динамика по ценности - только если все ценности целые (целой суммы мало)
+fn choose_algorithm<V: Value>(knapsack_volume: usize, items: &[Item<V>], total_value: V) -> Algorithm {
+    let capacity_cells = items.len().saturating_mul(knapsack_volume.saturating_add(1));
+    let value_cells = match (total_value.to_usize(), dp_by_value_items(items)) {
+        (Some(total_value), Some(_)) => items.len().saturating_mul(total_value.saturating_add(1)),
+        _ => usize::MAX,
+    };
+    if capacity_cells.min(value_cells) > DP_MAX_CELLS {
+        Algorithm::BranchAndBound
+    } else if capacity_cells <= value_cells {
+        Algorithm::DpByCapacity
+    } else {
+        Algorithm::DpByValue
+    }
+}
+
+fn calc_bounds<V: Value>(knapsack_volume: usize, sorted_items: &[Item<V>]) -> (V, V) {
     let mut curr_volume = knapsack_volume;
-    let mut lower_bound: usize = 0;
+    let mut lower_bound = V::default();
     let mut upper_bound = None;
 
-    for (volume, value) in sorted_items.iter() {
-        if *volume <= curr_volume {
-            curr_volume -= *volume;
-            lower_bound += *value;
-        } else {
-            if lower_bound != 0 && upper_bound == None {
-                let item_density = *value as f64 / *volume as f64;
-                let last_part_value = (item_density * curr_volume as f64).ceil() as usize;
-                upper_bound = Some(lower_bound + last_part_value);
-            }
+    for item in sorted_items.iter() {
+        if item.volume <= curr_volume {
+            curr_volume -= item.volume;
+            lower_bound = lower_bound + item.value;
+        } else if upper_bound.is_none() {
+            // граница Данцига: первый не поместившийся предмет берём дробно,
+            // даже если до него ничего не поместилось; если доля не
+            // представима - целиком, граница остаётся верной
+            let last_part = item.value.fraction_ceil(curr_volume, item.volume);
+            upper_bound = Some(last_part.and_then(|part| lower_bound.checked_add(part))
+                .unwrap_or(lower_bound + item.value));
         }
     }
     (lower_bound, upper_bound.unwrap_or(lower_bound))
 }
 
-fn knapsack_impl(
-    current_value: usize,
+// позиции предметов, которые жадно берёт calc_bounds
+fn greedy_fill<V>(knapsack_volume: usize, sorted_items: &[Item<V>]) -> Vec<usize> {
+    let mut curr_volume = knapsack_volume;
+    let mut taken = Vec::new();
+    for (pos, item) in sorted_items.iter().enumerate() {
+        if item.volume <= curr_volume {
+            curr_volume -= item.volume;
+            taken.push(pos);
+        }
+    }
+    taken
+}
+
+// Порядок обхода дерева ветвей и границ
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SearchOrder {
+    DepthFirst,
+    BestFirst, // узел с наибольшей верхней границей
+    Hybrid,    // лучший узел из очереди, от него спуск в глубину по "берём"
+}
+
+impl FromStr for SearchOrder {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "dfs" => Ok(SearchOrder::DepthFirst),
+            "best" => Ok(SearchOrder::BestFirst),
+            "hybrid" => Ok(SearchOrder::Hybrid),
+            _ => Err(format!("unknown search order '{}', expected dfs, best or hybrid", name)),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct SearchStats {
+    explored: usize,
+    pruned: usize,
+    max_depth: usize,
+    fixed: usize, // переменные, зафиксированные редукцией
+    dominated: usize,
+    elapsed: Duration,
+}
+
+impl SearchStats {
+    fn add(&mut self, other: SearchStats) {
+        self.explored += other.explored;
+        self.pruned += other.pruned;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.dominated += other.dominated;
+    }
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nodes explored: {}, pruned: {} (dominated: {}), max depth: {}, fixed variables: {}, time: {} ms",
+            self.explored, self.pruned, self.dominated, self.max_depth, self.fixed, self.elapsed.as_millis())
+    }
+}
+
+// Узел дерева: предметы до level решены, taken - последний взятый
+// предмет в цепочке TakenChain (None - ничего не взято).
+#[derive(Clone, Copy)]
+struct Node<V> {
+    level: usize,
+    volume_left: usize,
+    value: V,
+    upper_bound: V,
+    taken: Option<usize>,
+}
+
+// в очереди best-first сначала большая граница, затем более глубокий узел
+impl<V: Ord> Ord for Node<V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.upper_bound.cmp(&other.upper_bound).then(self.level.cmp(&other.level))
+    }
+}
+
+impl<V: Ord> PartialOrd for Node<V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V: Ord> PartialEq for Node<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<V: Ord> Eq for Node<V> {}
+
+// Взятые предметы всех узлов: (позиция предмета, предыдущее звено).
+// Узлы делят общие префиксы, поэтому путь не копируется.
+#[derive(Default)]
+struct TakenChain {
+    links: Vec<(usize, Option<usize>)>,
+}
+
+impl TakenChain {
+    fn push(&mut self, pos: usize, prev: Option<usize>) -> Option<usize> {
+        self.links.push((pos, prev));
+        Some(self.links.len() - 1)
+    }
+
+    fn positions(&self, mut link: Option<usize>) -> Vec<usize> {
+        let mut positions = Vec::new();
+        while let Some(idx) = link {
+            let (pos, prev) = self.links[idx];
+            positions.push(pos);
+            link = prev;
+        }
+        positions
+    }
+}
+
+// Граница Мартелло-Тота U2 = max(U0, U1) для задачи, в которой жадно
+// взяты предметы на greedy_value, осталось volume_rest места, critical -
+// первый не поместившийся предмет, prev - последний взятый перед ним,
+// next - следующий после него.
+// U0: critical не берём, next берём дробно.
+// U1: critical берём, освобождая место дробным удалением prev.
+// Если доля не представима, U0 заменяется на greedy_value + ценность
+// critical: она не меньше границы Данцига, а значит и U2.
+fn u2_bound<V: Value>(
+    greedy_value: V,
+    volume_rest: usize,
+    critical: Option<&Item<V>>,
+    prev: Option<&Item<V>>,
+    next: Option<&Item<V>>,
+) -> V {
+    let critical = match critical {
+        Some(item) => item,
+        None => return greedy_value,
+    };
+    let with_critical = greedy_value + critical.value;
+
+    let u0 = match next {
+        Some(item) => item.value.fraction_floor(volume_rest, item.volume)
+            .and_then(|part| greedy_value.checked_add(part)),
+        None => Some(greedy_value),
+    };
+    let u1 = match prev {
+        // у предмета нулевого объёма нет удельной ценности - берём границу Данцига
+        Some(item) if item.volume == 0 => critical.value.fraction_floor(volume_rest, critical.volume)
+            .and_then(|part| greedy_value.checked_add(part)),
+        Some(item) => item.value.fraction_ceil(critical.volume - volume_rest, item.volume)
+            .and_then(|removed| with_critical.checked_sub(removed)),
+        // перед critical ничего нет, и он сам не помещается
+        None => None,
+    };
+    match (u0, u1) {
+        (Some(u0), u1) => u1.map_or(u0, |u1| u0.max(u1)),
+        (None, _) => with_critical,
+    }
+}
+
+// Префиксные суммы объёмов и ценностей: граница Данцига для узла
+// считается бинарным поиском критического предмета за O(log n).
+struct PrefixBounds<'a, V> {
+    items: &'a [Item<V>],
+    volumes: Vec<usize>,
+    values: Vec<V>,
+}
+
+impl<'a, V: Value> PrefixBounds<'a, V> {
+    fn new(items: &'a [Item<V>]) -> Self {
+        let mut volumes = vec![0; items.len() + 1];
+        let mut values = vec![V::default(); items.len() + 1];
+        for (pos, item) in items.iter().enumerate() {
+            volumes[pos + 1] = volumes[pos] + item.volume;
+            values[pos + 1] = values[pos] + item.value;
+        }
+        Self { items, volumes, values }
+    }
+
+    // (критическая позиция, ценность предметов level..critical, граница U2
+    // вместе с ценностью узла node_value): предметы level..critical
+    // помещаются целиком, critical - уже нет
+    fn bounds(&self, level: usize, volume_left: usize, node_value: V) -> (usize, V, V) {
+        let base_volume = self.volumes[level];
+        let critical = level + self.volumes[level + 1..]
+            .partition_point(|&volume| volume - base_volume <= volume_left);
+        let greedy_value = self.values[critical] - self.values[level];
+        let volume_rest = volume_left - (self.volumes[critical] - base_volume);
+        let prev = if critical > level { self.items.get(critical - 1) } else { None };
+        let upper_bound = u2_bound(
+            node_value + greedy_value,
+            volume_rest,
+            self.items.get(critical),
+            prev,
+            self.items.get(critical + 1),
+        );
+        (critical, greedy_value, upper_bound)
+    }
+}
+
+// Узел доминирован, если в то же состояние (уровень, оставшийся объём)
+// уже пришли с не меньшей ценностью: поддеревья одинаковы, и лучшее
+// из них даёт уже встреченный узел. При переполнении вытесняются
+// глубокие записи - их поддеревья меньше всего.
+struct DominanceMemo<V> {
+    best: UpperBoundMem<V>,
+    max_entries: usize,
+}
+
+impl<V: Value> DominanceMemo<V> {
+    fn new(max_entries: usize) -> Self {
+        Self { best: UpperBoundMem::new(), max_entries }
+    }
+
+    // true, если узел доминирован; иначе запоминает его
+    fn dominated(&mut self, node: &Node<V>) -> bool {
+        let key = (node.level, node.volume_left);
+        if let Some(&value) = self.best.get(&key) {
+            if value >= node.value { return true; }
+        } else if self.best.len() >= self.max_entries {
+            self.evict();
+        }
+        self.best.insert(key, node.value);
+        false
+    }
+
+    // оставляет не больше половины записей, начиная с ближайших к корню уровней
+    fn evict(&mut self) {
+        let max_level = self.best.keys().map(|&(level, _)| level).max().unwrap_or(0);
+        let mut per_level = vec![0; max_level + 1];
+        for &(level, _) in self.best.keys() {
+            per_level[level] += 1;
+        }
+        let mut kept = 0;
+        let mut level_limit = 0;
+        while level_limit <= max_level && kept + per_level[level_limit] <= self.max_entries / 2 {
+            kept += per_level[level_limit];
+            level_limit += 1;
+        }
+        self.best.retain(|&(level, _), _| level < level_limit);
+    }
+}
+
+// лучшее решение: цепочка взятых предметов и жадный отрезок позиций
+struct Incumbent<V> {
+    value: V,
+    taken: Option<usize>,
+    greedy: Range<usize>,
+}
+
+// Ветви и границы; items отсортированы по убыванию удельной ценности.
+// Узлы с границей не больше cutoff отсекаются: решение ценностью
+// cutoff уже известно вызывающему.
+fn branch_and_bound<V: Value>(
     knapsack_volume: usize,
-    items: &[Item],
-    max_value: &mut usize
-) -> usize {
-    if items.len() == 0 { return 0; }
-
-    let (lower_bound, upper_bound) = calc_bounds(knapsack_volume, &items);
-    *max_value = std::cmp::max(*max_value, current_value + lower_bound);
-    if current_value + upper_bound <= *max_value { return 0; }
-
-    let min_volume_addition = items.iter().map(|x| x.0).min().unwrap();
-    if knapsack_volume < min_volume_addition { return 0; }
-
-    let (volume, value) = items[0];
-    let result = if volume <= knapsack_volume {
-        std::cmp::max(
-            value + knapsack_impl(current_value + value, knapsack_volume - volume, &items[1..], max_value),
-            knapsack_impl(current_value, knapsack_volume, &items[1..], max_value)
-        )
-    } else {
-        knapsack_impl(current_value, knapsack_volume, &items[1..], max_value)
+    items: &[Item<V>],
+    order: SearchOrder,
+    cutoff: V,
+) -> (Solution<V>, SearchStats) {
+    let start_time = Instant::now();
+    let prefix = PrefixBounds::new(items);
+    let mut chain = TakenChain::default();
+    let mut stats = SearchStats::default();
+    let mut memo = DominanceMemo::new(MEMO_MAX_ENTRIES);
+
+    let (greedy_value, root_bound) = calc_bounds(knapsack_volume, items);
+    let mut best = Incumbent { value: greedy_value, taken: None, greedy: 0..0 };
+    for pos in greedy_fill(knapsack_volume, items) {
+        best.taken = chain.push(pos, best.taken);
+    }
+
+    let root = Node { level: 0, volume_left: knapsack_volume, value: V::default(), upper_bound: root_bound, taken: None };
+    let mut stack = Vec::new();
+    let mut queue = BinaryHeap::new();
+    match order {
+        SearchOrder::DepthFirst => stack.push(root),
+        _ => queue.push(root),
+    }
+    // в гибридном обходе следующий узел спуска
+    let mut dive: Option<Node<V>> = None;
+
+    loop {
+        let node = match order {
+            SearchOrder::DepthFirst => stack.pop(),
+            SearchOrder::BestFirst => queue.pop(),
+            SearchOrder::Hybrid => dive.take().or_else(|| queue.pop()),
+        };
+        let node = match node {
+            Some(node) => node,
+            None => break,
+        };
+        if node.upper_bound <= best.value.max(cutoff) {
+            stats.pruned += 1;
+            continue;
+        }
+        if memo.dominated(&node) {
+            stats.pruned += 1;
+            stats.dominated += 1;
+            continue;
+        }
+        stats.explored += 1;
+        stats.max_depth = stats.max_depth.max(node.level);
+
+        let (critical, greedy_value, upper_bound) = prefix.bounds(node.level, node.volume_left, node.value);
+        if node.value + greedy_value > best.value {
+            best = Incumbent { value: node.value + greedy_value, taken: node.taken, greedy: node.level..critical };
+        }
+        if upper_bound <= best.value.max(cutoff) || node.level == items.len() {
+            stats.pruned += 1;
+            continue;
+        }
+
+        let item = items[node.level];
+        let skip = Node { level: node.level + 1, upper_bound, ..node };
+        let take = (item.volume <= node.volume_left).then(|| Node {
+            level: node.level + 1,
+            volume_left: node.volume_left - item.volume,
+            value: node.value + item.value,
+            upper_bound,
+            taken: chain.push(node.level, node.taken),
+        });
+
+        match order {
+            SearchOrder::DepthFirst => {
+                stack.push(skip);
+                stack.extend(take);
+            }
+            SearchOrder::BestFirst => {
+                queue.push(skip);
+                queue.extend(take);
+            }
+            SearchOrder::Hybrid => {
+                queue.push(skip);
+                dive = take;
+            }
+        }
+    }
+    stats.elapsed = start_time.elapsed();
+
+    let mut chosen = chain.positions(best.taken);
+    chosen.extend(best.greedy);
+    (Solution::from_chosen(items, chosen), stats)
+}
+
+// позиция первого не поместившегося при жадном наборе предмета
+fn critical_position<V>(knapsack_volume: usize, items: &[Item<V>]) -> usize {
+    let mut volume_left = knapsack_volume;
+    items.iter()
+        .position(|item| match volume_left.checked_sub(item.volume) {
+            Some(rest) => { volume_left = rest; false }
+            None => true,
+        })
+        .unwrap_or(items.len())
+}
+
+// U2 для всех предметов, кроме skip, при вместимости knapsack_volume,
+// вместе с уже набранной ценностью base
+fn u2_without<V: Value>(knapsack_volume: usize, items: &[Item<V>], skip: usize, base: V) -> V {
+    let mut rest = items.iter().enumerate().filter(|(pos, _)| *pos != skip).map(|(_, item)| item);
+    let mut volume_rest = knapsack_volume;
+    let mut greedy_value = base;
+    let mut prev = None;
+    for item in rest.by_ref() {
+        if item.volume > volume_rest {
+            return u2_bound(greedy_value, volume_rest, Some(item), prev, rest.next());
+        }
+        volume_rest -= item.volume;
+        greedy_value = greedy_value + item.value;
+        prev = Some(item);
+    }
+    greedy_value
+}
+
+// Редукция Мартелло-Тота: x_j = 1 (или 0), если без j (или с j) граница U2
+// не превосходит известного решения lower_bound. Любое решение лучше
+// lower_bound согласовано со всеми фиксациями сразу.
+// Возвращает Some(фиксированное значение) для каждого предмета.
+fn reduce<V: Value>(knapsack_volume: usize, items: &[Item<V>], lower_bound: V) -> Vec<Option<bool>> {
+    let critical = critical_position(knapsack_volume, items);
+    items.iter()
+        .enumerate()
+        .map(|(pos, item)| {
+            if pos < critical {
+                let bound_without = u2_without(knapsack_volume, items, pos, V::default());
+                (bound_without <= lower_bound).then_some(true)
+            } else {
+                let bound_with = u2_without(knapsack_volume - item.volume, items, pos, item.value);
+                (bound_with <= lower_bound).then_some(false)
+            }
+        })
+        .collect()
+}
+
+// ядро - предметы около критического, их не меньше CORE_MIN_HALF_WIDTH с каждой стороны
+const CORE_MIN_HALF_WIDTH: usize = 25;
+
+// Схема Мартелло-Тота: решаем ядро (предметы до него взяты, после - нет),
+// с этим решением фиксируем переменные редукцией и точно решаем
+// оставшиеся свободные предметы.
+// items отсортированы по убыванию удельной ценности.
+fn knapsack<V: Value>(knapsack_volume: usize, items: &[Item<V>], order: SearchOrder) -> (Solution<V>, SearchStats) {
+    let start_time = Instant::now();
+    let mut stats = SearchStats::default();
+
+    let critical = critical_position(knapsack_volume, items);
+    let half_width = CORE_MIN_HALF_WIDTH.max((items.len() as f64).sqrt() as usize);
+    let core = critical.saturating_sub(half_width)..(critical + half_width).min(items.len());
+    let head_volume: usize = items[..core.start].iter().map(|item| item.volume).sum();
+    let (core_solution, core_stats) =
+        branch_and_bound(knapsack_volume - head_volume, &items[core.clone()], order, V::default());
+    stats.add(core_stats);
+    let incumbent = core_solution.with_items(&items[..core.start]);
+
+    let fixed = reduce(knapsack_volume, items, incumbent.value);
+    stats.fixed = fixed.iter().filter(|x| x.is_some()).count();
+
+    let fixed_in: Vec<Item<V>> = items.iter().zip(fixed.iter()).filter(|(_, x)| **x == Some(true)).map(|(item, _)| *item).collect();
+    let free: Vec<Item<V>> = items.iter().zip(fixed.iter()).filter(|(_, x)| x.is_none()).map(|(item, _)| *item).collect();
+    let fixed_volume: usize = fixed_in.iter().map(|item| item.volume).sum();
+    let fixed_value = fixed_in.iter().fold(V::default(), |value, item| value + item.value);
+
+    // если фиксированные не помещаются, лучше incumbent решений нет
+    let solution = match knapsack_volume.checked_sub(fixed_volume) {
+        Some(free_volume) => {
+            let cutoff = incumbent.value.checked_sub(fixed_value).unwrap_or_default();
+            let (free_solution, free_stats) = branch_and_bound(free_volume, &free, order, cutoff);
+            stats.add(free_stats);
+            if free_solution.value + fixed_value > incumbent.value {
+                free_solution.with_items(&fixed_in)
+            } else {
+                incumbent
+            }
+        }
+        None => incumbent,
     };
+    stats.elapsed = start_time.elapsed();
+    (solution, stats)
+}
 
-    if result > *max_value { *max_value = result; }
-    result
+fn read_items<V: Value, B: BufRead>(scan: &mut Scanner<B>) -> Result<(usize, Vec<Item<V>>), ScanError> {
+    let knapsack_volume: usize = scan.token()?;
+    let num_items: usize = scan.token()?;
+
+    let mut items = Vec::with_capacity(num_items);
+    for index in 0..num_items {
+        let volume = scan.token()?;
+        let value = scan.token()?;
+        if volume <= knapsack_volume {
+            items.push(Item { volume, value, index });
+        }
+    }
+    Ok((knapsack_volume, items))
 }
 
-fn knapsack(knapsack_volume: usize, items: &[Item]) -> usize {
-    let (mut max_value, _) = calc_bounds(knapsack_volume, &items);
-    knapsack_impl(0, knapsack_volume, &items, &mut max_value);
-    max_value
+// параметры модели sem/4/knapsack/knapsack.mzn: n_items, weight_limit, weight, value
+fn read_items_dzn<V: Value>(data: &DznData) -> Result<(usize, Vec<Item<V>>), DznError> {
+    let knapsack_volume = data.usize("weight_limit")?;
+    let num_items = data.usize("n_items")?;
+    let volumes = data.usize_array_of_len("weight", num_items)?;
+    // ценности разбираются как из stdin: для --values rational годятся и дробные
+    let values: Vec<V> = data.parsed_array_of_len("value", num_items)?;
+
+    let items = volumes.into_iter()
+        .zip(values)
+        .enumerate()
+        .filter(|&(_, (volume, _))| volume <= knapsack_volume)
+        .map(|(index, (volume, value))| Item { volume, value, index })
+        .collect();
+    Ok((knapsack_volume, items))
 }
 
-fn main() {
-    let stdin = std::io::stdin();
-    let mut scan = io::Scanner::new(stdin.lock());
+fn input_error(message: &str) -> ! {
+    eprintln!("Input error: {}", message);
+    std::process::exit(1)
+}
 
-    let knapsack_volume: usize = scan.token();
-    let num_items: usize = scan.token();
+// Динамика по ценности индексирует таблицу ценностями: копия предметов
+// с целыми ценностями, index - позиция в items
+fn dp_by_value_items<V: Value>(items: &[Item<V>]) -> Option<Vec<Item>> {
+    items.iter()
+        .enumerate()
+        .map(|(pos, item)| item.value.to_usize().map(|value| Item { volume: item.volume, value, index: pos }))
+        .collect()
+}
 
-    let mut items: Vec<Item> = Vec::with_capacity(num_items);
-    for _ in 0..num_items {
-        let item: Item = (scan.token(), scan.token());
-        if item.0 <= knapsack_volume {
-            items.push(item);
+fn solve<V: Value>(options: &Options) {
+    let (knapsack_volume, items): (usize, Vec<Item<V>>) = match &options.dzn {
+        Some(path) => DznData::from_file(path)
+            .and_then(|data| read_items_dzn(&data))
+            .unwrap_or_else(|err| err.exit()),
+        None => {
+            let stdin = std::io::stdin();
+            let mut scan = Scanner::new(stdin.lock());
+            read_items(&mut scan).unwrap_or_else(|err| err.exit())
         }
+    };
+
+    // суммы любых подмножеств не должны переполняться
+    let total_value = V::checked_total(items.iter().map(|item| item.value)).unwrap_or_else(|| {
+        input_error("total value overflows the value type, try --values u128 or --values rational")
+    });
+    if items.iter().try_fold(0usize, |volume, item| volume.checked_add(item.volume)).is_none() {
+        input_error("total volume of items overflows usize");
     }
 
-    items.sort_unstable_by(|(w1, v1), (w2, v2)| {
-        let density1 = *v1 as f64 / *w1 as f64;
-        let density2 = *v2 as f64 / *w2 as f64;
-        density2.partial_cmp(&density1).unwrap()
+    // предметы нулевого объёма берутся всегда, нулевой ценности - никогда
+    let (free, mut items): (Vec<Item<V>>, Vec<Item<V>>) = items.into_iter()
+        .filter(|item| !item.value.is_zero())
+        .partition(|item| item.volume == 0);
+
+    let algorithm = match options.algorithm {
+        Algorithm::Auto => choose_algorithm(knapsack_volume, &items, total_value),
+        algorithm => algorithm,
+    };
+    let solution = match algorithm {
+        Algorithm::DpByCapacity if decision_table_bytes(items.len(), knapsack_volume + 1) > TABLE_MAX_BYTES =>
+            dp_by_capacity_compact(knapsack_volume, &items),
+        Algorithm::DpByCapacity => dp_by_capacity(knapsack_volume, &items),
+        Algorithm::DpByValue => {
+            let value_items = dp_by_value_items(&items)
+                .unwrap_or_else(|| input_error("dp-value needs integer values that fit in usize"));
+            let total_value: usize = value_items.iter().map(|item| item.value).sum();
+            let solution = if decision_table_bytes(items.len(), total_value + 1) > TABLE_MAX_BYTES {
+                dp_by_value_compact(knapsack_volume, &value_items)
+            } else {
+                dp_by_value(knapsack_volume, &value_items)
+            };
+            Solution::from_chosen(&items, solution.items)
+        }
+        _ => {
+            items.sort_unstable_by(|item1, item2| density_cmp(item2, item1));
+            let (solution, stats) = knapsack(knapsack_volume, &items, options.order);
+            if options.stats { eprintln!("{}", stats); }
+            solution
+        }
+    };
+    let solution = solution.with_items(&free);
+
+    // ценность и объём, затем номера выбранных предметов (с 1) в порядке ввода
+    println!("{} {}", solution.value, solution.volume);
+    let chosen: Vec<String> = solution.items.iter().map(|index| (index + 1).to_string()).collect();
+    println!("{}", chosen.join(" "));
+}
+
+fn main() {
+    let options = read_options().unwrap_or_else(|err| {
+        eprintln!("Options error: {}", err);
+        std::process::exit(2)
     });
 
-    println!("{}", knapsack(knapsack_volume, &items));
+    match options.values {
+        ValueType::U64 => solve::<u64>(&options),
+        ValueType::U128 => solve::<u128>(&options),
+        ValueType::Rational => solve::<Rational>(&options),
+    }
 }
 
-mod io {
-    use std::io::BufRead;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dopt::knapsack::dp_by_capacity;
+    use dopt::rng::Xoshiro256ss;
+
+    const ORDERS: [SearchOrder; 3] = [SearchOrder::DepthFirst, SearchOrder::BestFirst, SearchOrder::Hybrid];
+
+    // предметы с положительными объёмом и ценностью, по убыванию удельной ценности
+    fn random_items<V: Value>(
+        rng: &mut Xoshiro256ss,
+        num_items: usize,
+        knapsack_volume: usize,
+        mut value: impl FnMut(&mut Xoshiro256ss, usize) -> V,
+    ) -> Vec<Item<V>> {
+        let mut items: Vec<Item<V>> = (0..num_items)
+            .map(|index| {
+                let volume = rng.gen_range(1..knapsack_volume + 1);
+                Item { volume, value: value(rng, volume), index }
+            })
+            .collect();
+        items.sort_unstable_by(|item1, item2| density_cmp(item2, item1));
+        items
+    }
 
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
+    fn brute_force<V: Value>(knapsack_volume: usize, items: &[Item<V>]) -> V {
+        (0..1usize << items.len())
+            .filter_map(|mask| {
+                let chosen = items.iter().enumerate().filter(|(pos, _)| mask >> pos & 1 == 1).map(|(_, item)| item);
+                let (volume, value) = chosen.fold((0, V::default()), |(volume, value), item| (volume + item.volume, value + item.value));
+                (volume <= knapsack_volume).then_some(value)
+            })
+            .max()
+            .unwrap()
     }
 
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
+    // решение допустимо и его ценность и объём совпадают с набором предметов
+    fn check_solution<V: Value>(knapsack_volume: usize, items: &[Item<V>], solution: &Solution<V>) {
+        let mut by_index: Vec<Option<&Item<V>>> = vec![None; items.len()];
+        for item in items {
+            by_index[item.index] = Some(item);
+        }
+        let mut volume = 0;
+        let mut value = V::default();
+        for window in solution.items.windows(2) {
+            assert!(window[0] < window[1], "items are not distinct: {:?}", solution.items);
+        }
+        for &index in &solution.items {
+            let item = by_index[index].unwrap();
+            volume += item.volume;
+            value = value + item.value;
+        }
+        assert_eq!(volume, solution.volume);
+        assert!(value == solution.value);
+        assert!(volume <= knapsack_volume);
+    }
+
+    #[test]
+    fn search_orders_match_brute_force() {
+        let mut rng = Xoshiro256ss::new(12);
+        for _ in 0..300 {
+            let num_items = rng.gen_range(1..15);
+            let knapsack_volume = rng.gen_range(1..60);
+            let items = random_items(&mut rng, num_items, knapsack_volume, |rng, _| rng.gen_below(40) + 1);
+            let optimum = brute_force(knapsack_volume, &items);
+            for order in ORDERS {
+                let (solution, _) = knapsack(knapsack_volume, &items, order);
+                check_solution(knapsack_volume, &items, &solution);
+                assert_eq!(solution.value, optimum, "order {:?}, volume {}", order, knapsack_volume);
+            }
+        }
+    }
+
+    #[test]
+    fn rational_values_match_brute_force() {
+        let mut rng = Xoshiro256ss::new(13);
+        for _ in 0..100 {
+            let num_items = rng.gen_range(1..12);
+            let knapsack_volume = rng.gen_range(1..40);
+            let items = random_items(&mut rng, num_items, knapsack_volume, |rng, _| {
+                Rational::new(rng.gen_below(60) as u128 + 1, rng.gen_below(6) as u128 + 1).unwrap()
+            });
+            let optimum = brute_force(knapsack_volume, &items);
+            for order in ORDERS {
+                let (solution, _) = knapsack(knapsack_volume, &items, order);
+                check_solution(knapsack_volume, &items, &solution);
+                assert!(solution.value == optimum);
+            }
+        }
+    }
+
+    // больше двух ширин ядра: работают ядро, редукция и поиск по свободным предметам
+    #[test]
+    fn large_instances_match_dp() {
+        let mut rng = Xoshiro256ss::new(14);
+        for round in 0..30 {
+            let num_items = rng.gen_range(60..150);
+            let knapsack_volume = rng.gen_range(100..1000);
+            // половина экземпляров сильно коррелирована: ценность = объём + 10
+            let items = random_items(&mut rng, num_items, knapsack_volume / 4, |rng, volume| {
+                if round % 2 == 0 { volume as u64 + 10 } else { rng.gen_below(100) + 1 }
+            });
+            let optimum = dp_by_capacity(knapsack_volume, &items).value;
+            for order in ORDERS {
+                let (solution, _) = knapsack(knapsack_volume, &items, order);
+                check_solution(knapsack_volume, &items, &solution);
+                assert_eq!(solution.value, optimum);
             }
         }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
+    }
+
+    // любое решение дороже lower_bound согласовано со всеми фиксациями
+    #[test]
+    fn reduce_keeps_every_better_solution() {
+        let mut rng = Xoshiro256ss::new(15);
+        for _ in 0..200 {
+            let num_items = rng.gen_range(1..13);
+            let knapsack_volume = rng.gen_range(1..50);
+            let items = random_items(&mut rng, num_items, knapsack_volume, |rng, _| rng.gen_below(30) + 1);
+            let (lower_bound, _) = calc_bounds(knapsack_volume, &items);
+            let fixed = reduce(knapsack_volume, &items, lower_bound);
+            for mask in 0..1usize << num_items {
+                let taken = |pos: usize| mask >> pos & 1 == 1;
+                let volume: usize = (0..num_items).filter(|&pos| taken(pos)).map(|pos| items[pos].volume).sum();
+                let value: u64 = (0..num_items).filter(|&pos| taken(pos)).map(|pos| items[pos].value).sum();
+                if volume > knapsack_volume || value <= lower_bound { continue; }
+                for (pos, fix) in fixed.iter().enumerate() {
+                    if let Some(fix) = fix {
+                        assert_eq!(*fix, taken(pos), "item {} fixed wrongly", pos);
+                    }
                 }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
             }
         }
     }
 
-}
\ No newline at end of file
+    fn node(level: usize, volume_left: usize, value: u64) -> Node<u64> {
+        Node { level, volume_left, value, upper_bound: value, taken: None }
+    }
+
+    #[test]
+    fn dominance_memo() {
+        let mut memo = DominanceMemo::new(8);
+        assert!(!memo.dominated(&node(1, 5, 10)));
+        assert!(memo.dominated(&node(1, 5, 10)));
+        assert!(memo.dominated(&node(1, 5, 7)));
+        assert!(!memo.dominated(&node(1, 5, 12)));
+        assert!(memo.dominated(&node(1, 5, 11)));
+        // другое состояние не доминировано
+        assert!(!memo.dominated(&node(1, 4, 1)));
+        assert!(!memo.dominated(&node(2, 5, 1)));
+
+        // при переполнении остаются записи ближайших к корню уровней
+        let mut memo = DominanceMemo::new(8);
+        for level in 0..4 {
+            for volume_left in 0..2 {
+                assert!(!memo.dominated(&node(level, volume_left, 1)));
+            }
+        }
+        assert!(!memo.dominated(&node(4, 0, 1)));
+        assert!(memo.best.len() <= 8);
+        assert!(memo.dominated(&node(0, 0, 1)));
+        assert!(memo.dominated(&node(1, 1, 1)));
+        assert!(!memo.dominated(&node(3, 0, 1)));
+    }
+}
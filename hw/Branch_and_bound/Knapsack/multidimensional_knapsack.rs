@@ -0,0 +1,583 @@
+// Многомерный рюкзак 0-1: у рюкзака m ресурсов (объём, масса, бюджет...),
+// предмет расходует каждый из них.
+//
+// Вход: m, вместимости c_1..c_m, число предметов n,
+// затем для каждого предмета w_1..w_m и ценность.
+// При m = 1 это формат knapsack с "1" в начале.
+use dopt::io::{Scanner, ScanError};
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+
+// итерации подбора множителей суррогатной релаксации
+const SURROGATE_ITERATIONS: usize = 50;
+// sum_k u_k c_k для целых множителей точной границы не больше этого
+const EXACT_SCALE: f64 = (1u64 << 62) as f64;
+
+struct MdItem {
+    weights: Vec<usize>,
+    value: usize,
+    index: usize, // номер во входных данных
+}
+
+struct Problem {
+    capacities: Vec<usize>,
+    items: Vec<MdItem>,
+}
+
+impl Problem {
+    fn fits(&self, item: &MdItem, remaining: &[usize]) -> bool {
+        item.weights.iter().zip(remaining).all(|(weight, rest)| weight <= rest)
+    }
+}
+
+struct Options {
+    stats: bool,
+    node_limit: Option<u64>,
+}
+
+// --stats - статистика в stderr, --node-limit N - остановить B&B после N узлов
+fn read_options() -> Result<Options, String> {
+    let mut options = Options { stats: false, node_limit: None };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stats" => options.stats = true,
+            "--node-limit" => {
+                let value = args.next().ok_or("missing value for --node-limit")?;
+                let limit = value.parse().map_err(|_| format!("invalid value '{}' for --node-limit", value))?;
+                options.node_limit = Some(limit);
+            }
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+// Суррогатная релаксация: ограничения складываются с множителями u_k >= 0
+// в одно sum_k u_k w_ik <= sum_k u_k c_k. Получается обычный рюкзак,
+// и его граница Данцига - верхняя граница для исходной задачи при любых u.
+struct Surrogate {
+    multipliers: Vec<f64>,
+    weights: Vec<f64>, // суррогатный вес каждого предмета
+}
+
+impl Surrogate {
+    fn new(problem: &Problem, multipliers: Vec<f64>) -> Self {
+        let weights = problem.items.iter()
+            .map(|item| item.weights.iter().zip(&multipliers).map(|(&w, u)| w as f64 * u).sum())
+            .collect();
+        Self { multipliers, weights }
+    }
+
+    fn capacity(&self, remaining: &[usize]) -> f64 {
+        remaining.iter().zip(&self.multipliers).map(|(&c, u)| c as f64 * u).sum()
+    }
+
+    // позиции предметов по убыванию суррогатной удельной ценности
+    fn order(&self, problem: &Problem) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..problem.items.len()).collect();
+        let density = |pos: usize| problem.items[pos].value as f64 / self.weights[pos];
+        order.sort_by(|&pos1, &pos2| density(pos2).total_cmp(&density(pos1)));
+        order
+    }
+
+    // Граница Данцига и дробное решение LP по предметам order
+    // (предметы с нулевым суррогатным весом идут первыми и берутся целиком).
+    fn lp(&self, problem: &Problem, order: &[usize]) -> (f64, Vec<f64>) {
+        let mut x = vec![0.0; problem.items.len()];
+        let mut capacity = self.capacity(&problem.capacities);
+        let mut bound = 0.0;
+        for &pos in order {
+            let value = problem.items[pos].value as f64;
+            let weight = self.weights[pos];
+            if weight <= capacity {
+                capacity -= weight;
+                bound += value;
+                x[pos] = 1.0;
+            } else {
+                x[pos] = capacity / weight;
+                bound += value * x[pos];
+                break;
+            }
+        }
+        (bound, x)
+    }
+}
+
+// Подбор множителей: начинаем с u_k = 1 / c_k и усиливаем ресурсы,
+// которые дробное решение LP перегружает. Оставляем самую низкую границу.
+fn surrogate_relaxation(problem: &Problem) -> Surrogate {
+    let mut multipliers: Vec<f64> = problem.capacities.iter()
+        .map(|&c| if c == 0 { 0.0 } else { 1.0 / c as f64 })
+        .collect();
+    let mut best: Option<(f64, Surrogate)> = None;
+
+    for _ in 0..SURROGATE_ITERATIONS {
+        let surrogate = Surrogate::new(problem, multipliers.clone());
+        let order = surrogate.order(problem);
+        let (bound, x) = surrogate.lp(problem, &order);
+
+        for (k, u) in multipliers.iter_mut().enumerate() {
+            let capacity = problem.capacities[k];
+            if capacity == 0 { continue; }
+            let load: f64 = problem.items.iter().zip(&x).map(|(item, x)| item.weights[k] as f64 * x).sum();
+            *u *= (load / capacity as f64).clamp(0.5, 2.0);
+        }
+        let scale: f64 = multipliers.iter().zip(&problem.capacities).map(|(u, &c)| u * c as f64).sum();
+        if scale > 0.0 {
+            multipliers.iter_mut().for_each(|u| *u /= scale);
+        }
+
+        if best.as_ref().is_none_or(|(best_bound, _)| bound < *best_bound) {
+            best = Some((bound, surrogate));
+        }
+    }
+    best.map(|(_, surrogate)| surrogate)
+        .unwrap_or_else(|| Surrogate::new(problem, multipliers))
+}
+
+// Суррогатная задача с целыми множителями, округлёнными вниз из найденных
+// дробных: граница Данцига считается точно, без погрешности f64. Множители
+// масштабируются так, что sum_k u_k c_k <= 2^62; каждый предмет помещается
+// в пустой рюкзак, поэтому суррогатные веса не больше этой суммы, а
+// ценность * остаток вместимости укладывается в u128.
+struct ExactSurrogate {
+    multipliers: Vec<u128>,
+    weights: Vec<u128>,
+}
+
+impl ExactSurrogate {
+    fn new(problem: &Problem, surrogate: &Surrogate) -> Self {
+        let total = surrogate.capacity(&problem.capacities);
+        let scale = if total > 0.0 { EXACT_SCALE / total } else { 0.0 };
+        let multipliers: Vec<u128> = surrogate.multipliers.iter().map(|u| (u * scale).floor() as u128).collect();
+        let weights = problem.items.iter()
+            .map(|item| item.weights.iter().zip(&multipliers).map(|(&w, u)| w as u128 * u).sum())
+            .collect();
+        Self { multipliers, weights }
+    }
+
+    fn capacity(&self, remaining: &[usize]) -> u128 {
+        remaining.iter().zip(&self.multipliers).map(|(&c, u)| c as u128 * u).sum()
+    }
+
+    // позиции предметов по убыванию удельной ценности, сравнение точное;
+    // предметы нулевого веса - первыми
+    fn order(&self, problem: &Problem) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..problem.items.len()).collect();
+        let value = |pos: usize| problem.items[pos].value as u128;
+        order.sort_by(|&pos1, &pos2| match (self.weights[pos1], self.weights[pos2]) {
+            (0, 0) => value(pos2).cmp(&value(pos1)),
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            (weight1, weight2) => (value(pos2) * weight1).cmp(&(value(pos1) * weight2)),
+        });
+        order
+    }
+}
+
+// Решение: взятые предметы (позиции в problem.items) и остаток ресурсов.
+#[derive(Clone)]
+struct Selection {
+    taken: Vec<bool>,
+    remaining: Vec<usize>,
+    value: usize,
+}
+
+impl Selection {
+    fn empty(problem: &Problem) -> Self {
+        Self { taken: vec![false; problem.items.len()], remaining: problem.capacities.clone(), value: 0 }
+    }
+
+    fn add(&mut self, item: &MdItem, pos: usize) {
+        self.taken[pos] = true;
+        self.value += item.value;
+        self.remaining.iter_mut().zip(&item.weights).for_each(|(rest, w)| *rest -= w);
+    }
+
+    fn remove(&mut self, item: &MdItem, pos: usize) {
+        self.taken[pos] = false;
+        self.value -= item.value;
+        self.remaining.iter_mut().zip(&item.weights).for_each(|(rest, w)| *rest += w);
+    }
+
+    // добавляет по порядку order всё, что помещается
+    fn fill(&mut self, problem: &Problem, order: &[usize]) {
+        for &pos in order {
+            let item = &problem.items[pos];
+            if !self.taken[pos] && problem.fits(item, &self.remaining) {
+                self.add(item, pos);
+            }
+        }
+    }
+}
+
+// жадно по суррогатной удельной ценности
+fn greedy(problem: &Problem, order: &[usize]) -> Selection {
+    let mut selection = Selection::empty(problem);
+    selection.fill(problem, order);
+    selection
+}
+
+// Округление LP: берём предметы с x_i = 1, пока не нарушено ни одно
+// ограничение, убираем худшие по суррогатной ценности, затем добираем жадно.
+fn lp_rounding(problem: &Problem, surrogate: &Surrogate, order: &[usize]) -> Selection {
+    let (_, x) = surrogate.lp(problem, order);
+    let mut taken: Vec<usize> = order.iter().copied().filter(|&pos| x[pos] >= 1.0).collect();
+    let overloaded = |taken: &[usize]| {
+        (0..problem.capacities.len()).any(|k| {
+            taken.iter().map(|&pos| problem.items[pos].weights[k]).sum::<usize>() > problem.capacities[k]
+        })
+    };
+    while overloaded(&taken) {
+        taken.pop();
+    }
+
+    let mut selection = Selection::empty(problem);
+    for pos in taken {
+        selection.add(&problem.items[pos], pos);
+    }
+    selection.fill(problem, order);
+    selection
+}
+
+// Локальный поиск с первым улучшением: обмен взятого предмета на
+// более ценный невзятый, затем убрать предмет и добрать жадно.
+// Решение всё время допустимо.
+fn local_search(problem: &Problem, order: &[usize], mut selection: Selection) -> Selection {
+    let n = problem.items.len();
+    let mut improved = true;
+    while improved {
+        improved = false;
+        'swap: for out_pos in 0..n {
+            if !selection.taken[out_pos] { continue; }
+            let out_item = &problem.items[out_pos];
+            selection.remove(out_item, out_pos);
+            for in_pos in 0..n {
+                let in_item = &problem.items[in_pos];
+                if in_pos == out_pos || selection.taken[in_pos] || in_item.value <= out_item.value { continue; }
+                if problem.fits(in_item, &selection.remaining) {
+                    selection.add(in_item, in_pos);
+                    selection.fill(problem, order);
+                    improved = true;
+                    break 'swap;
+                }
+            }
+            selection.add(out_item, out_pos);
+        }
+        if improved { continue; }
+
+        for out_pos in 0..n {
+            if !selection.taken[out_pos] { continue; }
+            let mut candidate = selection.clone();
+            candidate.remove(&problem.items[out_pos], out_pos);
+            // сам убранный предмет обратно не добираем
+            candidate.taken[out_pos] = true;
+            candidate.fill(problem, order);
+            candidate.taken[out_pos] = false;
+            if candidate.value > selection.value {
+                selection = candidate;
+                improved = true;
+                break;
+            }
+        }
+    }
+    selection
+}
+
+#[derive(Default)]
+struct SearchStats {
+    root_bound: usize,
+    start_value: usize,
+    explored: u64,
+    pruned: u64,
+    complete: bool, // перебор закончен, решение оптимально
+    elapsed: Duration,
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "root bound: {}, start value: {}, nodes explored: {}, pruned: {}, optimal: {}, time: {} ms",
+            self.root_bound, self.start_value, self.explored, self.pruned, self.complete, self.elapsed.as_millis())
+    }
+}
+
+// Ветви и границы в порядке суррогатной удельной ценности.
+struct Search<'a> {
+    problem: &'a Problem,
+    surrogate: &'a ExactSurrogate,
+    order: Vec<usize>,
+    current: Selection,
+    best: Selection,
+    stats: SearchStats,
+    node_limit: Option<u64>,
+}
+
+impl Search<'_> {
+    // граница Данцига суррогатной задачи для предметов order[level..];
+    // предметы, не помещающиеся хотя бы по одному ресурсу, пропускаются
+    fn bound(&self, level: usize) -> usize {
+        let mut capacity = self.surrogate.capacity(&self.current.remaining);
+        let mut bound = self.current.value as u128;
+        for &pos in &self.order[level..] {
+            let item = &self.problem.items[pos];
+            if !self.problem.fits(item, &self.current.remaining) { continue; }
+            let weight = self.surrogate.weights[pos];
+            if weight <= capacity {
+                capacity -= weight;
+                bound += item.value as u128;
+            } else {
+                bound += item.value as u128 * capacity / weight;
+                break;
+            }
+        }
+        usize::try_from(bound).unwrap_or(usize::MAX)
+    }
+
+    fn out_of_nodes(&self) -> bool {
+        self.node_limit.is_some_and(|limit| self.stats.explored >= limit)
+    }
+
+    // true - узел нужно ветвить
+    fn visit(&mut self, level: usize) -> bool {
+        self.stats.explored += 1;
+        if self.current.value > self.best.value {
+            self.best = self.current.clone();
+        }
+        if level == self.order.len() { return false; }
+        if self.bound(level) <= self.best.value {
+            self.stats.pruned += 1;
+            return false;
+        }
+        true
+    }
+
+    // Обход в глубину с явным стеком шагов (рекурсия была бы глубины n):
+    // сначала предмет order[level] берётся, если помещается, затем пропускается.
+    fn search(&mut self) {
+        let mut steps = vec![Step::Visit(0)];
+        while let Some(step) = steps.pop() {
+            let level = match step {
+                Step::Visit(level) => level,
+                Step::Add(level) => {
+                    let pos = self.order[level];
+                    self.current.add(&self.problem.items[pos], pos);
+                    continue;
+                }
+                Step::Remove(level) => {
+                    let pos = self.order[level];
+                    self.current.remove(&self.problem.items[pos], pos);
+                    continue;
+                }
+            };
+            if self.out_of_nodes() { return; }
+            if !self.visit(level) { continue; }
+
+            steps.push(Step::Visit(level + 1));
+            let item = &self.problem.items[self.order[level]];
+            if self.problem.fits(item, &self.current.remaining) {
+                steps.push(Step::Remove(level));
+                steps.push(Step::Visit(level + 1));
+                steps.push(Step::Add(level));
+            }
+        }
+    }
+}
+
+// шаг обхода; level - уровень узла, предмет order[level]
+enum Step {
+    Visit(usize),
+    Add(usize),
+    Remove(usize),
+}
+
+fn multidimensional_knapsack(problem: &Problem, node_limit: Option<u64>) -> (Selection, SearchStats) {
+    let start_time = Instant::now();
+    let surrogate = surrogate_relaxation(problem);
+    let exact = ExactSurrogate::new(problem, &surrogate);
+    let order = exact.order(problem);
+
+    let greedy = greedy(problem, &order);
+    let rounded = lp_rounding(problem, &surrogate, &order);
+    let start = if rounded.value > greedy.value { rounded } else { greedy };
+    let start = local_search(problem, &order, start);
+
+    let mut search = Search {
+        problem,
+        surrogate: &exact,
+        order,
+        current: Selection::empty(problem),
+        best: start,
+        stats: SearchStats::default(),
+        node_limit,
+    };
+    search.stats.start_value = search.best.value;
+    search.stats.root_bound = search.bound(0);
+    search.search();
+    search.stats.complete = !search.out_of_nodes();
+    search.stats.elapsed = start_time.elapsed();
+    (search.best, search.stats)
+}
+
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<Problem, ScanError> {
+    let num_dimensions: usize = scan.token()?;
+    let capacities: Vec<usize> = scan.tokens(num_dimensions)?;
+    let num_items: usize = scan.token()?;
+
+    let mut problem = Problem { capacities, items: Vec::with_capacity(num_items) };
+    for index in 0..num_items {
+        let weights = scan.tokens(num_dimensions)?;
+        let value = scan.token()?;
+        let item = MdItem { weights, value, index };
+        // предмет, не помещающийся в пустой рюкзак, не нужен
+        if problem.fits(&item, &problem.capacities) {
+            problem.items.push(item);
+        }
+    }
+    Ok(problem)
+}
+
+fn main() {
+    let options = read_options().unwrap_or_else(|err| {
+        eprintln!("Options error: {}", err);
+        std::process::exit(2)
+    });
+
+    let stdin = std::io::stdin();
+    let mut scan = Scanner::new(stdin.lock());
+    let problem = read_problem(&mut scan).unwrap_or_else(|err| err.exit());
+
+    let (selection, stats) = multidimensional_knapsack(&problem, options.node_limit);
+    if options.stats { eprintln!("{}", stats); }
+
+    // ценность и расход каждого ресурса, затем номера предметов (с 1) в порядке ввода
+    let used: Vec<String> = problem.capacities.iter()
+        .zip(&selection.remaining)
+        .map(|(capacity, rest)| (capacity - rest).to_string())
+        .collect();
+    println!("{} {}", selection.value, used.join(" "));
+    let mut chosen: Vec<usize> = problem.items.iter()
+        .zip(&selection.taken)
+        .filter(|(_, &taken)| taken)
+        .map(|(item, _)| item.index + 1)
+        .collect();
+    chosen.sort_unstable();
+    let chosen: Vec<String> = chosen.iter().map(|index| index.to_string()).collect();
+    println!("{}", chosen.join(" "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dopt::rng::Xoshiro256ss;
+
+    // как read_problem, предметы не влезающие в пустой рюкзак отброшены
+    fn random_problem(rng: &mut Xoshiro256ss) -> Problem {
+        let num_dimensions = rng.gen_range(1..4);
+        let capacities: Vec<usize> = (0..num_dimensions).map(|_| rng.gen_range(0..20)).collect();
+        let num_items = rng.gen_range(0..11);
+        let mut problem = Problem { capacities, items: Vec::with_capacity(num_items) };
+        for index in 0..num_items {
+            let weights = (0..num_dimensions).map(|_| rng.gen_range(0..10)).collect();
+            let item = MdItem { weights, value: rng.gen_range(0..10), index };
+            if problem.fits(&item, &problem.capacities) {
+                problem.items.push(item);
+            }
+        }
+        problem
+    }
+
+    // лучшая ценность перебором наборов, в которых позиции из mask
+    // совпадают с fixed (остальные свободны)
+    fn brute_force(problem: &Problem, mask: usize, fixed: usize) -> Option<usize> {
+        (0..1usize << problem.items.len())
+            .filter(|set| set & mask == fixed)
+            .filter_map(|set| {
+                let mut selection = Selection::empty(problem);
+                for (pos, item) in problem.items.iter().enumerate() {
+                    if set >> pos & 1 == 0 { continue; }
+                    if !problem.fits(item, &selection.remaining) { return None; }
+                    selection.add(item, pos);
+                }
+                Some(selection.value)
+            })
+            .max()
+    }
+
+    fn check_selection(problem: &Problem, selection: &Selection) {
+        let mut expected = Selection::empty(problem);
+        for (pos, item) in problem.items.iter().enumerate() {
+            if !selection.taken[pos] { continue; }
+            assert!(problem.fits(item, &expected.remaining));
+            expected.add(item, pos);
+        }
+        assert_eq!(selection.value, expected.value);
+        assert_eq!(selection.remaining, expected.remaining);
+    }
+
+    #[test]
+    fn search_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(15);
+        for _ in 0..500 {
+            let problem = random_problem(&mut rng);
+            let expected = brute_force(&problem, 0, 0).unwrap();
+
+            let (selection, stats) = multidimensional_knapsack(&problem, None);
+            check_selection(&problem, &selection);
+            assert_eq!(selection.value, expected);
+            assert!(stats.complete);
+            assert!(stats.start_value <= expected && expected <= stats.root_bound);
+
+            let (selection, _) = multidimensional_knapsack(&problem, Some(3));
+            check_selection(&problem, &selection);
+            assert!(selection.value <= expected);
+        }
+    }
+
+    // при любых неотрицательных множителях граница не ниже оптимума
+    #[test]
+    fn surrogate_bounds_are_upper_bounds() {
+        let mut rng = Xoshiro256ss::new(16);
+        for _ in 0..500 {
+            let problem = random_problem(&mut rng);
+            let expected = brute_force(&problem, 0, 0).unwrap();
+            let multipliers = (0..problem.capacities.len())
+                .map(|_| if rng.gen_bool(0.2) { 0.0 } else { rng.gen_f64() })
+                .collect();
+            let surrogate = Surrogate::new(&problem, multipliers);
+            let (bound, _) = surrogate.lp(&problem, &surrogate.order(&problem));
+            assert!(bound + 1e-9 >= expected as f64, "{} < {}", bound, expected);
+            let tuned = surrogate_relaxation(&problem);
+            let (bound, _) = tuned.lp(&problem, &tuned.order(&problem));
+            assert!(bound + 1e-9 >= expected as f64, "{} < {}", bound, expected);
+
+            // точная граница в узле: предметы order[..level] уже решены
+            let exact = ExactSurrogate::new(&problem, &surrogate);
+            let order = exact.order(&problem);
+            let level = rng.gen_range(0..order.len() + 1);
+            let mut current = Selection::empty(&problem);
+            let (mut mask, mut fixed) = (0, 0);
+            for &pos in &order[..level] {
+                mask |= 1 << pos;
+                let item = &problem.items[pos];
+                if rng.gen_bool(0.5) && problem.fits(item, &current.remaining) {
+                    current.add(item, pos);
+                    fixed |= 1 << pos;
+                }
+            }
+            let best_completion = brute_force(&problem, mask, fixed).unwrap();
+            let search = Search {
+                problem: &problem,
+                surrogate: &exact,
+                order,
+                current,
+                best: Selection::empty(&problem),
+                stats: SearchStats::default(),
+                node_limit: None,
+            };
+            assert!(search.bound(level) >= best_completion);
+        }
+    }
+}
@@ -0,0 +1,560 @@
+// Рюкзак с выбором: предметы разбиты на классы, из каждого класса
+// берётся ровно один вариант, суммарный объём не больше вместимости.
+//
+// Вход: вместимость, число классов k, затем для каждого класса число
+// вариантов и объём и ценность каждого варианта.
+// Выход: ценность и объём, затем номер выбранного варианта (с 1) в каждом классе.
+use dopt::io::{Scanner, ScanError};
+use dopt::knapsack::ratio_cmp;
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::io::BufRead;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+// динамика выбирается, если в таблице выборов не больше DP_MAX_CELLS клеток
+const DP_MAX_CELLS: usize = 1 << 25;
+
+#[derive(Clone, Copy, Debug)]
+struct Choice {
+    volume: usize,
+    value: usize,
+    index: usize, // номер варианта в классе во входных данных
+}
+
+// Класс после отсева доминируемых вариантов: объёмы и ценности строго растут,
+// options[0] - самый лёгкий, он же базовый.
+struct Class {
+    options: Vec<Choice>,
+    // приросты верхней выпуклой оболочки от базового варианта (LP-недоминируемые)
+    increments: Vec<Increment>,
+}
+
+#[derive(Clone, Copy)]
+struct Increment {
+    volume: usize,
+    value: usize,
+}
+
+impl Class {
+    // Вариант доминируем, если есть не тяжелее и не дешевле; такой никогда
+    // не нужен. LP-доминируемые (под оболочкой) нужны точному решению,
+    // поэтому только не попадают в приросты.
+    fn new(mut options: Vec<Choice>) -> Self {
+        options.sort_unstable_by(|a, b| a.volume.cmp(&b.volume).then(b.value.cmp(&a.value)));
+        let mut kept: Vec<Choice> = Vec::with_capacity(options.len());
+        for option in options {
+            if kept.last().is_none_or(|last| option.value > last.value) {
+                kept.push(option);
+            }
+        }
+
+        let mut hull: Vec<Choice> = Vec::with_capacity(kept.len());
+        for &option in &kept {
+            while hull.len() >= 2 {
+                let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+                let slope_ab = (b.value - a.value, b.volume - a.volume);
+                let slope_ac = (option.value - a.value, option.volume - a.volume);
+                if ratio_cmp(slope_ab, slope_ac) == Ordering::Greater { break; }
+                hull.pop();
+            }
+            hull.push(option);
+        }
+        let increments = hull.windows(2)
+            .map(|pair| Increment { volume: pair[1].volume - pair[0].volume, value: pair[1].value - pair[0].value })
+            .collect();
+        Self { options: kept, increments }
+    }
+
+    fn base(&self) -> &Choice {
+        &self.options[0]
+    }
+}
+
+// Значение LP-релаксации для приростов при вместимости capacity (с округлением вниз).
+// Как у Дайера-Земеля, приросты не сортируются: критическая удельная ценность
+// ищется делением пополам через выбор медианы, в среднем за O(n).
+fn lp_value(increments: &mut [Increment], mut capacity: usize) -> usize {
+    let mut value = 0;
+    let mut rest = increments;
+    while !rest.is_empty() {
+        let mid = rest.len() / 2;
+        rest.select_nth_unstable_by(mid, |a, b| ratio_cmp((b.value, b.volume), (a.value, a.volume)));
+        let (higher, tail) = rest.split_at_mut(mid);
+        let higher_volume: usize = higher.iter().map(|inc| inc.volume).sum();
+        if higher_volume > capacity {
+            rest = higher;
+            continue;
+        }
+        capacity -= higher_volume;
+        value += higher.iter().map(|inc| inc.value).sum::<usize>();
+
+        let (pivot, lower) = tail.split_first_mut().unwrap();
+        if pivot.volume > capacity {
+            return value + (capacity as u128 * pivot.value as u128 / pivot.volume as u128) as usize;
+        }
+        capacity -= pivot.volume;
+        value += pivot.value;
+        rest = lower;
+    }
+    value
+}
+
+// choice[i] - позиция выбранного варианта в classes[i].options
+struct Selection {
+    choice: Vec<usize>,
+    value: usize,
+    volume: usize,
+}
+
+impl Selection {
+    fn new(classes: &[Class], choice: Vec<usize>) -> Self {
+        let value = classes.iter().zip(&choice).map(|(class, &pos)| class.options[pos].value).sum();
+        let volume = classes.iter().zip(&choice).map(|(class, &pos)| class.options[pos].volume).sum();
+        Self { choice, value, volume }
+    }
+}
+
+// Округление LP вниз: в каждом классе точка оболочки, до которой
+// приросты взяты целиком. Приросты класса идут по убыванию удельной
+// ценности, поэтому жадный проход по всем приростам берёт их по порядку.
+// Оставшееся место добирается заменами вариантов на более ценные.
+fn lp_rounding(classes: &[Class], capacity: usize, extra_capacity: usize) -> Selection {
+    let mut increments: Vec<(usize, Increment)> = classes.iter()
+        .enumerate()
+        .flat_map(|(class_idx, class)| class.increments.iter().map(move |&inc| (class_idx, inc)))
+        .collect();
+    increments.sort_by(|(_, a), (_, b)| ratio_cmp((b.value, b.volume), (a.value, a.volume)));
+
+    let mut hull_points = vec![0; classes.len()];
+    let mut extra_left = extra_capacity;
+    for (class_idx, inc) in increments {
+        if inc.volume > extra_left { break; }
+        extra_left -= inc.volume;
+        hull_points[class_idx] += 1;
+    }
+
+    // номер точки оболочки -> позиция варианта в options
+    let choice = classes.iter()
+        .zip(hull_points)
+        .map(|(class, steps)| {
+            let volume = class.base().volume + class.increments[..steps].iter().map(|inc| inc.volume).sum::<usize>();
+            class.options.iter().position(|option| option.volume == volume).unwrap()
+        })
+        .collect();
+    upgrade(classes, Selection::new(classes, choice), capacity)
+}
+
+// Пока остаётся место, заменяет вариант в каком-нибудь классе
+// на более ценный с наибольшим приростом ценности, который помещается.
+fn upgrade(classes: &[Class], mut selection: Selection, capacity: usize) -> Selection {
+    loop {
+        let free = capacity - selection.volume;
+        let best_upgrade = classes.iter()
+            .enumerate()
+            .flat_map(|(class_idx, class)| {
+                let current = class.options[selection.choice[class_idx]];
+                class.options.iter()
+                    .enumerate()
+                    .filter(move |(_, option)| option.value > current.value && option.volume - current.volume <= free)
+                    .map(move |(pos, option)| (option.value - current.value, class_idx, pos))
+            })
+            .max();
+        let (_, class_idx, pos) = match best_upgrade {
+            Some(upgrade) => upgrade,
+            None => return selection,
+        };
+        selection.choice[class_idx] = pos;
+        selection = Selection::new(classes, std::mem::take(&mut selection.choice));
+    }
+}
+
+// Динамика по добавочному объёму над базовыми вариантами: O(R * n) времени
+// и R * k клеток на восстановление, R = вместимость - сумма базовых объёмов.
+fn dp(classes: &[Class], extra_capacity: usize) -> Selection {
+    let width = extra_capacity + 1;
+    let mut best = vec![0; width];
+    let mut choice = vec![0_u32; classes.len() * width];
+    for (class_idx, class) in classes.iter().enumerate() {
+        let base = class.base();
+        let prev = best.clone();
+        let row = &mut choice[class_idx * width..(class_idx + 1) * width];
+        for c in 0..width {
+            best[c] = prev[c] + base.value;
+            for (pos, option) in class.options.iter().enumerate().skip(1) {
+                let extra = option.volume - base.volume;
+                if extra > c { break; }
+                let value = prev[c - extra] + option.value;
+                if value > best[c] {
+                    best[c] = value;
+                    row[c] = pos as u32;
+                }
+            }
+        }
+    }
+
+    let mut positions = vec![0; classes.len()];
+    let mut c = extra_capacity;
+    for class_idx in (0..classes.len()).rev() {
+        let pos = choice[class_idx * width + c] as usize;
+        positions[class_idx] = pos;
+        c -= classes[class_idx].options[pos].volume - classes[class_idx].base().volume;
+    }
+    Selection::new(classes, positions)
+}
+
+#[derive(Default)]
+struct SearchStats {
+    explored: u64,
+    pruned: u64,
+    elapsed: Duration,
+}
+
+// Ветви и границы по классам: варианты класса перебираются от самого
+// ценного, граница - LP-релаксация оставшихся классов.
+struct Search<'a> {
+    classes: &'a [Class],
+    base_values: Vec<usize>, // суммы базовых ценностей классов i..k
+    choice: Vec<usize>,
+    best: Selection,
+    increments: Vec<Increment>,
+    stats: SearchStats,
+}
+
+impl Search<'_> {
+    fn bound(&mut self, class_idx: usize, extra_left: usize) -> usize {
+        self.increments.clear();
+        for class in &self.classes[class_idx..] {
+            self.increments.extend_from_slice(&class.increments);
+        }
+        self.base_values[class_idx] + lp_value(&mut self.increments, extra_left)
+    }
+
+    // Обход в глубину с явным стеком узлов: рекурсия была бы глубины
+    // числа классов. Вариант узла записывается в choice, когда узел
+    // снимается со стека; варианты предков к этому времени уже на месте.
+    fn search(&mut self, extra_capacity: usize) {
+        let root = Node { class_idx: 0, option: None, extra_left: extra_capacity, value: 0 };
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if let Some(pos) = node.option {
+                self.choice[node.class_idx - 1] = pos;
+            }
+            self.stats.explored += 1;
+            if node.class_idx == self.classes.len() {
+                if node.value > self.best.value {
+                    self.best = Selection::new(self.classes, self.choice.clone());
+                }
+                continue;
+            }
+            if node.value + self.bound(node.class_idx, node.extra_left) <= self.best.value {
+                self.stats.pruned += 1;
+                continue;
+            }
+
+            // со стека первым снимется самый ценный вариант
+            let class = &self.classes[node.class_idx];
+            let base_volume = class.base().volume;
+            for (pos, option) in class.options.iter().enumerate() {
+                let extra = option.volume - base_volume;
+                if extra > node.extra_left { continue; }
+                stack.push(Node {
+                    class_idx: node.class_idx + 1,
+                    option: Some(pos),
+                    extra_left: node.extra_left - extra,
+                    value: node.value + option.value,
+                });
+            }
+        }
+    }
+}
+
+// option - вариант, выбранный в предыдущем классе (None у корня)
+struct Node {
+    class_idx: usize,
+    option: Option<usize>,
+    extra_left: usize,
+    value: usize,
+}
+
+fn branch_and_bound(classes: &[Class], extra_capacity: usize, start: Selection) -> (Selection, SearchStats) {
+    let mut base_values = vec![0; classes.len() + 1];
+    for class_idx in (0..classes.len()).rev() {
+        base_values[class_idx] = base_values[class_idx + 1] + classes[class_idx].base().value;
+    }
+    let mut search = Search {
+        classes,
+        base_values,
+        choice: vec![0; classes.len()],
+        best: start,
+        increments: Vec::new(),
+        stats: SearchStats::default(),
+    };
+    search.search(extra_capacity);
+    (search.best, search.stats)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Algorithm {
+    Auto,
+    Dp,
+    BranchAndBound,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "auto" => Ok(Algorithm::Auto),
+            "dp" => Ok(Algorithm::Dp),
+            "bb" => Ok(Algorithm::BranchAndBound),
+            _ => Err(format!("unknown algorithm '{}', expected auto, dp or bb", name)),
+        }
+    }
+}
+
+struct Options {
+    algorithm: Algorithm,
+    stats: bool,
+}
+
+// --algorithm auto|dp|bb, по умолчанию auto; --stats - статистика в stderr
+fn read_options() -> Result<Options, String> {
+    let mut options = Options { algorithm: Algorithm::Auto, stats: false };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--algorithm" => options.algorithm = args.next().ok_or("missing value for --algorithm")?.parse()?,
+            "--stats" => options.stats = true,
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+struct Report {
+    options: usize,
+    dominated: usize,
+    lp_dominated: usize,
+    lp_bound: usize,
+    start_value: usize,
+    algorithm: Algorithm,
+    search: SearchStats,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "options: {}, dominated: {}, LP-dominated: {}, LP bound: {}, start value: {}, algorithm: {:?}",
+            self.options, self.dominated, self.lp_dominated, self.lp_bound, self.start_value, self.algorithm)?;
+        if self.algorithm == Algorithm::BranchAndBound {
+            write!(f, ", nodes explored: {}, pruned: {}", self.search.explored, self.search.pruned)?;
+        }
+        write!(f, ", time: {} ms", self.search.elapsed.as_millis())
+    }
+}
+
+fn read_classes<B: BufRead>(scan: &mut Scanner<B>) -> Result<(usize, Vec<Vec<Choice>>), ScanError> {
+    let capacity: usize = scan.token()?;
+    let num_classes: usize = scan.token()?;
+    let mut classes = Vec::with_capacity(num_classes);
+    for _ in 0..num_classes {
+        let num_options: usize = scan.token()?;
+        let mut options = Vec::with_capacity(num_options);
+        for index in 0..num_options {
+            let (volume, value) = (scan.token()?, scan.token()?);
+            options.push(Choice { volume, value, index });
+        }
+        classes.push(options);
+    }
+    Ok((capacity, classes))
+}
+
+fn main() {
+    let options = read_options().unwrap_or_else(|err| {
+        eprintln!("Options error: {}", err);
+        std::process::exit(2)
+    });
+
+    let stdin = std::io::stdin();
+    let mut scan = Scanner::new(stdin.lock());
+    let (capacity, raw_classes) = read_classes(&mut scan).unwrap_or_else(|err| err.exit());
+    if let Some(class_idx) = raw_classes.iter().position(|options| options.is_empty()) {
+        eprintln!("Input error: class {} has no options", class_idx + 1);
+        std::process::exit(1);
+    }
+
+    let start_time = Instant::now();
+    let total_options: usize = raw_classes.iter().map(|options| options.len()).sum();
+    let classes: Vec<Class> = raw_classes.into_iter().map(Class::new).collect();
+    let base_volume: usize = classes.iter().map(|class| class.base().volume).sum();
+    let extra_capacity = capacity.checked_sub(base_volume).unwrap_or_else(|| {
+        eprintln!("No feasible selection: the lightest options need {} > {}", base_volume, capacity);
+        std::process::exit(1)
+    });
+
+    let kept_options: usize = classes.iter().map(|class| class.options.len()).sum();
+    let mut report = Report {
+        options: total_options,
+        dominated: total_options - kept_options,
+        lp_dominated: classes.iter().map(|class| class.options.len() - 1 - class.increments.len()).sum(),
+        lp_bound: 0,
+        start_value: 0,
+        algorithm: options.algorithm,
+        search: SearchStats::default(),
+    };
+    let mut all_increments: Vec<Increment> = classes.iter().flat_map(|class| class.increments.clone()).collect();
+    let base_value: usize = classes.iter().map(|class| class.base().value).sum();
+    report.lp_bound = base_value + lp_value(&mut all_increments, extra_capacity);
+
+    let start = lp_rounding(&classes, capacity, extra_capacity);
+    report.start_value = start.value;
+    // явно заказанная динамика со слишком большой таблицей тоже уходит в B&B
+    let cells = classes.len().saturating_mul(extra_capacity + 1);
+    report.algorithm = match report.algorithm {
+        Algorithm::Auto if cells <= DP_MAX_CELLS => Algorithm::Dp,
+        Algorithm::Auto => Algorithm::BranchAndBound,
+        Algorithm::Dp if cells > DP_MAX_CELLS => {
+            eprintln!("Warning: the DP table needs {} cells > {}, using branch and bound", cells, DP_MAX_CELLS);
+            Algorithm::BranchAndBound
+        }
+        algorithm => algorithm,
+    };
+    let selection = match report.algorithm {
+        Algorithm::Dp => dp(&classes, extra_capacity),
+        _ => {
+            let (selection, stats) = branch_and_bound(&classes, extra_capacity, start);
+            report.search = stats;
+            selection
+        }
+    };
+    report.search.elapsed = start_time.elapsed();
+    if options.stats { eprintln!("{}", report); }
+
+    println!("{} {}", selection.value, selection.volume);
+    let chosen: Vec<String> = classes.iter()
+        .zip(&selection.choice)
+        .map(|(class, &pos)| (class.options[pos].index + 1).to_string())
+        .collect();
+    println!("{}", chosen.join(" "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dopt::rng::Xoshiro256ss;
+
+    fn random_classes(rng: &mut Xoshiro256ss) -> (usize, Vec<Vec<Choice>>) {
+        let num_classes = rng.gen_range(0..6);
+        let raw_classes = (0..num_classes)
+            .map(|_| {
+                let num_options = rng.gen_range(1..6);
+                (0..num_options)
+                    .map(|index| Choice { volume: rng.gen_range(0..10), value: rng.gen_range(0..10), index })
+                    .collect()
+            })
+            .collect();
+        (rng.gen_range(0..40), raw_classes)
+    }
+
+    // лучшая ценность перебором всех наборов по одному варианту из класса
+    fn brute_force(capacity: usize, raw_classes: &[Vec<Choice>]) -> Option<usize> {
+        let mut best = None;
+        let mut positions = vec![0; raw_classes.len()];
+        loop {
+            let chosen = raw_classes.iter().zip(&positions).map(|(options, &pos)| options[pos]);
+            let volume: usize = chosen.clone().map(|option| option.volume).sum();
+            if volume <= capacity {
+                let value: usize = chosen.map(|option| option.value).sum();
+                best = best.max(Some(value));
+            }
+            match (0..raw_classes.len()).find(|&class_idx| positions[class_idx] + 1 < raw_classes[class_idx].len()) {
+                Some(class_idx) => {
+                    positions[class_idx] += 1;
+                    positions[..class_idx].fill(0);
+                }
+                None => return best,
+            }
+        }
+    }
+
+    fn check_selection(classes: &[Class], capacity: usize, selection: &Selection) {
+        assert_eq!(selection.choice.len(), classes.len());
+        let expected = Selection::new(classes, selection.choice.clone());
+        assert_eq!((selection.value, selection.volume), (expected.value, expected.volume));
+        assert!(selection.volume <= capacity);
+    }
+
+    #[test]
+    fn dp_and_branch_and_bound_match_brute_force() {
+        let mut rng = Xoshiro256ss::new(19);
+        for _ in 0..500 {
+            let (capacity, raw_classes) = random_classes(&mut rng);
+            let expected = brute_force(capacity, &raw_classes);
+            let classes: Vec<Class> = raw_classes.into_iter().map(Class::new).collect();
+            let base_volume: usize = classes.iter().map(|class| class.base().volume).sum();
+            let extra_capacity = match capacity.checked_sub(base_volume) {
+                Some(extra_capacity) => extra_capacity,
+                None => {
+                    assert_eq!(expected, None);
+                    continue;
+                }
+            };
+            let expected = expected.unwrap();
+
+            let mut all_increments: Vec<Increment> = classes.iter().flat_map(|class| class.increments.clone()).collect();
+            let base_value: usize = classes.iter().map(|class| class.base().value).sum();
+            assert!(base_value + lp_value(&mut all_increments, extra_capacity) >= expected);
+
+            let start = lp_rounding(&classes, capacity, extra_capacity);
+            check_selection(&classes, capacity, &start);
+            assert!(start.value <= expected);
+
+            let selection = dp(&classes, extra_capacity);
+            check_selection(&classes, capacity, &selection);
+            assert_eq!(selection.value, expected);
+
+            let (selection, _) = branch_and_bound(&classes, extra_capacity, start);
+            check_selection(&classes, capacity, &selection);
+            assert_eq!(selection.value, expected);
+        }
+    }
+
+    #[test]
+    fn options_lie_under_the_hull() {
+        let mut rng = Xoshiro256ss::new(20);
+        for _ in 0..500 {
+            let (_, raw_classes) = random_classes(&mut rng);
+            for raw_options in raw_classes {
+                let class = Class::new(raw_options.clone());
+                let base = *class.base();
+                // приросты с убывающей удельной ценностью
+                for pair in class.increments.windows(2) {
+                    let (a, b) = (pair[0], pair[1]);
+                    assert_eq!(ratio_cmp((a.value, a.volume), (b.value, b.volume)), Ordering::Greater);
+                }
+                // базовый вариант самый лёгкий, любой вариант не выше оболочки
+                for option in raw_options {
+                    assert!(option.volume >= base.volume);
+                    let mut increments = class.increments.clone();
+                    assert!(base.value + lp_value(&mut increments, option.volume - base.volume) >= option.value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dominated_options_are_dropped() {
+        let options = [(0, 0), (2, 5), (4, 6), (6, 10), (3, 1), (4, 2)]
+            .iter()
+            .enumerate()
+            .map(|(index, &(volume, value))| Choice { volume, value, index })
+            .collect();
+        let class = Class::new(options);
+        let kept: Vec<usize> = class.options.iter().map(|option| option.index).collect();
+        assert_eq!(kept, [0, 1, 2, 3]);
+        // (4, 6) LP-доминируем: лежит под отрезком от (2, 5) до (6, 10)
+        let increments: Vec<(usize, usize)> = class.increments.iter().map(|inc| (inc.volume, inc.value)).collect();
+        assert_eq!(increments, [(2, 5), (4, 5)]);
+    }
+}
@@ -0,0 +1,465 @@
+// Несколько рюкзаков разной вместимости: каждый предмет кладётся
+// не больше чем в один рюкзак, суммарная ценность максимальна.
+//
+// Вход: число рюкзаков m, их вместимости, число предметов n,
+// затем для каждого предмета объём и ценность.
+// Выход: ценность, затем для каждого предмета номер рюкзака (с 1), 0 - не взят.
+use dopt::io::{Scanner, ScanError};
+use dopt::knapsack::{density_cmp, dp_by_capacity, Item};
+use std::fmt::{self, Display};
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+
+// точные границы (динамикой по вместимости) считаются,
+// если в таблице не больше DP_MAX_CELLS клеток
+const DP_MAX_CELLS: usize = 1 << 22;
+
+struct Problem {
+    capacities: Vec<usize>,
+    items: Vec<Item>,
+}
+
+// bins[j] - рюкзак предмета j (позиция в Problem::items)
+#[derive(Clone)]
+struct Assignment {
+    bins: Vec<Option<usize>>,
+    remaining: Vec<usize>,
+    value: usize,
+}
+
+impl Assignment {
+    fn empty(problem: &Problem) -> Self {
+        Self { bins: vec![None; problem.items.len()], remaining: problem.capacities.clone(), value: 0 }
+    }
+
+    fn put(&mut self, item: &Item, pos: usize, bin: usize) {
+        self.bins[pos] = Some(bin);
+        self.remaining[bin] -= item.volume;
+        self.value += item.value;
+    }
+
+    fn take_out(&mut self, item: &Item, pos: usize) {
+        let bin = self.bins[pos].take().expect("Item is not assigned");
+        self.remaining[bin] += item.volume;
+        self.value -= item.value;
+    }
+
+    // рюкзак с наименьшим подходящим остатком
+    fn best_fit(&self, volume: usize) -> Option<usize> {
+        (0..self.remaining.len())
+            .filter(|&bin| self.remaining[bin] >= volume)
+            .min_by_key(|&bin| self.remaining[bin])
+    }
+
+    // кладёт непристроенные предметы positions по принципу best fit
+    fn fill<I: IntoIterator<Item = usize>>(&mut self, problem: &Problem, positions: I) {
+        for pos in positions {
+            if self.bins[pos].is_some() { continue; }
+            let item = &problem.items[pos];
+            if let Some(bin) = self.best_fit(item.volume) {
+                self.put(item, pos, bin);
+            }
+        }
+    }
+}
+
+impl Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bins: Vec<String> = self.bins.iter().map(|bin| bin.map_or(0, |bin| bin + 1).to_string()).collect();
+        write!(f, "{}\n{}", self.value, bins.join(" "))
+    }
+}
+
+// Жадное заполнение с починкой. Предметы из решения одного рюкзака
+// суммарной вместимости раскладываются по best fit в порядке убывания
+// объёма; то, что не поместилось, отбрасывается. Затем добираем
+// остальные предметы и улучшаем: невзятый предмет заменяет менее ценный
+// взятый или помещается после переноса взятого в другой рюкзак.
+fn greedy_repair(problem: &Problem, by_density: &[usize]) -> Assignment {
+    let total_capacity: usize = problem.capacities.iter().sum();
+    let mut relaxed = Vec::new();
+    let mut volume = 0;
+    for &pos in by_density {
+        if volume + problem.items[pos].volume <= total_capacity {
+            volume += problem.items[pos].volume;
+            relaxed.push(pos);
+        }
+    }
+    relaxed.sort_by_key(|&pos| std::cmp::Reverse(problem.items[pos].volume));
+
+    let mut assignment = Assignment::empty(problem);
+    assignment.fill(problem, relaxed);
+    assignment.fill(problem, by_density.iter().copied());
+
+    while improve(problem, by_density, &mut assignment) {
+        assignment.fill(problem, by_density.iter().copied());
+    }
+    assignment
+}
+
+// одно улучшающее изменение, false - таких нет
+fn improve(problem: &Problem, by_density: &[usize], assignment: &mut Assignment) -> bool {
+    let n = problem.items.len();
+    for &in_pos in by_density {
+        // каждое изменение увеличивает ценность хотя бы на ценность in
+        if assignment.bins[in_pos].is_some() || problem.items[in_pos].value == 0 { continue; }
+        let in_item = &problem.items[in_pos];
+        for out_pos in 0..n {
+            let bin = match assignment.bins[out_pos] {
+                Some(bin) => bin,
+                None => continue,
+            };
+            let out_item = &problem.items[out_pos];
+            if assignment.remaining[bin] + out_item.volume < in_item.volume { continue; }
+
+            // перенос out в другой рюкзак освобождает место для in
+            let other = (0..assignment.remaining.len())
+                .find(|&other| other != bin && assignment.remaining[other] >= out_item.volume);
+            if let Some(other) = other {
+                assignment.take_out(out_item, out_pos);
+                assignment.put(out_item, out_pos, other);
+                assignment.put(in_item, in_pos, bin);
+                return true;
+            }
+            // замена менее ценного
+            if out_item.value < in_item.value {
+                assignment.take_out(out_item, out_pos);
+                assignment.put(in_item, in_pos, bin);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[derive(Default)]
+struct SearchStats {
+    root_bound: usize,
+    start_value: usize,
+    explored: u64,
+    pruned: u64,
+    closed_by_heuristic: u64, // нижняя граница узла совпала с верхней
+    complete: bool,
+    elapsed: Duration,
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "root bound: {}, start value: {}, nodes explored: {}, pruned: {}, closed by lower bound: {}, \
+            optimal: {}, time: {} ms",
+            self.root_bound, self.start_value, self.explored, self.pruned, self.closed_by_heuristic,
+            self.complete, self.elapsed.as_millis())
+    }
+}
+
+// Bound-and-bound: предметы по убыванию удельной ценности кладутся в каждый
+// подходящий рюкзак или пропускаются. Верхняя граница - суррогатная
+// релаксация: один рюкзак с суммарной оставшейся вместимостью (сначала
+// граница Данцига, затем точное решение). Нижняя - рюкзаки по возрастанию
+// остатка заполняются оптимально по одному. Совпали - поддерево решено.
+struct Search<'a> {
+    problem: &'a Problem,
+    order: &'a [usize],
+    current: Assignment,
+    best: Assignment,
+    stats: SearchStats,
+    node_limit: Option<u64>,
+}
+
+impl Search<'_> {
+    fn upper_bound(&self, level: usize) -> usize {
+        let mut capacity: usize = self.current.remaining.iter().sum();
+        let largest = self.current.remaining.iter().copied().max().unwrap_or(0);
+        let mut bound = self.current.value;
+        for &pos in &self.order[level..] {
+            let item = &self.problem.items[pos];
+            // не помещается ни в один рюкзак
+            if item.volume > largest { continue; }
+            if item.volume <= capacity {
+                capacity -= item.volume;
+                bound += item.value;
+            } else {
+                bound += (capacity as u128 * item.value as u128 / item.volume as u128) as usize;
+                break;
+            }
+        }
+        bound
+    }
+
+    // предметы order[level..], которые ещё помещаются в рюкзак с остатком volume
+    fn candidates(&self, level: usize, assignment: &Assignment, volume: usize) -> Vec<Item> {
+        self.order[level..].iter()
+            .filter(|&&pos| assignment.bins[pos].is_none() && self.problem.items[pos].volume <= volume)
+            .map(|&pos| Item { index: pos, ..self.problem.items[pos] })
+            .collect()
+    }
+
+    // точное решение суррогатного рюкзака; None - слишком большая таблица
+    fn surrogate_bound(&self, level: usize) -> Option<usize> {
+        let capacity: usize = self.current.remaining.iter().sum();
+        let largest = self.current.remaining.iter().copied().max().unwrap_or(0);
+        let items = self.candidates(level, &self.current, largest);
+        if items.len().saturating_mul(capacity + 1) > DP_MAX_CELLS { return None; }
+
+        let mut best = vec![0; capacity + 1];
+        for item in &items {
+            for c in (item.volume..=capacity).rev() {
+                best[c] = best[c].max(best[c - item.volume] + item.value);
+            }
+        }
+        Some(self.current.value + best[capacity])
+    }
+
+    // текущее решение, дополненное по рюкзакам: каждый следующий
+    // заполняется оптимально оставшимися предметами (или жадно,
+    // если динамика слишком велика)
+    fn sequential_fill(&self, level: usize) -> Assignment {
+        let mut completed = self.current.clone();
+        let mut bins: Vec<usize> = (0..completed.remaining.len()).collect();
+        bins.sort_by_key(|&bin| completed.remaining[bin]);
+        for bin in bins {
+            let volume = completed.remaining[bin];
+            let items = self.candidates(level, &completed, volume);
+            if items.len().saturating_mul(volume + 1) > DP_MAX_CELLS {
+                completed.fill(self.problem, items.iter().map(|item| item.index));
+                continue;
+            }
+            for pos in dp_by_capacity(volume, &items).items {
+                completed.put(&self.problem.items[pos], pos, bin);
+            }
+        }
+        completed
+    }
+
+    fn out_of_nodes(&self) -> bool {
+        self.node_limit.is_some_and(|limit| self.stats.explored >= limit)
+    }
+
+    // true - узел нужно ветвить
+    fn visit(&mut self, level: usize) -> bool {
+        self.stats.explored += 1;
+
+        let mut upper_bound = self.upper_bound(level);
+        if upper_bound > self.best.value {
+            upper_bound = self.surrogate_bound(level).unwrap_or(upper_bound);
+        }
+        if upper_bound <= self.best.value {
+            self.stats.pruned += 1;
+            return false;
+        }
+        let completed = self.sequential_fill(level);
+        if completed.value > self.best.value {
+            self.best = completed;
+        }
+        if self.best.value == upper_bound {
+            self.stats.closed_by_heuristic += 1;
+            return false;
+        }
+        level < self.order.len()
+    }
+
+    // Обход в глубину с явным стеком шагов: глубина рекурсии была бы
+    // равна числу предметов. Потомки кладутся в обратном порядке, поэтому
+    // обход тот же: предмет в каждый подходящий рюкзак, затем пропуск.
+    fn search(&mut self) {
+        let mut steps = vec![Step::Visit(0)];
+        while let Some(step) = steps.pop() {
+            let level = match step {
+                Step::Visit(level) => level,
+                Step::Put(level, bin) => {
+                    let pos = self.order[level];
+                    self.current.put(&self.problem.items[pos], pos, bin);
+                    continue;
+                }
+                Step::TakeOut(level) => {
+                    let pos = self.order[level];
+                    self.current.take_out(&self.problem.items[pos], pos);
+                    continue;
+                }
+            };
+            if self.out_of_nodes() { return; }
+            if !self.visit(level) { continue; }
+
+            let item = &self.problem.items[self.order[level]];
+            steps.push(Step::Visit(level + 1));
+            for bin in (0..self.current.remaining.len()).rev() {
+                let remaining = self.current.remaining[bin];
+                // рюкзаки с одинаковым остатком взаимозаменяемы
+                if remaining < item.volume || self.current.remaining[..bin].contains(&remaining) { continue; }
+                steps.push(Step::TakeOut(level));
+                steps.push(Step::Visit(level + 1));
+                steps.push(Step::Put(level, bin));
+            }
+        }
+    }
+}
+
+// шаг обхода; level - уровень узла, предмет order[level]
+enum Step {
+    Visit(usize),
+    Put(usize, usize), // положить предмет уровня в рюкзак
+    TakeOut(usize),
+}
+
+fn multiple_knapsack(problem: &Problem, heuristic_only: bool, node_limit: Option<u64>) -> (Assignment, SearchStats) {
+    let start_time = Instant::now();
+    let mut order: Vec<usize> = (0..problem.items.len()).collect();
+    order.sort_by(|&pos1, &pos2| density_cmp(&problem.items[pos2], &problem.items[pos1]));
+    let start = greedy_repair(problem, &order);
+
+    let mut search = Search {
+        problem,
+        order: &order,
+        current: Assignment::empty(problem),
+        best: start,
+        stats: SearchStats::default(),
+        node_limit,
+    };
+    search.stats.start_value = search.best.value;
+    search.stats.root_bound = search.upper_bound(0);
+    if !heuristic_only {
+        search.search();
+    }
+    search.stats.complete = !heuristic_only && !search.out_of_nodes();
+    search.stats.elapsed = start_time.elapsed();
+    (search.best, search.stats)
+}
+
+struct Options {
+    heuristic_only: bool,
+    stats: bool,
+    node_limit: Option<u64>,
+}
+
+// --heuristic - только жадное заполнение с починкой, без перебора;
+// --node-limit N - остановить перебор после N узлов; --stats - статистика в stderr
+fn read_options() -> Result<Options, String> {
+    let mut options = Options { heuristic_only: false, stats: false, node_limit: None };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--heuristic" => options.heuristic_only = true,
+            "--stats" => options.stats = true,
+            "--node-limit" => {
+                let value = args.next().ok_or("missing value for --node-limit")?;
+                let limit = value.parse().map_err(|_| format!("invalid value '{}' for --node-limit", value))?;
+                options.node_limit = Some(limit);
+            }
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<Problem, ScanError> {
+    let num_knapsacks: usize = scan.token()?;
+    let capacities = scan.tokens(num_knapsacks)?;
+    let num_items: usize = scan.token()?;
+    let mut items = Vec::with_capacity(num_items);
+    for index in 0..num_items {
+        let (volume, value) = (scan.token()?, scan.token()?);
+        items.push(Item { volume, value, index });
+    }
+    Ok(Problem { capacities, items })
+}
+
+fn main() {
+    let options = read_options().unwrap_or_else(|err| {
+        eprintln!("Options error: {}", err);
+        std::process::exit(2)
+    });
+
+    let stdin = std::io::stdin();
+    let mut scan = Scanner::new(stdin.lock());
+    let problem = read_problem(&mut scan).unwrap_or_else(|err| err.exit());
+
+    let (assignment, stats) = multiple_knapsack(&problem, options.heuristic_only, options.node_limit);
+    if options.stats { eprintln!("{}", stats); }
+    println!("{}", assignment);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dopt::rng::Xoshiro256ss;
+
+    // есть предметы нулевого объёма и нулевой ценности
+    fn random_problem(rng: &mut Xoshiro256ss) -> Problem {
+        let capacities: Vec<usize> = (0..rng.gen_range(1..4)).map(|_| rng.gen_range(0..15)).collect();
+        let items = (0..rng.gen_range(0..8))
+            .map(|index| Item { volume: rng.gen_range(0..10), value: rng.gen_range(0..10), index })
+            .collect();
+        Problem { capacities, items }
+    }
+
+    // перебор всех (m + 1)^n распределений
+    fn brute_force(problem: &Problem) -> usize {
+        let m = problem.capacities.len();
+        let mut bins = vec![0; problem.items.len()];
+        let mut best = 0;
+        loop {
+            let mut loads = vec![0; m + 1];
+            let mut value = 0;
+            for (item, &bin) in problem.items.iter().zip(bins.iter()) {
+                loads[bin] += item.volume;
+                if bin < m { value += item.value; }
+            }
+            if (0..m).all(|bin| loads[bin] <= problem.capacities[bin]) {
+                best = best.max(value);
+            }
+            // следующее распределение: m - предмет не взят
+            match bins.iter().position(|&bin| bin < m) {
+                Some(pos) => {
+                    bins[pos] += 1;
+                    bins[..pos].iter_mut().for_each(|bin| *bin = 0);
+                }
+                None => return best,
+            }
+        }
+    }
+
+    fn check_assignment(problem: &Problem, assignment: &Assignment) {
+        let mut loads = vec![0; problem.capacities.len()];
+        let mut value = 0;
+        for (item, bin) in problem.items.iter().zip(assignment.bins.iter()) {
+            if let Some(bin) = bin {
+                loads[*bin] += item.volume;
+                value += item.value;
+            }
+        }
+        assert_eq!(value, assignment.value);
+        assert!(loads.iter().zip(problem.capacities.iter()).all(|(load, capacity)| load <= capacity));
+    }
+
+    #[test]
+    fn search_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(17);
+        for _ in 0..500 {
+            let problem = random_problem(&mut rng);
+            let optimum = brute_force(&problem);
+            let (assignment, stats) = multiple_knapsack(&problem, false, None);
+            check_assignment(&problem, &assignment);
+            assert_eq!(assignment.value, optimum, "capacities {:?}, items {:?}", problem.capacities, problem.items);
+            assert!(stats.complete && stats.root_bound >= optimum);
+
+            let (assignment, _) = multiple_knapsack(&problem, true, None);
+            check_assignment(&problem, &assignment);
+            let (assignment, _) = multiple_knapsack(&problem, false, Some(2));
+            check_assignment(&problem, &assignment);
+        }
+    }
+
+    // предметы (0, 0) не ломают порядок по удельной ценности
+    #[test]
+    fn useless_items_keep_bound_valid() {
+        let volumes_values = [(0, 6), (8, 2), (7, 5), (0, 0), (2, 5), (0, 0), (5, 5)];
+        let items = volumes_values.iter()
+            .enumerate()
+            .map(|(index, &(volume, value))| Item { volume, value, index })
+            .collect();
+        let problem = Problem { capacities: vec![7], items };
+        let (assignment, stats) = multiple_knapsack(&problem, false, None);
+        assert_eq!(assignment.value, 16);
+        assert!(stats.root_bound >= 16);
+    }
+}
@@ -0,0 +1,721 @@
+// Упаковка в контейнеры: нижние границы Мартелло-Тота L1, L2, L3,
+// редукция, фиксирующая доминирующие контейнеры, и жадные эвристики.
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+// L1: суммарный вес, делённый на вместимость, с округлением вверх.
+// При нулевой вместимости допустимы только предметы нулевого веса,
+// и все границы равны 0.
+pub fn continuous_bound(capacity: usize, weights: &[usize]) -> usize {
+    if capacity == 0 { return 0; }
+    weights.iter().sum::<usize>().div_ceil(capacity)
+}
+
+// L2 для весов, отсортированных по неубыванию. Для порога K:
+// J1 - предметы тяжелее C - K, J2 - тяжелее C/2, но не тяжелее C - K,
+// J3 - от K до C/2. Предметы J1 и J2 занимают по контейнеру, к J1
+// ничего из J3 не добавить, поэтому J3 сверх свободного места в J2
+// требует новых контейнеров. L2 - максимум по K из {0} и весов J3.
+pub fn l2_bound_sorted(capacity: usize, ascending: &[usize]) -> usize {
+    if capacity == 0 { return 0; }
+    let mut prefix = Vec::with_capacity(ascending.len() + 1);
+    prefix.push(0);
+    for &weight in ascending {
+        prefix.push(prefix.last().unwrap() + weight);
+    }
+    let n = ascending.len();
+    let half = ascending.partition_point(|&weight| 2 * weight <= capacity);
+
+    let mut best = continuous_bound(capacity, ascending);
+    let thresholds = std::iter::once(0).chain(ascending[..half].iter().copied());
+    let mut last_threshold = None;
+    for threshold in thresholds {
+        if last_threshold == Some(threshold) { continue; }
+        last_threshold = Some(threshold);
+
+        let small = ascending.partition_point(|&weight| weight < threshold);
+        let large = ascending.partition_point(|&weight| weight <= capacity - threshold);
+        let medium_count = large - half;
+        let medium_free = medium_count * capacity - (prefix[large] - prefix[half]);
+        let small_weight = prefix[half] - prefix[small];
+        let bound = (n - half) + small_weight.saturating_sub(medium_free).div_ceil(capacity);
+        best = best.max(bound);
+    }
+    best
+}
+
+pub fn l2_bound(capacity: usize, weights: &[usize]) -> usize {
+    let mut ascending = weights.to_vec();
+    ascending.sort_unstable();
+    l2_bound_sorted(capacity, &ascending)
+}
+
+// Контейнеры, зафиксированные редукцией, и оставшиеся предметы.
+// Номера - позиции в переданном срезе весов.
+pub struct Reduction {
+    pub fixed: Vec<Vec<usize>>,
+    pub residual: Vec<usize>,
+}
+
+impl Reduction {
+    pub fn fixed_items(&self) -> usize {
+        self.fixed.iter().map(Vec::len).sum()
+    }
+
+    pub fn residual_weights(&self, weights: &[usize]) -> Vec<usize> {
+        self.residual.iter().map(|&idx| weights[idx]).collect()
+    }
+
+    // Контейнеры всех предметов по упаковке остатка residual_bins (по
+    // позициям в residual): сначала фиксированные, затем контейнеры
+    // остатка. Пустые контейнеры остатка пропускаются, а предметы
+    // контейнера нулевого веса идут в первый фиксированный контейнер.
+    pub fn merge(&self, weights: &[usize], residual_bins: &[usize]) -> Vec<usize> {
+        let mut bins = vec![0; weights.len()];
+        for (bin, items) in self.fixed.iter().enumerate() {
+            for &idx in items {
+                bins[idx] = bin;
+            }
+        }
+        // (число предметов, вес) контейнеров остатка
+        let mut contents = vec![(0, 0); num_bins(residual_bins)];
+        for (pos, &bin) in residual_bins.iter().enumerate() {
+            contents[bin].0 += 1;
+            contents[bin].1 += weights[self.residual[pos]];
+        }
+        // новые номера непустых контейнеров остатка подряд после фиксированных
+        let mut renumber = vec![0; contents.len()];
+        let mut next = self.fixed.len();
+        for (bin, &(items, load)) in contents.iter().enumerate() {
+            if items == 0 || (load == 0 && !self.fixed.is_empty()) { continue; }
+            renumber[bin] = next;
+            next += 1;
+        }
+        for (pos, &bin) in residual_bins.iter().enumerate() {
+            bins[self.residual[pos]] = renumber[bin];
+        }
+        bins
+    }
+}
+
+// Редукция Мартелло-Тота (MTRP) с множествами из одного и двух
+// предметов. Предметы j берутся по убыванию веса; k - самый тяжёлый
+// из остальных, помещающийся к j. Контейнер {j} или {j, k} фиксируется,
+// если доминирует все допустимые множества с j: к j не помещается ничего,
+// k дополняет j до полного, или никакие два предмета к j уже не
+// помещаются. Тогда есть оптимальная упаковка с этим контейнером.
+// Предметы нулевого веса помещаются и в полный контейнер, для них это
+// рассуждение неверно: они всегда остаются в residual.
+pub fn reduce(capacity: usize, weights: &[usize]) -> Reduction {
+    let mut free: BTreeSet<(usize, usize)> = weights.iter()
+        .enumerate()
+        .filter(|&(_, &weight)| weight > 0)
+        .map(|(idx, &weight)| (weight, idx))
+        .collect();
+    let mut order: Vec<usize> = free.iter().map(|&(_, idx)| idx).collect();
+    order.sort_by_key(|&idx| std::cmp::Reverse(weights[idx]));
+
+    let mut fixed = Vec::new();
+    for j in order {
+        if !free.remove(&(weights[j], j)) { continue; }
+        let rest = capacity - weights[j];
+        let partner = free.range(..=(rest, usize::MAX)).next_back().copied();
+        let bin = match partner {
+            None => Some(vec![j]),
+            Some((weight_k, k)) => {
+                let mut lightest = free.iter().map(|&(weight, _)| weight);
+                let no_pair_fits = match (lightest.next(), lightest.next()) {
+                    (Some(first), Some(second)) => first + second > rest,
+                    _ => true,
+                };
+                (weight_k == rest || no_pair_fits).then(|| vec![j, k])
+            }
+        };
+        match bin {
+            Some(bin) => {
+                if let [_, k] = bin[..] {
+                    free.remove(&(weights[k], k));
+                }
+                fixed.push(bin);
+            }
+            // j остаётся свободным
+            None => { free.insert((weights[j], j)); }
+        }
+    }
+    let mut residual: Vec<usize> = free.into_iter().map(|(_, idx)| idx).collect();
+    residual.extend((0..weights.len()).filter(|&idx| weights[idx] == 0));
+    residual.sort_unstable();
+    Reduction { fixed, residual }
+}
+
+// За раунд L3 отбрасывается 1/L3_DROP_FRACTION самых лёгких предметов
+// (но хотя бы один): раундов O(log n), а не n.
+const L3_DROP_FRACTION: usize = 64;
+
+// L3: редукция, затем L2 для оставшихся предметов; самые лёгкие
+// оставшиеся предметы отбрасываются (ослабление задачи), и всё
+// повторяется. Фиксированные контейнеры каждого шага суммируются.
+pub fn l3_bound(capacity: usize, weights: &[usize]) -> usize {
+    let mut best = l2_bound(capacity, weights);
+    let mut remaining = weights.to_vec();
+    let mut fixed_bins = 0;
+    while !remaining.is_empty() {
+        let reduction = reduce(capacity, &remaining);
+        fixed_bins += reduction.fixed.len();
+        let mut residual: Vec<usize> = reduction.residual.iter().map(|&idx| remaining[idx]).collect();
+        residual.sort_unstable();
+        best = best.max(fixed_bins + l2_bound_sorted(capacity, &residual));
+        let drop = (residual.len() / L3_DROP_FRACTION).max(1).min(residual.len());
+        residual.drain(..drop);
+        remaining = residual;
+    }
+    best
+}
+
+// Жадные эвристики упаковки. Decreasing-варианты сначала сортируют
+// предметы по убыванию веса.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Heuristic {
+    NextFit,
+    FirstFit,
+    BestFit,
+    WorstFit,
+    NextFitDecreasing,
+    FirstFitDecreasing,
+    BestFitDecreasing,
+    WorstFitDecreasing,
+    ModifiedFirstFitDecreasing,
+    SubsetSumFill, // контейнеры по очереди заполняются самым полным подмножеством
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 10] = [
+        Heuristic::NextFit,
+        Heuristic::FirstFit,
+        Heuristic::BestFit,
+        Heuristic::WorstFit,
+        Heuristic::NextFitDecreasing,
+        Heuristic::FirstFitDecreasing,
+        Heuristic::BestFitDecreasing,
+        Heuristic::WorstFitDecreasing,
+        Heuristic::ModifiedFirstFitDecreasing,
+        Heuristic::SubsetSumFill,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heuristic::NextFit => "nf",
+            Heuristic::FirstFit => "ff",
+            Heuristic::BestFit => "bf",
+            Heuristic::WorstFit => "wf",
+            Heuristic::NextFitDecreasing => "nfd",
+            Heuristic::FirstFitDecreasing => "ffd",
+            Heuristic::BestFitDecreasing => "bfd",
+            Heuristic::WorstFitDecreasing => "wfd",
+            Heuristic::ModifiedFirstFitDecreasing => "mffd",
+            Heuristic::SubsetSumFill => "subset-sum",
+        }
+    }
+
+    // bins[i] - номер контейнера предмета i, контейнеры нумеруются с 0 подряд;
+    // все веса не больше capacity
+    pub fn pack(self, capacity: usize, weights: &[usize]) -> Vec<usize> {
+        let mut packer = Packer::new(capacity, weights);
+        let input_order = 0..weights.len();
+        match self {
+            Heuristic::NextFit => packer.next_fit(input_order),
+            Heuristic::FirstFit => packer.first_fit(input_order),
+            Heuristic::BestFit => packer.best_fit(input_order),
+            Heuristic::WorstFit => packer.worst_fit(input_order),
+            Heuristic::NextFitDecreasing => packer.next_fit(decreasing_order(weights)),
+            Heuristic::FirstFitDecreasing => packer.first_fit(decreasing_order(weights)),
+            Heuristic::BestFitDecreasing => packer.best_fit(decreasing_order(weights)),
+            Heuristic::WorstFitDecreasing => packer.worst_fit(decreasing_order(weights)),
+            Heuristic::ModifiedFirstFitDecreasing => packer.modified_first_fit_decreasing(),
+            Heuristic::SubsetSumFill => packer.subset_sum_fill(),
+        }
+        packer.bins
+    }
+}
+
+impl FromStr for Heuristic {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Heuristic::ALL.iter()
+            .copied()
+            .find(|heuristic| heuristic.name() == name)
+            .ok_or_else(|| format!("unknown heuristic '{}', expected nf, ff, bf, wf, nfd, ffd, bfd, wfd, mffd, \
+                subset-sum or all", name))
+    }
+}
+
+pub fn num_bins(bins: &[usize]) -> usize {
+    bins.iter().max().map_or(0, |bin| bin + 1)
+}
+
+// Упаковка заданной эвристикой или (None) лучшая из всех
+pub fn heuristic_packing(
+    capacity: usize,
+    weights: &[usize],
+    heuristic: Option<Heuristic>,
+) -> (Heuristic, Vec<usize>) {
+    match heuristic {
+        Some(heuristic) => (heuristic, heuristic.pack(capacity, weights)),
+        None => Heuristic::ALL.iter()
+            .map(|&heuristic| (heuristic, heuristic.pack(capacity, weights)))
+            .min_by_key(|(_, bins)| num_bins(bins))
+            .unwrap(),
+    }
+}
+
+fn decreasing_order(weights: &[usize]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    order.sort_by_key(|&idx| std::cmp::Reverse(weights[idx]));
+    order
+}
+
+// Дерево отрезков с максимумом остатка вместимости: самый левый контейнер
+// с остатком не меньше weight за O(log n). Ещё не открытые контейнеры
+// пусты, поэтому новый контейнер находится тем же спуском.
+struct ResidualTree {
+    leaves: usize,
+    max: Vec<usize>,
+}
+
+impl ResidualTree {
+    fn new(residuals: &[usize], bins: usize, capacity: usize) -> Self {
+        let leaves = bins.max(residuals.len()).max(1).next_power_of_two();
+        let mut max = vec![capacity; 2 * leaves];
+        max[leaves..leaves + residuals.len()].copy_from_slice(residuals);
+        for node in (1..leaves).rev() {
+            max[node] = max[2 * node].max(max[2 * node + 1]);
+        }
+        Self { leaves, max }
+    }
+
+    fn set(&mut self, bin: usize, residual: usize) {
+        let mut node = self.leaves + bin;
+        self.max[node] = residual;
+        while node > 1 {
+            node /= 2;
+            self.max[node] = self.max[2 * node].max(self.max[2 * node + 1]);
+        }
+    }
+
+    fn first_fit(&self, weight: usize) -> usize {
+        let mut node = 1;
+        while node < self.leaves {
+            node = if self.max[2 * node] >= weight { 2 * node } else { 2 * node + 1 };
+        }
+        node - self.leaves
+    }
+}
+
+// предел вместимости для точной динамики по подмножествам
+const SUBSET_SUM_MAX_CAPACITY: usize = 1 << 22;
+// предел суммарной работы динамики (слово-предмет) на всю упаковку
+const SUBSET_SUM_WORK_LIMIT: usize = 1 << 30;
+
+// Строящаяся упаковка: контейнеры предметов и загрузки открытых контейнеров
+struct Packer<'a> {
+    capacity: usize,
+    weights: &'a [usize],
+    bins: Vec<usize>,
+    loads: Vec<usize>,
+}
+
+impl<'a> Packer<'a> {
+    fn new(capacity: usize, weights: &'a [usize]) -> Self {
+        Self { capacity, weights, bins: vec![usize::MAX; weights.len()], loads: Vec::new() }
+    }
+
+    fn residual(&self, bin: usize) -> usize {
+        self.capacity - self.loads[bin]
+    }
+
+    // bin == loads.len() - новый контейнер
+    fn put(&mut self, item: usize, bin: usize) {
+        if bin == self.loads.len() { self.loads.push(0); }
+        self.loads[bin] += self.weights[item];
+        self.bins[item] = bin;
+    }
+
+    fn next_fit<I: IntoIterator<Item = usize>>(&mut self, order: I) {
+        for item in order {
+            let bin = match self.loads.len() {
+                0 => 0,
+                len if self.residual(len - 1) >= self.weights[item] => len - 1,
+                len => len,
+            };
+            self.put(item, bin);
+        }
+    }
+
+    fn first_fit<I: IntoIterator<Item = usize>>(&mut self, order: I) {
+        let order: Vec<usize> = order.into_iter().collect();
+        let residuals: Vec<usize> = (0..self.loads.len()).map(|bin| self.residual(bin)).collect();
+        let mut tree = ResidualTree::new(&residuals, self.loads.len() + order.len(), self.capacity);
+        for item in order {
+            let bin = tree.first_fit(self.weights[item]);
+            self.put(item, bin);
+            tree.set(bin, self.residual(bin));
+        }
+    }
+
+    // (остаток, контейнер) открытых контейнеров
+    fn residual_set(&self) -> BTreeSet<(usize, usize)> {
+        (0..self.loads.len()).map(|bin| (self.residual(bin), bin)).collect()
+    }
+
+    // в самый полный контейнер, куда предмет помещается
+    fn best_fit<I: IntoIterator<Item = usize>>(&mut self, order: I) {
+        let mut residuals = self.residual_set();
+        for item in order {
+            let bin = match residuals.range((self.weights[item], 0)..).next().copied() {
+                Some(entry) => {
+                    residuals.remove(&entry);
+                    entry.1
+                }
+                None => self.loads.len(),
+            };
+            self.put(item, bin);
+            residuals.insert((self.residual(bin), bin));
+        }
+    }
+
+    // в самый свободный контейнер, если предмет туда помещается
+    fn worst_fit<I: IntoIterator<Item = usize>>(&mut self, order: I) {
+        let mut residuals = self.residual_set();
+        for item in order {
+            let bin = match residuals.last().copied() {
+                Some(entry) if entry.0 >= self.weights[item] => {
+                    residuals.remove(&entry);
+                    entry.1
+                }
+                _ => self.loads.len(),
+            };
+            self.put(item, bin);
+            residuals.insert((self.residual(bin), bin));
+        }
+    }
+
+    // MFFD Джонсона-Гэри: большие предметы (> C/2) - по контейнеру;
+    // проход вперёд по ним добавляет самый большой помещающийся средний
+    // предмет (C/6, C/2]; проход назад - пару: самый маленький средний и
+    // самый большой средний, помещающийся вместе с ним; остальное - FFD.
+    fn modified_first_fit_decreasing(&mut self) {
+        let capacity = self.capacity;
+        let order = decreasing_order(self.weights);
+        let large_count = order.iter().take_while(|&&item| 2 * self.weights[item] > capacity).count();
+        for &item in &order[..large_count] {
+            self.put(item, self.loads.len());
+        }
+        let mut medium: BTreeSet<(usize, usize)> = order[large_count..].iter()
+            .filter(|&&item| 6 * self.weights[item] > capacity)
+            .map(|&item| (self.weights[item], item))
+            .collect();
+
+        for bin in 0..large_count {
+            if let Some(&entry) = medium.range(..=(self.residual(bin), usize::MAX)).next_back() {
+                medium.remove(&entry);
+                self.put(entry.1, bin);
+            }
+        }
+        for bin in (0..large_count).rev() {
+            let mut smallest = medium.iter().copied();
+            let (first, second) = match (smallest.next(), smallest.next()) {
+                (Some(first), Some(second)) => (first, second),
+                _ => break,
+            };
+            if first.0 + second.0 > self.residual(bin) { continue; }
+            medium.remove(&first);
+            self.put(first.1, bin);
+            let partner = *medium.range(..=(self.residual(bin), usize::MAX)).next_back().unwrap();
+            medium.remove(&partner);
+            self.put(partner.1, bin);
+        }
+
+        let rest: Vec<usize> = order.into_iter().filter(|&item| self.bins[item] == usize::MAX).collect();
+        self.first_fit(rest);
+    }
+
+    // Контейнер за контейнером: самое полное подмножество оставшихся
+    // предметов. Для вместимости больше SUBSET_SUM_MAX_CAPACITY или
+    // когда исчерпан SUBSET_SUM_WORK_LIMIT остаток упаковывается FFD:
+    // это то же, что жадно заполнять контейнеры по убыванию веса.
+    fn subset_sum_fill(&mut self) {
+        let mut remaining: Vec<usize> = decreasing_order(self.weights);
+        let words = (self.capacity + 1).div_ceil(64);
+        let mut work_left = SUBSET_SUM_WORK_LIMIT;
+        while !remaining.is_empty() {
+            let work = words.saturating_mul(remaining.len());
+            if self.capacity > SUBSET_SUM_MAX_CAPACITY || work > work_left {
+                // в прежние контейнеры не помещается ни один оставшийся
+                // предмет, иначе подмножество не было бы самым полным
+                self.first_fit(remaining);
+                return;
+            }
+            work_left -= work;
+            let bin = self.loads.len();
+            for pos in fullest_subset(self.capacity, self.weights, &remaining) {
+                self.put(remaining[pos], bin);
+            }
+            remaining.retain(|&item| self.bins[item] == usize::MAX);
+        }
+    }
+}
+
+// Позиции в items подмножества с наибольшей суммой не больше capacity.
+// Достижимые суммы - битовое множество, сдвиг со сложением по словам;
+// parent[s] - предмет, которым сумма s достигнута впервые, поэтому
+// s - w(parent[s]) достигнута более ранними предметами.
+fn fullest_subset(capacity: usize, weights: &[usize], items: &[usize]) -> Vec<usize> {
+    let words = (capacity + 1).div_ceil(64);
+    let mut reach = vec![0u64; words];
+    reach[0] = 1;
+    let mut parent = vec![u32::MAX; capacity + 1];
+    let mut zero_weight = Vec::new();
+    let top_mask = match (capacity + 1) % 64 {
+        0 => u64::MAX,
+        bits => (1 << bits) - 1,
+    };
+
+    for (pos, &item) in items.iter().enumerate() {
+        let weight = weights[item];
+        if weight == 0 {
+            zero_weight.push(pos);
+            continue;
+        }
+        let (word_shift, bit_shift) = (weight / 64, weight % 64);
+        for word in (word_shift..words).rev() {
+            let src = word - word_shift;
+            let mut shifted = reach[src] << bit_shift;
+            if bit_shift > 0 && src > 0 {
+                shifted |= reach[src - 1] >> (64 - bit_shift);
+            }
+            if word == words - 1 { shifted &= top_mask; }
+            let mut added = shifted & !reach[word];
+            reach[word] |= added;
+            while added != 0 {
+                parent[word * 64 + added.trailing_zeros() as usize] = pos as u32;
+                added &= added - 1;
+            }
+        }
+        if reach[capacity / 64] >> (capacity % 64) & 1 == 1 { break; }
+    }
+
+    let best = (0..words).rev()
+        .find(|&word| reach[word] != 0)
+        .map_or(0, |word| word * 64 + 63 - reach[word].leading_zeros() as usize);
+    let mut chosen = zero_weight;
+    let mut sum = best;
+    while sum > 0 {
+        let pos = parent[sum] as usize;
+        chosen.push(pos);
+        sum -= weights[items[pos]];
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Xoshiro256ss;
+
+    // веса от 0 до capacity: есть нулевые, полные и дополняющие друг друга
+    fn random_weights(rng: &mut Xoshiro256ss, num_items: usize, capacity: usize) -> Vec<usize> {
+        (0..num_items)
+            .map(|_| match rng.gen_range(0..6) {
+                0 => 0,
+                1 => capacity,
+                _ => rng.gen_range(1..capacity + 1),
+            })
+            .collect()
+    }
+
+    // Оптимальная упаковка перебором порядков: для множества mask
+    // лексикографически наименьшая пара (контейнеров, загрузка последнего)
+    fn brute_force(capacity: usize, weights: &[usize]) -> Vec<usize> {
+        let n = weights.len();
+        let mut best = vec![(usize::MAX, 0); 1 << n];
+        let mut last = vec![usize::MAX; 1 << n];
+        best[0] = (1, 0);
+        for mask in 0..1usize << n {
+            let (bins, load) = best[mask];
+            for item in (0..n).filter(|item| mask >> item & 1 == 0) {
+                let next = if load + weights[item] <= capacity { (bins, load + weights[item]) } else { (bins + 1, weights[item]) };
+                if next < best[mask | 1 << item] {
+                    best[mask | 1 << item] = next;
+                    last[mask | 1 << item] = item;
+                }
+            }
+        }
+        let mut order = Vec::new();
+        let mut mask = (1 << n) - 1;
+        while mask != 0 {
+            order.push(last[mask]);
+            mask &= !(1 << last[mask]);
+        }
+        let mut bins = vec![0; n];
+        let (mut bin, mut load) = (0, 0);
+        for &item in order.iter().rev() {
+            if load + weights[item] > capacity {
+                (bin, load) = (bin + 1, 0);
+            }
+            load += weights[item];
+            bins[item] = bin;
+        }
+        bins
+    }
+
+    // контейнеры нумеруются подряд с 0 и не переполнены
+    fn check_packing(capacity: usize, weights: &[usize], bins: &[usize]) {
+        assert_eq!(bins.len(), weights.len());
+        let mut loads = vec![0; num_bins(bins)];
+        let mut used = vec![false; loads.len()];
+        for (item, &bin) in bins.iter().enumerate() {
+            loads[bin] += weights[item];
+            used[bin] = true;
+        }
+        assert!(used.iter().all(|&used| used), "empty bin in {:?}", bins);
+        assert!(loads.iter().all(|&load| load <= capacity), "overfull bin in {:?} for {:?}", bins, weights);
+    }
+
+    #[test]
+    fn brute_force_is_optimal_on_known_instances() {
+        assert_eq!(num_bins(&brute_force(10, &[6, 4, 6, 4, 5, 5])), 3);
+        assert_eq!(num_bins(&brute_force(10, &[3, 3, 3, 3, 3, 3, 3])), 3);
+        assert_eq!(num_bins(&brute_force(10, &[])), 0);
+    }
+
+    #[test]
+    fn reduction_keeps_optimum() {
+        let mut rng = Xoshiro256ss::new(24);
+        for _ in 0..500 {
+            let num_items = rng.gen_range(0..10);
+            let capacity = rng.gen_range(1..20);
+            let weights = random_weights(&mut rng, num_items, capacity);
+            let optimum = num_bins(&brute_force(capacity, &weights));
+
+            let reduction = reduce(capacity, &weights);
+            // фиксированные контейнеры и остаток делят предметы без пересечений
+            let mut seen = vec![0; num_items];
+            for bin in &reduction.fixed {
+                assert!(bin.iter().map(|&idx| weights[idx]).sum::<usize>() <= capacity);
+                for &idx in bin {
+                    seen[idx] += 1;
+                }
+            }
+            for &idx in &reduction.residual {
+                seen[idx] += 1;
+            }
+            assert!(seen.iter().all(|&count| count == 1), "weights {:?}", weights);
+            assert_eq!(reduction.fixed_items() + reduction.residual.len(), num_items);
+
+            let residual = reduction.residual_weights(&weights);
+            let residual_bins = brute_force(capacity, &residual);
+            let bins = reduction.merge(&weights, &residual_bins);
+            check_packing(capacity, &weights, &bins);
+            assert_eq!(num_bins(&bins), optimum, "capacity {}, weights {:?}", capacity, weights);
+            // контейнер остатка из одних нулевых весов уходит в фиксированный
+            if residual.iter().any(|&weight| weight > 0) || reduction.fixed.is_empty() {
+                assert_eq!(num_bins(&bins), reduction.fixed.len() + num_bins(&residual_bins));
+            }
+        }
+    }
+
+    #[test]
+    fn merge_renumbers_residual_bins() {
+        let weights = [7, 3, 4, 0, 2];
+        let reduction = Reduction { fixed: vec![vec![0, 1]], residual: vec![2, 3, 4] };
+        // контейнер 1 остатка пуст, контейнер 2 - только нулевой вес
+        assert_eq!(reduction.merge(&weights, &[0, 2, 3]), vec![0, 0, 1, 0, 2]);
+        let reduction = Reduction { fixed: Vec::new(), residual: vec![0, 1, 2, 3, 4] };
+        assert_eq!(reduction.merge(&weights, &[0, 2, 2, 4, 2]), vec![0, 1, 1, 2, 1]);
+    }
+
+    #[test]
+    fn lower_bounds_do_not_exceed_optimum() {
+        let mut rng = Xoshiro256ss::new(25);
+        for _ in 0..500 {
+            let num_items = rng.gen_range(0..10);
+            let capacity = rng.gen_range(1..20);
+            let weights = random_weights(&mut rng, num_items, capacity);
+            let optimum = num_bins(&brute_force(capacity, &weights));
+            let l1 = continuous_bound(capacity, &weights);
+            let l2 = l2_bound(capacity, &weights);
+            let l3 = l3_bound(capacity, &weights);
+            assert!(l1 <= l2 && l2 <= l3 && l3 <= optimum, "{} {} {} {} for {:?}", l1, l2, l3, optimum, weights);
+        }
+    }
+
+    // Наивные O(n^2) First Fit и Best Fit: первый и самый полный
+    // (при равенстве - первый) контейнер, куда предмет помещается
+    fn naive_fit(capacity: usize, weights: &[usize], order: &[usize], best: bool) -> Vec<usize> {
+        let mut bins = vec![0; weights.len()];
+        let mut loads: Vec<usize> = Vec::new();
+        for &item in order {
+            let fitting = (0..loads.len()).filter(|&bin| loads[bin] + weights[item] <= capacity);
+            let bin = if best {
+                fitting.min_by_key(|&bin| capacity - loads[bin])
+            } else {
+                fitting.min()
+            };
+            let bin = bin.unwrap_or_else(|| {
+                loads.push(0);
+                loads.len() - 1
+            });
+            loads[bin] += weights[item];
+            bins[item] = bin;
+        }
+        bins
+    }
+
+    #[test]
+    fn fit_heuristics_match_naive() {
+        let mut rng = Xoshiro256ss::new(23);
+        for round in 0..300 {
+            let num_items = rng.gen_range(0..200);
+            let capacity = if round % 3 == 0 { rng.gen_range(1..10) } else { rng.gen_range(10..1000) };
+            let weights = random_weights(&mut rng, num_items, capacity);
+            let input_order: Vec<usize> = (0..num_items).collect();
+            let decreasing = decreasing_order(&weights);
+            for (heuristic, order, best) in [
+                (Heuristic::FirstFit, &input_order, false),
+                (Heuristic::BestFit, &input_order, true),
+                (Heuristic::FirstFitDecreasing, &decreasing, false),
+                (Heuristic::BestFitDecreasing, &decreasing, true),
+            ] {
+                let bins = heuristic.pack(capacity, &weights);
+                assert_eq!(bins, naive_fit(capacity, &weights, order, best), "{} on {:?}", heuristic.name(), weights);
+            }
+        }
+    }
+
+    // все эвристики дают допустимую упаковку не лучше оптимума
+    #[test]
+    fn heuristics_pack_feasibly() {
+        let mut rng = Xoshiro256ss::new(26);
+        for _ in 0..300 {
+            let num_items = rng.gen_range(0..10);
+            let capacity = rng.gen_range(1..30);
+            let weights = random_weights(&mut rng, num_items, capacity);
+            let optimum = num_bins(&brute_force(capacity, &weights));
+            for heuristic in Heuristic::ALL {
+                let bins = heuristic.pack(capacity, &weights);
+                check_packing(capacity, &weights, &bins);
+                assert!(num_bins(&bins) >= optimum, "{} on {:?}", heuristic.name(), weights);
+            }
+            let (_, bins) = heuristic_packing(capacity, &weights, None);
+            check_packing(capacity, &weights, &bins);
+        }
+        for _ in 0..50 {
+            let capacity = rng.gen_range(1..1000);
+            let weights = random_weights(&mut rng, 500, capacity);
+            for heuristic in Heuristic::ALL {
+                check_packing(capacity, &weights, &heuristic.pack(capacity, &weights));
+            }
+        }
+    }
+}
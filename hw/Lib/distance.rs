@@ -0,0 +1,277 @@
+use crate::graphs::{AdjacencyGraph, AllNeighbors, Graph};
+use crate::matrix::Matrix;
+use std::cell::RefCell;
+use std::ops::{Index, IndexMut};
+
+// Источник попарных расстояний между вершинами. Позволяет писать
+// алгоритмы один раз и выбирать хранение по размеру задачи:
+// плотная матрица, упакованный треугольник или вычисление по координатам.
+pub trait DistanceOracle {
+    type Weight: Copy;
+
+    fn num_nodes(&self) -> usize;
+    fn distance(&self, node1: usize, node2: usize) -> Self::Weight;
+}
+
+impl<T: Default + Copy> DistanceOracle for Matrix<T> {
+    type Weight = T;
+
+    fn num_nodes(&self) -> usize {
+        let (rows, _) = self.shape();
+        rows
+    }
+
+    fn distance(&self, node1: usize, node2: usize) -> T {
+        self[(node1, node2)]
+    }
+}
+
+// Полный граф поверх расстояний без копирования: вес ребра (i, j) -
+// расстояние между i и j. Подходит для любого хранения - Matrix,
+// PackedMatrix или LazyDistances.
+pub struct DistanceGraph<'a, D> {
+    oracle: &'a D,
+}
+
+impl<'a, D: DistanceOracle> DistanceGraph<'a, D> {
+    pub fn new(oracle: &'a D) -> Self {
+        Self { oracle }
+    }
+}
+
+impl<D: DistanceOracle> Graph for DistanceGraph<'_, D> {
+    type Edge = D::Weight;
+
+    fn num_nodes(&self) -> usize {
+        self.oracle.num_nodes()
+    }
+
+    fn weight(&self, node1: usize, node2: usize) -> Option<D::Weight> {
+        Some(self.oracle.distance(node1, node2))
+    }
+
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
+
+impl<D: DistanceOracle> AdjacencyGraph for DistanceGraph<'_, D> {
+    type Neighbors<'a> = AllNeighbors<'a, Self> where Self: 'a;
+
+    fn neighbors(&self, node_idx: usize) -> Self::Neighbors<'_> {
+        AllNeighbors::new(self, node_idx)
+    }
+}
+
+// Симметричная матрица: хранится только верхний треугольник
+// вместе с диагональю, n * (n + 1) / 2 элементов вместо n * n.
+pub struct PackedMatrix<T: Default + Clone> {
+    size: usize,
+    elements: Vec<T>,
+}
+
+impl<T: Default + Clone> PackedMatrix<T> {
+    pub fn empty(size: usize) -> Self {
+        let elements = vec![T::default(); size * (size + 1) / 2];
+        Self { size, elements }
+    }
+
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(size: usize, mut f: F) -> Self {
+        let mut elements = Vec::with_capacity(size * (size + 1) / 2);
+        for row_idx in 0..size {
+            for col_idx in row_idx..size {
+                elements.push(f(row_idx, col_idx));
+            }
+        }
+        Self { size, elements }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    fn element_idx(&self, index2d: (usize, usize)) -> usize {
+        let (row_idx, col_idx) = match index2d {
+            (i, j) if i <= j => (i, j),
+            (i, j) => (j, i),
+        };
+        assert!(col_idx < self.size, "Index out of bounds");
+        row_idx * (2 * self.size - row_idx + 1) / 2 + (col_idx - row_idx)
+    }
+}
+
+impl<T: Default + Clone> Index<(usize, usize)> for PackedMatrix<T> {
+    type Output = T;
+
+    fn index(&self, index2d: (usize, usize)) -> &Self::Output {
+        &self.elements[self.element_idx(index2d)]
+    }
+}
+
+// matrix[(i, j)] и matrix[(j, i)] - один и тот же элемент
+impl<T: Default + Clone> IndexMut<(usize, usize)> for PackedMatrix<T> {
+    fn index_mut(&mut self, index2d: (usize, usize)) -> &mut T {
+        let element_idx = self.element_idx(index2d);
+        &mut self.elements[element_idx]
+    }
+}
+
+impl<T: Default + Copy> DistanceOracle for PackedMatrix<T> {
+    type Weight = T;
+
+    fn num_nodes(&self) -> usize {
+        self.size
+    }
+
+    fn distance(&self, node1: usize, node2: usize) -> T {
+        self[(node1, node2)]
+    }
+}
+
+// слот кеша: (node1, node2, расстояние), node1 <= node2
+type CacheSlot<W> = Option<(usize, usize, W)>;
+
+// Расстояния считаются по координатам при каждом запросе, память O(n).
+// Необязательный кеш прямого отображения хранит последние посчитанные пары.
+pub struct LazyDistances<P, F, W> {
+    points: Vec<P>,
+    metric: F,
+    cache: Option<RefCell<Vec<CacheSlot<W>>>>,
+}
+
+impl<P, F, W> LazyDistances<P, F, W>
+where
+    F: Fn(&P, &P) -> W,
+    W: Copy,
+{
+    pub fn new(points: Vec<P>, metric: F) -> Self {
+        Self { points, metric, cache: None }
+    }
+
+    pub fn with_cache(points: Vec<P>, metric: F, cache_size: usize) -> Self {
+        let cache = (cache_size > 0).then(|| RefCell::new(vec![None; cache_size]));
+        Self { points, metric, cache }
+    }
+
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+
+    fn cache_slot(key: (usize, usize), cache_size: usize) -> usize {
+        let (node1, node2) = key;
+        let hash = (node1 as u64).wrapping_mul(0x9E3779B97F4A7C15) ^ (node2 as u64);
+        (hash.wrapping_mul(0xBF58476D1CE4E5B9) >> 32) as usize % cache_size
+    }
+}
+
+impl<P, F, W> DistanceOracle for LazyDistances<P, F, W>
+where
+    F: Fn(&P, &P) -> W,
+    W: Copy,
+{
+    type Weight = W;
+
+    fn num_nodes(&self) -> usize {
+        self.points.len()
+    }
+
+    fn distance(&self, node1: usize, node2: usize) -> W {
+        let compute = || (self.metric)(&self.points[node1], &self.points[node2]);
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return compute(),
+        };
+
+        // метрика симметрична, поэтому (i, j) и (j, i) - одна запись
+        let key = (node1.min(node2), node1.max(node2));
+        let mut cache = cache.borrow_mut();
+        let slot = Self::cache_slot(key, cache.len());
+        match cache[slot] {
+            Some((cached1, cached2, weight)) if (cached1, cached2) == key => weight,
+            _ => {
+                let weight = compute();
+                cache[slot] = Some((key.0, key.1, weight));
+                weight
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn packed_matrix_indexing() {
+        for size in 0..7 {
+            // каждая пара i <= j попадает в свой элемент
+            let mut packed = PackedMatrix::empty(size);
+            for i in 0..size {
+                for j in i..size {
+                    assert_eq!(packed[(i, j)], 0);
+                    packed[(j, i)] = i * 10 + j + 1;
+                }
+            }
+            let expected = PackedMatrix::from_fn(size, |i, j| i * 10 + j + 1);
+            assert_eq!(packed.elements, expected.elements);
+            assert_eq!(packed.elements.len(), size * (size + 1) / 2);
+            for i in 0..size {
+                for j in 0..size {
+                    assert_eq!(packed[(i, j)], i.min(j) * 10 + i.max(j) + 1);
+                    assert_eq!(packed.distance(i, j), packed[(j, i)]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds")]
+    fn packed_matrix_bounds() {
+        let packed: PackedMatrix<u32> = PackedMatrix::empty(3);
+        let _ = packed[(3, 0)];
+    }
+
+    #[test]
+    fn lazy_cache_collisions() {
+        let points: Vec<i64> = vec![0, 3, 7, 12, 20];
+        let calls = Cell::new(0);
+        let metric = |a: &i64, b: &i64| {
+            calls.set(calls.get() + 1);
+            (a - b).abs()
+        };
+        // при кеше из одного слота сталкиваются все пары
+        let lazy = LazyDistances::with_cache(points.clone(), metric, 1);
+        assert_eq!(lazy.distance(0, 1), 3);
+        assert_eq!(lazy.distance(1, 0), 3);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(lazy.distance(0, 2), 7);
+        assert_eq!(lazy.distance(0, 1), 3);
+        assert_eq!(calls.get(), 3);
+
+        // любой порядок запросов даёт те же расстояния, что и без кеша
+        for cache_size in [1, 2, 3, 16] {
+            let lazy = LazyDistances::with_cache(points.clone(), |a: &i64, b: &i64| (a - b).abs(), cache_size);
+            let plain = LazyDistances::new(points.clone(), |a: &i64, b: &i64| (a - b).abs());
+            for step in 0..100 {
+                let (i, j) = (step * 7 % 5, step * 3 % 5);
+                assert_eq!(lazy.distance(i, j), plain.distance(i, j));
+            }
+        }
+        // нулевой размер - без кеша
+        let uncached = LazyDistances::with_cache(points, metric, 0);
+        calls.set(0);
+        assert_eq!(uncached.distance(1, 2) + uncached.distance(2, 1), 8);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn distance_graph_is_complete() {
+        let packed = PackedMatrix::from_fn(4, |i, j| i + j);
+        let graph = DistanceGraph::new(&packed);
+        assert!(graph.is_complete());
+        assert_eq!(graph.weight(3, 1), Some(4));
+        let neighbors: Vec<(usize, usize)> = graph.neighbors(2).collect();
+        assert_eq!(neighbors, [(0, 2), (1, 3), (3, 5)]);
+    }
+}
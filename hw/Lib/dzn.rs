@@ -0,0 +1,674 @@
+// Данные MiniZinc (.dzn): присваивания `имя = значение;`.
+// Поддерживаются целые и дробные числа, bool, строки, множества
+// ({1, 3} и 1..5), массивы, двумерные массивы ([| 1, 2 | 3, 4 |]
+// и arrayNd(..., [...])), комментарии `% ...` и `/* ... */`.
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+// Числа хранят запись из файла: так целые любой длины и дроби
+// разбираются в нужный тип без потери точности.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DznValue {
+    Int(String),
+    Float(String),
+    Bool(bool),
+    Str(String),
+    Set(Vec<i64>),
+    Array(Vec<DznValue>),
+    // элементы по строкам
+    Array2d { rows: usize, cols: usize, data: Vec<DznValue> },
+}
+
+impl DznValue {
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            DznValue::Int(text) => text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    // целое тоже годится как дробное
+    pub fn as_float(&self) -> Option<f64> {
+        self.as_number_text()?.parse().ok()
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            DznValue::Int(text) => text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    // запись целого или дробного числа, как в файле
+    pub fn as_number_text(&self) -> Option<&str> {
+        match self {
+            DznValue::Int(text) | DznValue::Float(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    // массив любой размерности как плоский список элементов
+    fn elements(&self) -> Option<&[DznValue]> {
+        match self {
+            DznValue::Array(data) | DznValue::Array2d { data, .. } => Some(data),
+            _ => None,
+        }
+    }
+}
+
+// Ошибка в данных. Позиция (line, column) считается с 1.
+#[derive(Debug)]
+pub enum DznError {
+    Syntax { message: String, line: usize, column: usize },
+    Missing(String),
+    Type { name: String, expected: &'static str },
+    Invalid { name: String, reason: String },
+    Io(std::io::Error),
+}
+
+impl DznError {
+    // для main: сообщить об ошибке во входных данных и завершиться
+    pub fn exit(self) -> ! {
+        eprintln!("Input error: {}", self);
+        std::process::exit(1)
+    }
+}
+
+impl fmt::Display for DznError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DznError::Syntax { message, line, column } => write!(f, "{} at {}:{}", message, line, column),
+            DznError::Missing(name) => write!(f, "parameter '{}' is not defined", name),
+            DznError::Type { name, expected } => write!(f, "parameter '{}' is not {}", name, expected),
+            DznError::Invalid { name, reason } => write!(f, "parameter '{}': {}", name, reason),
+            DznError::Io(error) => write!(f, "failed to read data: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for DznError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DznError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(String),
+    Float(String),
+    Str(String),
+    Symbol(&'static str),
+}
+
+// токен и его позиция (строка, столбец)
+type Spanned = (Token, (usize, usize));
+
+// длинные символы раньше коротких с тем же началом
+const SYMBOLS: [&str; 13] = ["[|", "|]", "..", "=", ";", ",", "[", "]", "{", "}", "(", ")", "|"];
+
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0, line: 1, line_start: 0 }
+    }
+
+    fn position(&self) -> (usize, usize) {
+        (self.line, self.text[self.line_start..self.pos].chars().count() + 1)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, DznError> {
+        let (line, column) = self.position();
+        Err(DznError::Syntax { message, line, column })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn advance(&mut self, len: usize) {
+        for (offset, c) in self.text[self.pos..self.pos + len].char_indices() {
+            if c == '\n' {
+                self.line += 1;
+                self.line_start = self.pos + offset + 1;
+            }
+        }
+        self.pos += len;
+    }
+
+    fn skip_blank(&mut self) -> Result<(), DznError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.advance(rest.len() - trimmed.len());
+            if trimmed.starts_with('%') {
+                self.advance(trimmed.find('\n').unwrap_or(trimmed.len()));
+            } else if trimmed.starts_with("/*") {
+                match trimmed.find("*/") {
+                    Some(end) => self.advance(end + 2),
+                    None => return self.error("unterminated comment".to_string()),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    // следующий токен вместе с его позицией; None - конец данных
+    fn next(&mut self) -> Result<Option<Spanned>, DznError> {
+        self.skip_blank()?;
+        let position = self.position();
+        let rest = self.rest();
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let token = if c.is_ascii_digit() || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+            self.number()?
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            self.advance(len);
+            Token::Ident(rest[..len].to_string())
+        } else if c == '"' {
+            let len = match rest[1..].find('"') {
+                Some(len) => len,
+                None => return self.error("unterminated string".to_string()),
+            };
+            self.advance(len + 2);
+            Token::Str(rest[1..len + 1].to_string())
+        } else {
+            match SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+                Some(symbol) => {
+                    self.advance(symbol.len());
+                    Token::Symbol(symbol)
+                }
+                None => return self.error(format!("unexpected character '{}'", c)),
+            }
+        };
+        Ok(Some((token, position)))
+    }
+
+    // целое или дробное; "1..5" - диапазон, а не дробное "1."
+    fn number(&mut self) -> Result<Token, DznError> {
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let mut len = sign + rest[sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len() - sign);
+        let mut is_float = false;
+        if rest[len..].starts_with('.') && rest[len + 1..].starts_with(|c: char| c.is_ascii_digit()) {
+            is_float = true;
+            len += 1 + rest[len + 1..].find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len() - len - 1);
+        }
+        if rest[len..].starts_with(['e', 'E']) {
+            let exp = &rest[len + 1..];
+            let exp_sign = usize::from(exp.starts_with(['+', '-']));
+            let digits = exp[exp_sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(exp.len() - exp_sign);
+            if digits > 0 {
+                is_float = true;
+                len += 1 + exp_sign + digits;
+            }
+        }
+
+        // запись уже проверена по цифрам, значение разбирается при чтении параметра
+        let text = rest[..len].to_string();
+        self.advance(len);
+        Ok(if is_float { Token::Float(text) } else { Token::Int(text) })
+    }
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Spanned>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Result<Option<&Token>, DznError> {
+        if self.peeked.is_none() {
+            self.peeked = self.lexer.next()?;
+        }
+        Ok(self.peeked.as_ref().map(|(token, _)| token))
+    }
+
+    // позиция следующего токена (или конца данных)
+    fn peek_position(&mut self) -> Result<(usize, usize), DznError> {
+        self.peek()?;
+        Ok(self.peeked.as_ref().map_or_else(|| self.lexer.position(), |(_, position)| *position))
+    }
+
+    fn next(&mut self) -> Result<Spanned, DznError> {
+        self.peek()?;
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.lexer.error("unexpected end of data".to_string()),
+        }
+    }
+
+    fn error_at<T>(&self, (line, column): (usize, usize), message: String) -> Result<T, DznError> {
+        Err(DznError::Syntax { message, line, column })
+    }
+
+    fn peek_is(&mut self, symbol: &str) -> Result<bool, DznError> {
+        Ok(matches!(self.peek()?, Some(Token::Symbol(s)) if *s == symbol))
+    }
+
+    // пропускает symbol, если он следующий
+    fn eat(&mut self, symbol: &str) -> Result<bool, DznError> {
+        let found = self.peek_is(symbol)?;
+        if found { self.peeked = None; }
+        Ok(found)
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), DznError> {
+        let (token, position) = self.next()?;
+        if !matches!(token, Token::Symbol(s) if s == symbol) {
+            return self.error_at(position, format!("expected '{}', found {}", symbol, describe(&token)));
+        }
+        Ok(())
+    }
+
+    fn assignment(&mut self) -> Result<(String, DznValue), DznError> {
+        let (token, position) = self.next()?;
+        let name = match token {
+            Token::Ident(name) => name,
+            token => return self.error_at(position, format!("expected parameter name, found {}", describe(&token))),
+        };
+        self.expect("=")?;
+        let value = self.value()?;
+        self.expect(";")?;
+        Ok((name, value))
+    }
+
+    fn value(&mut self) -> Result<DznValue, DznError> {
+        let (token, position) = self.next()?;
+        match token {
+            Token::Int(text) => {
+                if !self.eat("..")? { return Ok(DznValue::Int(text)); }
+                let low = self.range_bound(&text, position)?;
+                let high = self.int()?;
+                Ok(DznValue::Set((low..=high).collect()))
+            }
+            Token::Float(value) => Ok(DznValue::Float(value)),
+            Token::Str(value) => Ok(DznValue::Str(value)),
+            Token::Ident(name) if name == "true" => Ok(DznValue::Bool(true)),
+            Token::Ident(name) if name == "false" => Ok(DznValue::Bool(false)),
+            Token::Ident(name) if name.starts_with("array") && name.ends_with('d') => self.array_nd(&name, position),
+            Token::Symbol("{") => self.set(position),
+            Token::Symbol("[") => self.array(),
+            Token::Symbol("[|") => self.array2d(position),
+            token => self.error_at(position, format!("expected value, found {}", describe(&token))),
+        }
+    }
+
+    fn int(&mut self) -> Result<i64, DznError> {
+        match self.next()? {
+            (Token::Int(text), position) => self.range_bound(&text, position),
+            (token, position) => self.error_at(position, format!("expected integer, found {}", describe(&token))),
+        }
+    }
+
+    // границы диапазонов и элементы множеств - i64
+    fn range_bound(&self, text: &str, position: (usize, usize)) -> Result<i64, DznError> {
+        match text.parse() {
+            Ok(value) => Ok(value),
+            Err(_) => self.error_at(position, format!("integer '{}' is out of range", text)),
+        }
+    }
+
+    // элементы через запятую до закрывающего close (допускается запятая в конце)
+    fn list(&mut self, close: &str) -> Result<Vec<DznValue>, DznError> {
+        let mut values = Vec::new();
+        while !self.eat(close)? {
+            values.push(self.value()?);
+            if !self.eat(",")? {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(values)
+    }
+
+    fn set(&mut self, position: (usize, usize)) -> Result<DznValue, DznError> {
+        let mut elements = Vec::new();
+        for value in self.list("}")? {
+            match value {
+                DznValue::Int(text) => elements.push(self.range_bound(&text, position)?),
+                DznValue::Set(range) => elements.extend(range),
+                _ => return self.error_at(position, "set elements must be integers".to_string()),
+            }
+        }
+        elements.sort_unstable();
+        elements.dedup();
+        Ok(DznValue::Set(elements))
+    }
+
+    fn array(&mut self) -> Result<DznValue, DznError> {
+        self.list("]").map(DznValue::Array)
+    }
+
+    // [| a, b | c, d |]
+    fn array2d(&mut self, position: (usize, usize)) -> Result<DznValue, DznError> {
+        let mut rows: Vec<Vec<DznValue>> = Vec::new();
+        let mut row = Vec::new();
+        loop {
+            if self.eat("|]")? {
+                if !row.is_empty() { rows.push(row); }
+                break;
+            }
+            if self.eat("|")? {
+                rows.push(std::mem::take(&mut row));
+                continue;
+            }
+            row.push(self.value()?);
+            if !self.eat(",")? && !self.peek_is("|")? && !self.peek_is("|]")? {
+                let (token, position) = self.next()?;
+                return self.error_at(position, format!("expected ',' or '|', found {}", describe(&token)));
+            }
+        }
+
+        let cols = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != cols) {
+            return self.error_at(position, "rows of 2d array have different lengths".to_string());
+        }
+        Ok(DznValue::Array2d { rows: rows.len(), cols, data: rows.into_iter().flatten().collect() })
+    }
+
+    // array1d(1..n, [...]), array2d(1..n, 1..m, [...])
+    fn array_nd(&mut self, name: &str, position: (usize, usize)) -> Result<DznValue, DznError> {
+        let dims: usize = match name["array".len()..name.len() - 1].parse() {
+            Ok(dims) if dims > 0 => dims,
+            _ => return self.error_at(position, format!("unknown function '{}'", name)),
+        };
+        self.expect("(")?;
+        let mut shape = Vec::with_capacity(dims);
+        for _ in 0..dims {
+            match self.value()? {
+                DznValue::Set(range) => shape.push(range.len()),
+                _ => return self.error_at(position, format!("{} expects index ranges", name)),
+            }
+            self.expect(",")?;
+        }
+        let data = match self.value()? {
+            DznValue::Array(data) => data,
+            _ => return self.error_at(position, format!("{} expects an array", name)),
+        };
+        self.eat(",")?;
+        self.expect(")")?;
+
+        if shape.iter().product::<usize>() != data.len() {
+            return self.error_at(position, format!("{} size doesn't match its index ranges", name));
+        }
+        match shape[..] {
+            [rows, cols] => Ok(DznValue::Array2d { rows, cols, data }),
+            _ => Ok(DznValue::Array(data)),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Ident(name) => format!("'{}'", name),
+        Token::Int(text) | Token::Float(text) => format!("'{}'", text),
+        Token::Str(value) => format!("\"{}\"", value),
+        Token::Symbol(symbol) => format!("'{}'", symbol),
+    }
+}
+
+// Разобранный файл данных: значения по именам параметров.
+#[derive(Debug, Default)]
+pub struct DznData {
+    values: HashMap<String, DznValue>,
+}
+
+impl DznData {
+    pub fn parse(text: &str) -> Result<Self, DznError> {
+        let mut parser = Parser { lexer: Lexer::new(text), peeked: None };
+        let mut data = Self::default();
+        while parser.peek()?.is_some() {
+            let position = parser.peek_position()?;
+            let (name, value) = parser.assignment()?;
+            if data.values.insert(name.clone(), value).is_some() {
+                return parser.error_at(position, format!("parameter '{}' is defined twice", name));
+            }
+        }
+        Ok(data)
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, DznError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).map_err(DznError::Io)?;
+        Self::parse(&text)
+    }
+
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, DznError> {
+        let text = std::fs::read_to_string(path).map_err(DznError::Io)?;
+        Self::parse(&text)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&DznValue, DznError> {
+        self.values.get(name).ok_or_else(|| DznError::Missing(name.to_string()))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    fn convert<T>(&self, name: &str, expected: &'static str, f: impl Fn(&DznValue) -> Option<T>) -> Result<T, DznError> {
+        f(self.get(name)?).ok_or_else(|| DznError::Type { name: name.to_string(), expected })
+    }
+
+    fn convert_array<T>(
+        &self,
+        name: &str,
+        expected: &'static str,
+        f: impl Fn(&DznValue) -> Option<T>,
+    ) -> Result<Vec<T>, DznError> {
+        self.convert(name, expected, |value| value.elements()?.iter().map(&f).collect())
+    }
+
+    pub fn int(&self, name: &str) -> Result<i64, DznError> {
+        self.convert(name, "an integer", DznValue::as_int)
+    }
+
+    pub fn usize(&self, name: &str) -> Result<usize, DznError> {
+        self.convert(name, "a non-negative integer", DznValue::as_usize)
+    }
+
+    pub fn float(&self, name: &str) -> Result<f64, DznError> {
+        self.convert(name, "a number", DznValue::as_float)
+    }
+
+    pub fn set(&self, name: &str) -> Result<Vec<i64>, DznError> {
+        self.convert(name, "a set of integers", |value| match value {
+            DznValue::Set(elements) => Some(elements.clone()),
+            _ => None,
+        })
+    }
+
+    pub fn int_array(&self, name: &str) -> Result<Vec<i64>, DznError> {
+        self.convert_array(name, "an array of integers", DznValue::as_int)
+    }
+
+    pub fn usize_array(&self, name: &str) -> Result<Vec<usize>, DznError> {
+        self.convert_array(name, "an array of non-negative integers", DznValue::as_usize)
+    }
+
+    pub fn float_array(&self, name: &str) -> Result<Vec<f64>, DznError> {
+        self.convert_array(name, "an array of numbers", DznValue::as_float)
+    }
+
+    // строки двумерного массива
+    pub fn int_array2d(&self, name: &str) -> Result<Vec<Vec<i64>>, DznError> {
+        self.convert(name, "a 2d array of integers", |value| match value {
+            DznValue::Array2d { cols, data, .. } if *cols > 0 => data
+                .chunks(*cols)
+                .map(|row| row.iter().map(DznValue::as_int).collect())
+                .collect(),
+            DznValue::Array2d { .. } => Some(Vec::new()),
+            _ => None,
+        })
+    }
+
+    // массив name длины length (обычно length - другой параметр, например n_items)
+    pub fn usize_array_of_len(&self, name: &str, length: usize) -> Result<Vec<usize>, DznError> {
+        let array = self.usize_array(name)?;
+        check_len(name, array.len(), length)?;
+        Ok(array)
+    }
+
+    // Числовой массив длины length, каждый элемент разбирается T::from_str
+    // из записи в файле: u128 и дроби не проходят через i64 и f64
+    pub fn parsed_array_of_len<T: FromStr>(&self, name: &str, length: usize) -> Result<Vec<T>, DznError> {
+        let texts = self.convert_array(name, "an array of numbers", |value| value.as_number_text().map(str::to_string))?;
+        check_len(name, texts.len(), length)?;
+        texts.iter()
+            .enumerate()
+            .map(|(pos, text)| text.parse().map_err(|_| DznError::Invalid {
+                name: name.to_string(),
+                reason: format!("element {} '{}' is not a valid value", pos + 1, text),
+            }))
+            .collect()
+    }
+}
+
+fn check_len(name: &str, actual: usize, expected: usize) -> Result<(), DznError> {
+    if actual != expected {
+        return Err(DznError::Invalid {
+            name: name.to_string(),
+            reason: format!("has {} elements, expected {}", actual, expected),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(text: &str) -> DznValue {
+        DznValue::Int(text.to_string())
+    }
+
+    // (строка, столбец) синтаксической ошибки
+    fn syntax_error(text: &str) -> (String, usize, usize) {
+        match DznData::parse(text) {
+            Err(DznError::Syntax { message, line, column }) => (message, line, column),
+            other => panic!("expected a syntax error for {:?}, got {:?}", text, other),
+        }
+    }
+
+    #[test]
+    fn scalars_and_comments() {
+        let data = DznData::parse(
+            "% комментарий\n n = 5; /* многострочный\n комментарий */ x = -2.5e1; ok = true;\n\
+             name = \"a b\"; big = 18446744073709551615; tiny = 1e-3; % в конце",
+        ).unwrap();
+        assert_eq!(data.int("n").unwrap(), 5);
+        assert_eq!(data.usize("n").unwrap(), 5);
+        assert_eq!(data.float("n").unwrap(), 5.0);
+        assert_eq!(data.float("x").unwrap(), -25.0);
+        assert_eq!(data.get("ok").unwrap(), &DznValue::Bool(true));
+        assert_eq!(data.get("name").unwrap(), &DznValue::Str("a b".to_string()));
+        assert_eq!(data.usize("big").unwrap(), usize::MAX);
+        assert!(matches!(data.int("big"), Err(DznError::Type { .. })));
+        assert_eq!(data.float("tiny").unwrap(), 0.001);
+        assert!(data.contains("n") && !data.contains("m"));
+        assert!(matches!(data.get("m"), Err(DznError::Missing(_))));
+        assert!(matches!(data.usize("x"), Err(DznError::Type { .. })));
+    }
+
+    #[test]
+    fn ranges_are_not_floats() {
+        let data = DznData::parse("a = 1..5; b = {3, 1, 2..4, 1}; c = -2..-1; d = 1.5; e = {}; f = 3..2;").unwrap();
+        assert_eq!(data.set("a").unwrap(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(data.set("b").unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(data.set("c").unwrap(), vec![-2, -1]);
+        assert_eq!(data.get("d").unwrap(), &DznValue::Float("1.5".to_string()));
+        assert_eq!(data.set("e").unwrap(), Vec::<i64>::new());
+        assert_eq!(data.set("f").unwrap(), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn arrays() {
+        let data = DznData::parse(
+            "a = [1, 2, 3,]; b = []; c = [| 1, 2 | 3, 4 |]; d = [| |]; e = [1.5, 2];\n\
+             f = array2d(1..2, 1..3, [1, 2, 3, 4, 5, 6]); g = array1d(0..2, [7, 8, 9]); h = [[1], [2, 3]];",
+        ).unwrap();
+        assert_eq!(data.int_array("a").unwrap(), vec![1, 2, 3]);
+        assert_eq!(data.usize_array("b").unwrap(), Vec::<usize>::new());
+        assert_eq!(data.int_array2d("c").unwrap(), vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(data.int_array2d("d").unwrap(), Vec::<Vec<i64>>::new());
+        assert_eq!(data.float_array("e").unwrap(), vec![1.5, 2.0]);
+        assert!(matches!(data.int_array("e"), Err(DznError::Type { .. })));
+        assert_eq!(data.int_array2d("f").unwrap(), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        // двумерный массив годится и как плоский
+        assert_eq!(data.int_array("f").unwrap(), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(data.int_array("g").unwrap(), vec![7, 8, 9]);
+        assert_eq!(data.get("h").unwrap(), &DznValue::Array(vec![
+            DznValue::Array(vec![int("1")]),
+            DznValue::Array(vec![int("2"), int("3")]),
+        ]));
+    }
+
+    #[test]
+    fn arrays_of_given_length() {
+        let data = DznData::parse("n = 3; w = [1, 2, 3]; v = [18446744073709551616, 0.10, 2.25e0];").unwrap();
+        assert_eq!(data.usize_array_of_len("w", 3).unwrap(), vec![1, 2, 3]);
+        assert!(matches!(data.usize_array_of_len("w", 2), Err(DznError::Invalid { .. })));
+        // u128 и дроби разбираются из записи в файле
+        let values: Vec<String> = data.parsed_array_of_len("v", 3).unwrap();
+        assert_eq!(values, ["18446744073709551616", "0.10", "2.25e0"]);
+        let err = data.parsed_array_of_len::<u128>("v", 3).unwrap_err();
+        assert_eq!(err.to_string(), "parameter 'v': element 2 '0.10' is not a valid value");
+        let data = DznData::parse("v = [340282366920938463463374607431768211455, 7];").unwrap();
+        assert_eq!(data.parsed_array_of_len::<u128>("v", 2).unwrap(), vec![u128::MAX, 7]);
+        assert!(matches!(data.parsed_array_of_len::<u128>("n", 1), Err(DznError::Missing(_))));
+    }
+
+    #[test]
+    fn error_positions() {
+        let cases = [
+            ("n = 5", "unexpected end of data", 1, 6),
+            ("n = 5\nm = 6;", "expected ';', found 'm'", 2, 1),
+            ("n = 5;\n  n = 6;", "parameter 'n' is defined twice", 2, 3),
+            ("n = 5; /* без конца", "unterminated comment", 1, 8),
+            ("s = \"abc;", "unterminated string", 1, 5),
+            ("x = 1 # 2;", "unexpected character '#'", 1, 7),
+            ("5 = n;", "expected parameter name, found '5'", 1, 1),
+            ("a = [1 2];", "expected ']', found '2'", 1, 8),
+            ("a = [| 1, 2 | 3 |];", "rows of 2d array have different lengths", 1, 5),
+            ("a = [| 1 2 |];", "expected ',' or '|', found '2'", 1, 10),
+            ("a = array2d(1..2, 1..2, [1, 2, 3]);", "array2d size doesn't match its index ranges", 1, 5),
+            ("a = arrayXd([1]);", "unknown function 'arrayXd'", 1, 5),
+            ("a = 1..99999999999999999999;", "integer '99999999999999999999' is out of range", 1, 8),
+            ("a = {1, \"x\"};", "set elements must be integers", 1, 5),
+            ("a = ;", "expected value, found ';'", 1, 5),
+            ("\n\n   € = 1;", "unexpected character '€'", 3, 4),
+            ("a = \"é\"; b = #;", "unexpected character '#'", 1, 14),
+        ];
+        for (text, message, line, column) in cases {
+            assert_eq!(syntax_error(text), (message.to_string(), line, column), "data {:?}", text);
+        }
+    }
+
+    #[test]
+    fn error_messages() {
+        let err = DznData::parse("n = 1..").unwrap_err();
+        assert_eq!(err.to_string(), "unexpected end of data at 1:8");
+        let data = DznData::parse("n = -1;").unwrap();
+        assert_eq!(data.usize("n").unwrap_err().to_string(), "parameter 'n' is not a non-negative integer");
+        assert_eq!(data.int("m").unwrap_err().to_string(), "parameter 'm' is not defined");
+        let data = DznData::read("n = 2;".as_bytes()).unwrap();
+        assert_eq!(data.int("n").unwrap(), 2);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::graphs::AdjacencyGraph;
+use std::collections::HashMap;
+
+// Эйлеров обход (алгоритм Хирхольцера) из вершины start: каждое ребро
+// проходится ровно один раз, неориентированное - в одну из сторон.
+// Если эйлерова цикла нет, возвращает обход только части рёбер.
+// Вершины идут в порядке, обратном проходу: в ориентированном графе
+// путь нужно развернуть, чтобы он шёл по дугам.
+pub fn euler_tour<G: AdjacencyGraph>(graph: &G, start: usize) -> Vec<usize> {
+    let n_nodes = graph.num_nodes();
+
+    // arcs[i] - (сосед, номер ребра); обе стороны неориентированного
+    // ребра получают один номер
+    let mut arcs: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n_nodes];
+    let mut unmatched: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    let mut n_edges = 0;
+    for (node_idx, node_arcs) in arcs.iter_mut().enumerate() {
+        for (next_idx, _) in graph.neighbors(node_idx) {
+            let edge_idx = if graph.is_directed() || node_idx <= next_idx {
+                n_edges += 1;
+                if !graph.is_directed() && node_idx != next_idx {
+                    unmatched.entry((node_idx, next_idx)).or_default().push(n_edges - 1);
+                }
+                n_edges - 1
+            } else {
+                unmatched.get_mut(&(next_idx, node_idx))
+                    .and_then(|edges| edges.pop())
+                    .expect("Undirected edge is visible only from one end")
+            };
+            node_arcs.push((next_idx, edge_idx));
+        }
+    }
+
+    let mut used = vec![false; n_edges];
+    let mut stack = Vec::with_capacity(n_nodes);
+    let mut path = Vec::with_capacity(n_edges + 1);
+    stack.push(start);
+
+    while let Some(&node_idx) = stack.last() {
+        match arcs[node_idx].pop() {
+            // ребро уже пройдено с другого конца
+            Some((_, edge_idx)) if used[edge_idx] => {}
+            Some((next_idx, edge_idx)) => {
+                used[edge_idx] = true;
+                stack.push(next_idx);
+            }
+            None => path.push(stack.pop().unwrap()),
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Direction, Directed, Graph, Undirected};
+    use crate::rng::Xoshiro256ss;
+
+    // Граф из случайного замкнутого маршрута от вершины 0: эйлеров цикл
+    // есть всегда. Бывают петли и кратные рёбра.
+    fn random_eulerian<D: Direction>(rng: &mut Xoshiro256ss) -> (Graph<(), (), D>, Vec<(usize, usize)>) {
+        let n_nodes = rng.gen_range(1..6);
+        let mut graph = Graph::from_nodes(vec![(); n_nodes]);
+        let mut edges = Vec::new();
+        let mut node = 0;
+        for _ in 0..rng.gen_range(1..13) {
+            let next = rng.gen_range(0..n_nodes);
+            graph.add_edge(node, next, ());
+            edges.push((node, next));
+            node = next;
+        }
+        graph.add_edge(node, 0, ());
+        edges.push((node, 0));
+        (graph, edges)
+    }
+
+    // обход - цикл из 0, проходящий каждое ребро ровно один раз
+    fn check_tour(tour: &[usize], mut edges: Vec<(usize, usize)>, directed: bool) {
+        assert_eq!(tour.len(), edges.len() + 1);
+        assert_eq!((tour[0], tour[tour.len() - 1]), (0, 0));
+        let key = |(node1, node2): (usize, usize)| if directed { (node1, node2) } else { (node1.min(node2), node1.max(node2)) };
+        let mut walked: Vec<(usize, usize)> = tour.windows(2).map(|pair| key((pair[0], pair[1]))).collect();
+        edges.iter_mut().for_each(|edge| *edge = key(*edge));
+        walked.sort_unstable();
+        edges.sort_unstable();
+        assert_eq!(walked, edges);
+    }
+
+    #[test]
+    fn tours_use_every_edge_once() {
+        let mut rng = Xoshiro256ss::new(8);
+        for _ in 0..300 {
+            let (graph, edges) = random_eulerian::<Directed>(&mut rng);
+            let mut tour = euler_tour(&graph, 0);
+            tour.reverse();
+            check_tour(&tour, edges, true);
+
+            let (graph, edges) = random_eulerian::<Undirected>(&mut rng);
+            check_tour(&euler_tour(&graph, 0), edges, false);
+        }
+    }
+
+    #[test]
+    fn triangle() {
+        let mut graph: Graph<(), (), Undirected> = Graph::from_nodes(vec![(); 3]);
+        graph.add_edge(0, 1, ());
+        graph.add_edge(1, 2, ());
+        graph.add_edge(2, 0, ());
+        let tour = euler_tour(&graph, 0);
+        assert!(tour == [0, 1, 2, 0] || tour == [0, 2, 1, 0], "{:?}", tour);
+        let empty: Graph<(), (), Undirected> = Graph::from_nodes(vec![(); 1]);
+        assert_eq!(euler_tour(&empty, 0), [0]);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::matrix::Matrix;
+use crate::graphs::{AdjacencyGraph, AllNeighbors, Graph};
+use std::ops::{Index, IndexMut};
+
+pub struct FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Clone,
+{
+    nodes: Vec<N>,
+    edges: Matrix<E>,
+}
+
+impl<N, E> FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Clone,
+{
+    pub fn new(num_nodes: usize) -> Self {
+        let nodes = vec![N::default(); num_nodes];
+        let edges = Matrix::empty_squared(num_nodes);
+        Self { nodes, edges }
+    }
+
+    pub fn from_nodes(nodes: Vec<N>) -> Self {
+        let num_nodes = nodes.len();
+        let edges = Matrix::empty_squared(num_nodes);
+        Self { nodes, edges }
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        let n_nodes = self.nodes.len();
+        let n_edges = {
+            let (rows, cols) = self.edges.shape();
+            rows * cols
+        };
+        (n_nodes, n_edges)
+    }
+}
+
+impl<N, E> Graph for FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Copy,
+{
+    type Edge = E;
+
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn weight(&self, node1: usize, node2: usize) -> Option<E> {
+        Some(self.edges[(node1, node2)])
+    }
+
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
+
+impl<N, E> AdjacencyGraph for FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Copy,
+{
+    type Neighbors<'a> = AllNeighbors<'a, Self> where Self: 'a;
+
+    fn neighbors(&self, node_idx: usize) -> Self::Neighbors<'_> {
+        AllNeighbors::new(self, node_idx)
+    }
+}
+
+impl<N, E> Index<(usize, usize)> for FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Clone,
+{
+    type Output = E;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.edges[index]
+    }
+}
+
+impl<N, E> IndexMut<(usize, usize)> for FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Clone,
+{
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut E {
+        &mut self.edges[index]
+    }
+}
+
+impl<N, E> Index<usize> for FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Clone,
+{
+    type Output = N;
+
+    fn index(&self, node_idx: usize) -> &Self::Output {
+        &self.nodes[node_idx]
+    }
+}
+
+impl<N, E> IndexMut<usize> for FullGraph<N, E>
+where
+    N: Default + Clone,
+    E: Default + Clone,
+{
+    fn index_mut(&mut self, node_idx: usize) -> &mut N {
+        &mut self.nodes[node_idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors_skip_the_node_itself() {
+        let mut graph: FullGraph<char, u32> = FullGraph::from_nodes(vec!['a', 'b', 'c']);
+        for node1 in 0..3 {
+            for node2 in 0..3 {
+                graph[(node1, node2)] = (node1 * 10 + node2) as u32;
+            }
+        }
+        graph[1] = 'x';
+        assert_eq!(graph[1], 'x');
+        assert_eq!(graph.shape(), (3, 9));
+        assert_eq!(graph.weight(2, 0), Some(20));
+        let neighbors: Vec<(usize, u32)> = graph.neighbors(1).collect();
+        assert_eq!(neighbors, [(0, 10), (2, 12)]);
+        assert_eq!(graph.neighbors(0).count(), 2);
+        assert_eq!(FullGraph::<(), u32>::new(1).neighbors(0).count(), 0);
+    }
+}
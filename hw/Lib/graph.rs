@@ -1,78 +1,411 @@
-mod graph {
-    use std::ops::Index;
-
-    pub struct Graph<N, E>
-    {
-        nodes: Vec<N>,
-        edges: Vec<Vec<(usize, E)>>,
-    }
-
-    impl<N, E> Graph<N, E>
-    where E: Copy,
-    {
-        pub fn new() -> Self {
-            Self {
-                nodes: Vec::new(),
-                edges: Vec::new(),
-            }
+use crate::graphs;
+use std::collections::HashMap;
+use std::iter::Copied;
+use std::marker::PhantomData;
+use std::ops::Index;
+use std::slice::Iter;
+
+// Ориентированность графа задаётся типом: Graph<N, E, Directed>
+// или Graph<N, E, Undirected>.
+pub trait Direction {
+    const DIRECTED: bool;
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Directed;
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Undirected;
+
+impl Direction for Directed {
+    const DIRECTED: bool = true;
+}
+
+impl Direction for Undirected {
+    const DIRECTED: bool = false;
+}
+
+pub type UndirectedGraph<N, E> = Graph<N, E, Undirected>;
+
+// Граф на списках смежности. Неориентированное ребро (i, j) - одно ребро:
+// оно видно из обоих концов, удаляется целиком и считается в size() один раз.
+// С хеш-смежностью порядок соседей после удаления рёбер не сохраняется.
+pub struct Graph<N, E, D = Directed>
+{
+    nodes: Vec<N>,
+    edges: Vec<Vec<(usize, E)>>,
+    n_edges: usize,
+    // сосед -> позиция в edges[node], если включена хеш-смежность
+    positions: Option<Vec<HashMap<usize, usize>>>,
+    direction: PhantomData<D>,
+}
+
+impl<N, E, D> Graph<N, E, D>
+where
+    E: Copy,
+    D: Direction,
+{
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            n_edges: 0,
+            positions: None,
+            direction: PhantomData,
         }
+    }
 
-        pub fn from_nodes(nodes: Vec<N>) -> Self {
-            let n_nodes = nodes.len();
-            Self {
-                nodes,
-                edges: vec![Vec::new(); n_nodes],
-            }
+    pub fn from_nodes(nodes: Vec<N>) -> Self {
+        let n_nodes = nodes.len();
+        Self {
+            nodes,
+            edges: vec![Vec::new(); n_nodes],
+            n_edges: 0,
+            positions: None,
+            direction: PhantomData,
         }
+    }
 
-        pub fn size(&self) -> (usize, usize) {
-            let n_nodes = self.nodes.len();
-            let n_edges = self.edges.iter().map(|neigh| neigh.len()).sum();
-            (n_nodes, n_edges)
+    // Поиск ребра за O(1) вместо прохода по списку соседей.
+    // В таком графе нет кратных рёбер: add_edge существующего ребра
+    // заменяет его вес.
+    pub fn with_hashed_adjacency(mut self) -> Self {
+        let positions = self.edges.iter()
+            .map(|neigh| {
+                let mut node_positions = HashMap::with_capacity(neigh.len());
+                for (pos, &(node_idx, _)) in neigh.iter().enumerate() {
+                    let duplicate = node_positions.insert(node_idx, pos).is_some();
+                    assert!(!duplicate, "Hashed adjacency doesn't support parallel edges");
+                }
+                node_positions
+            })
+            .collect();
+        self.positions = Some(positions);
+        self
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.nodes.len(), self.n_edges)
+    }
+
+    pub fn is_directed(&self) -> bool {
+        D::DIRECTED
+    }
+
+    pub fn neigh(&self, node_idx: usize) -> &[(usize, E)] {
+        &self.edges[node_idx]
+    }
+
+    pub fn add_node(&mut self, node: N) -> usize {
+        self.nodes.push(node);
+        self.edges.push(Vec::new());
+        if let Some(positions) = &mut self.positions {
+            positions.push(HashMap::new());
         }
+        self.nodes.len() - 1
+    }
+
+    // для неориентированного графа добавляет ребро в обе стороны
+    pub fn add_edge(&mut self, node1: usize, node2: usize, weight: E) {
+        if self.set_weight(node1, node2, weight) { return; }
 
-        pub fn neigh(&self, node_idx: usize) -> &[(usize, E)] {
-            &self.edges[node_idx]
+        self.push_arc(node1, node2, weight);
+        if !D::DIRECTED && node1 != node2 {
+            self.push_arc(node2, node1, weight);
         }
+        self.n_edges += 1;
+    }
+
+    pub fn edge(&self, node1: usize, node2: usize) -> Option<&E> {
+        let pos = self.position(node1, node2)?;
+        Some(&self.edges[node1][pos].1)
+    }
+
+    pub fn has_edge(&self, node1: usize, node2: usize) -> bool {
+        self.position(node1, node2).is_some()
+    }
+
+    // удаляет ребро (для кратных рёбер - раньше всех добавленное) и возвращает его вес
+    pub fn remove_edge(&mut self, node1: usize, node2: usize) -> Option<E> {
+        let pos = self.position(node1, node2)?;
+        let (_, weight) = self.remove_arc(node1, pos);
+        self.remove_back_arc(node1, node2, 0);
+        self.n_edges -= 1;
+        Some(weight)
+    }
+
+    // удаляет последнее ребро из списка соседей вершины
+    pub fn pop_edge(&mut self, node_idx: usize) -> Option<(usize, E)> {
+        let last_pos = self.edges[node_idx].len().checked_sub(1)?;
+        let next_node = self.edges[node_idx][last_pos].0;
+        let rank = self.edges[node_idx][..last_pos].iter().filter(|(idx, _)| *idx == next_node).count();
+        let (_, weight) = self.remove_arc(node_idx, last_pos);
+        self.remove_back_arc(node_idx, next_node, rank);
+        self.n_edges -= 1;
+        Some((next_node, weight))
+    }
+
+    // вершины с индексами
+    pub fn nodes(&self) -> impl Iterator<Item = (usize, &N)> + '_ {
+        self.nodes.iter().enumerate()
+    }
 
-        pub fn neigh_mut(&mut self, node_idx: usize) -> &mut Vec<(usize, E)> {
-            &mut self.edges[node_idx]
+    // рёбра (i, j, вес); неориентированное ребро выдаётся один раз, с i <= j
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, &E)> + '_ {
+        self.edges.iter()
+            .enumerate()
+            .flat_map(|(node1, neigh)| {
+                neigh.iter().map(move |(node2, weight)| (node1, *node2, weight))
+            })
+            .filter(|&(node1, node2, _)| D::DIRECTED || node1 <= node2)
+    }
+
+    fn position(&self, node1: usize, node2: usize) -> Option<usize> {
+        match &self.positions {
+            Some(positions) => positions[node1].get(&node2).copied(),
+            None => self.edges[node1].iter().position(|(idx, _)| *idx == node2),
         }
+    }
 
-        pub fn add_node(&mut self, node: N) -> usize {
-            self.nodes.push(node);
-            self.edges.push(Vec::new());
-            self.nodes.len() - 1
+    // true, если ребро уже было и граф без кратных рёбер
+    fn set_weight(&mut self, node1: usize, node2: usize, weight: E) -> bool {
+        if self.positions.is_none() { return false; }
+        let pos = match self.position(node1, node2) {
+            Some(pos) => pos,
+            None => return false,
+        };
+        self.edges[node1][pos].1 = weight;
+        if !D::DIRECTED && node1 != node2 {
+            let back_pos = self.position(node2, node1).unwrap();
+            self.edges[node2][back_pos].1 = weight;
         }
+        true
+    }
 
-        pub fn add_edge(&mut self, node1: usize, node2: usize, weight: E) {
-            self.edges[node1].push((node2, weight));
+    fn push_arc(&mut self, from: usize, to: usize, weight: E) {
+        self.edges[from].push((to, weight));
+        if let Some(positions) = &mut self.positions {
+            positions[from].insert(to, self.edges[from].len() - 1);
         }
+    }
 
-        pub fn add_edge_undirected(&mut self, node1: usize, node2: usize, weight: E) {
-            self.edges[node1].push((node2, weight));
-            self.edges[node2].push((node1, weight));
+    // без хеш-смежности порядок дуг сохраняется: на этом держится remove_back_arc
+    fn remove_arc(&mut self, from: usize, pos: usize) -> (usize, E) {
+        let positions = match &mut self.positions {
+            Some(positions) => positions,
+            None => return self.edges[from].remove(pos),
+        };
+        let arc = self.edges[from].swap_remove(pos);
+        positions[from].remove(&arc.0);
+        if let Some(&(moved, _)) = self.edges[from].get(pos) {
+            positions[from].insert(moved, pos);
         }
+        arc
+    }
+
+    // Встречная дуга неориентированного ребра, rank-я по счёту дуга node1 -> node2
+    // среди кратных. Кратные рёбра лежат в списках обоих концов в порядке
+    // добавления, поэтому ей соответствует rank-я дуга node2 -> node1.
+    fn remove_back_arc(&mut self, node1: usize, node2: usize, rank: usize) {
+        if D::DIRECTED || node1 == node2 { return; }
+        let back_pos = match &self.positions {
+            Some(positions) => positions[node2][&node1],
+            None => self.edges[node2].iter()
+                .enumerate()
+                .filter(|(_, (idx, _))| *idx == node1)
+                .nth(rank)
+                .map(|(pos, _)| pos)
+                .unwrap(),
+        };
+        self.remove_arc(node2, back_pos);
+    }
+}
+
+impl<N, E> Graph<N, E, Directed>
+where E: Copy,
+{
+    // пара встречных дуг; в отличие от Undirected это два разных ребра
+    pub fn add_edge_undirected(&mut self, node1: usize, node2: usize, weight: E) {
+        self.add_edge(node1, node2, weight);
+        self.add_edge(node2, node1, weight);
+    }
+}
+
+impl<N, E, D> Default for Graph<N, E, D>
+where
+    E: Copy,
+    D: Direction,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E, D> graphs::Graph for Graph<N, E, D>
+where
+    E: Copy,
+    D: Direction,
+{
+    type Edge = E;
+
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn weight(&self, node1: usize, node2: usize) -> Option<E> {
+        self.edge(node1, node2).copied()
+    }
+
+    fn is_directed(&self) -> bool {
+        D::DIRECTED
+    }
+}
+
+impl<N, E, D> graphs::AdjacencyGraph for Graph<N, E, D>
+where
+    E: Copy,
+    D: Direction,
+{
+    type Neighbors<'a> = Copied<Iter<'a, (usize, E)>> where Self: 'a;
+
+    fn neighbors(&self, node_idx: usize) -> Self::Neighbors<'_> {
+        self.edges[node_idx].iter().copied()
+    }
+}
+
+// graph[i] - значение в вершине
+impl<N, E, D> Index<usize> for Graph<N, E, D> {
+    type Output = N;
+
+    fn index(&self, node_idx: usize) -> &Self::Output {
+        &self.nodes[node_idx]
+    }
+}
+
+// graph[(i, j)] - значение ребра (i, j)
+impl<N, E, D> Index<(usize, usize)> for Graph<N, E, D>
+where
+    E: Copy,
+    D: Direction,
+{
+    type Output = E;
+
+    fn index(&self, edge_idx: (usize, usize)) -> &Self::Output {
+        let (node1, node2) = edge_idx;
+        self.edge(node1, node2).expect("Edge doesn't exist")
     }
+}
 
-    // graph[i] - значение в вершине
-    impl<N, E> Index<usize> for Graph<N, E> {
-        type Output = N;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Xoshiro256ss;
+
+    // рёбра (i, j, вес) в порядке добавления
+    struct Model {
+        directed: bool,
+        edges: Vec<(usize, usize, u32)>,
+    }
+
+    impl Model {
+        fn matches(&self, edge: (usize, usize, u32), node1: usize, node2: usize) -> bool {
+            let (from, to, _) = edge;
+            (from, to) == (node1, node2) || !self.directed && (from, to) == (node2, node1)
+        }
 
-        fn index(&self, node_idx: usize) -> &Self::Output {
-            &self.nodes[node_idx]
+        fn find(&self, node1: usize, node2: usize) -> Option<usize> {
+            self.edges.iter().position(|&edge| self.matches(edge, node1, node2))
         }
     }
 
-    // graph[(i, j)] - значение ребра (i, j)
-    impl<N, E> Index<(usize, usize)> for Graph<N, E> {
-        type Output = E;
+    // Случайные добавления и удаления; без хеш-смежности удаляется самое
+    // раннее из кратных рёбер, pop_edge - самое позднее ребро вершины.
+    fn check_against_model<D: Direction>(rng: &mut Xoshiro256ss, hashed: bool) {
+        let n_nodes = rng.gen_range(1..5);
+        let mut graph: Graph<(), u32, D> = Graph::from_nodes(vec![(); n_nodes]);
+        if hashed { graph = graph.with_hashed_adjacency(); }
+        let mut model = Model { directed: D::DIRECTED, edges: Vec::new() };
+        for weight in 0..60 {
+            let (node1, node2) = (rng.gen_range(0..n_nodes), rng.gen_range(0..n_nodes));
+            match rng.gen_range(0..4) {
+                0 | 1 => {
+                    graph.add_edge(node1, node2, weight);
+                    match model.find(node1, node2) {
+                        Some(pos) if hashed => model.edges[pos].2 = weight,
+                        _ => model.edges.push((node1, node2, weight)),
+                    }
+                }
+                2 => {
+                    let expected = model.find(node1, node2).map(|pos| model.edges.remove(pos).2);
+                    assert_eq!(graph.remove_edge(node1, node2), expected);
+                }
+                _ => {
+                    let popped = graph.pop_edge(node1);
+                    let incident = |&(from, to, _): &(usize, usize, u32)| from == node1 || !D::DIRECTED && to == node1;
+                    let pos = match popped {
+                        Some((_, weight)) if hashed => model.edges.iter().position(|edge| edge.2 == weight),
+                        _ => model.edges.iter().rposition(incident),
+                    };
+                    let expected = pos.map(|pos| model.edges.remove(pos)).map(|(from, to, weight)| {
+                        (if from == node1 { to } else { from }, weight)
+                    });
+                    assert_eq!(popped, expected);
+                }
+            }
+
+            assert_eq!(graph.size(), (n_nodes, model.edges.len()));
+            assert_eq!(graph.edges().count(), model.edges.len());
+            for node1 in 0..n_nodes {
+                for node2 in 0..n_nodes {
+                    let expected = model.find(node1, node2).map(|pos| model.edges[pos].2);
+                    assert_eq!(graph.edge(node1, node2).copied(), expected);
+                }
+            }
+            let mut listed: Vec<(usize, usize, u32)> = graph.edges().map(|(node1, node2, &weight)| (node1, node2, weight)).collect();
+            let mut expected: Vec<(usize, usize, u32)> = model.edges.iter()
+                .map(|&(from, to, weight)| if D::DIRECTED { (from, to, weight) } else { (from.min(to), from.max(to), weight) })
+                .collect();
+            listed.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(listed, expected);
+        }
+    }
 
-        fn index(&self, edge_idx: (usize, usize)) -> &Self::Output {
-            let (node1, node2) = edge_idx;
-            let edge = self.edges[node1].iter().find(|(idx, _)| *idx == node2);
-            let (_, weight) = edge.expect("Edge doesn't exist");
-            weight
+    #[test]
+    fn edges_match_model() {
+        let mut rng = Xoshiro256ss::new(5);
+        for _ in 0..300 {
+            check_against_model::<Directed>(&mut rng, false);
+            check_against_model::<Directed>(&mut rng, true);
+            check_against_model::<Undirected>(&mut rng, false);
+            check_against_model::<Undirected>(&mut rng, true);
         }
     }
-}
\ No newline at end of file
+
+    // встречная дуга удаляется у того же из кратных рёбер
+    #[test]
+    fn undirected_parallel_edges_stay_paired() {
+        let mut graph: UndirectedGraph<(), u32> = Graph::from_nodes(vec![(); 5]);
+        graph.add_edge(2, 4, 9);
+        graph.add_edge(1, 2, 5);
+        graph.add_edge(1, 2, 7);
+        assert_eq!(graph.remove_edge(2, 4), Some(9));
+        assert_eq!(graph.remove_edge(1, 2), Some(5));
+        assert_eq!((graph.edge(1, 2), graph.edge(2, 1)), (Some(&7), Some(&7)));
+        assert_eq!(graph.size(), (5, 1));
+    }
+
+    #[test]
+    fn self_loops_are_counted_once() {
+        let mut graph: UndirectedGraph<(), u32> = Graph::from_nodes(vec![(); 2]);
+        graph.add_edge(0, 0, 1);
+        graph.add_edge(0, 1, 2);
+        assert_eq!(graph.size(), (2, 2));
+        assert_eq!(graph.neigh(0).len(), 2);
+        let edges: Vec<(usize, usize, &u32)> = graph.edges().collect();
+        assert_eq!(edges, [(0, 0, &1), (0, 1, &2)]);
+        assert_eq!(graph.pop_edge(1), Some((0, 2)));
+        assert_eq!(graph.pop_edge(0), Some((0, 1)));
+        assert_eq!(graph.size(), (2, 0));
+    }
+}
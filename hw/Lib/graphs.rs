@@ -0,0 +1,55 @@
+// Общий интерфейс графов, чтобы алгоритмы (остов, эйлеров обход,
+// кратчайшие пути) писались один раз для плотных и разреженных графов.
+// Вершины - индексы 0..num_nodes().
+pub trait Graph {
+    type Edge: Copy;
+
+    fn num_nodes(&self) -> usize;
+
+    // вес ребра (i, j), None - ребра нет
+    fn weight(&self, node1: usize, node2: usize) -> Option<Self::Edge>;
+
+    // в неориентированном графе ребро (i, j) видно и из i, и из j
+    fn is_directed(&self) -> bool {
+        true
+    }
+
+    // полный граф: weight(i, j) есть для любых i != j
+    fn is_complete(&self) -> bool {
+        false
+    }
+}
+
+// Граф, который умеет перечислять соседей вершины вместе с весами рёбер.
+pub trait AdjacencyGraph: Graph {
+    type Neighbors<'a>: Iterator<Item = (usize, Self::Edge)> where Self: 'a;
+
+    fn neighbors(&self, node_idx: usize) -> Self::Neighbors<'_>;
+}
+
+// Соседи в полном графе: все вершины, кроме самой вершины.
+pub struct AllNeighbors<'a, G> {
+    graph: &'a G,
+    node_idx: usize,
+    next_idx: usize,
+}
+
+impl<'a, G> AllNeighbors<'a, G> {
+    pub fn new(graph: &'a G, node_idx: usize) -> Self {
+        Self { graph, node_idx, next_idx: 0 }
+    }
+}
+
+impl<G: Graph> Iterator for AllNeighbors<'_, G> {
+    type Item = (usize, G::Edge);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_idx == self.node_idx { self.next_idx += 1; }
+        if self.next_idx >= self.graph.num_nodes() { return None; }
+
+        let neighbor = self.next_idx;
+        self.next_idx += 1;
+        let weight = self.graph.weight(self.node_idx, neighbor).expect("Complete graph misses an edge");
+        Some((neighbor, weight))
+    }
+}
@@ -1,32 +1,252 @@
-mod io {
-    use std::io::BufRead;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
 
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
+// Ошибка чтения входных данных. Позиция (line, column) считается с 1
+// и указывает на начало токена или на место, где данные закончились.
+#[derive(Debug)]
+pub enum ScanError {
+    UnexpectedEof { line: usize, column: usize },
+    Parse { token: String, expected: &'static str, line: usize, column: usize },
+    Io { error: std::io::Error, line: usize, column: usize },
+}
+
+impl ScanError {
+    pub fn position(&self) -> (usize, usize) {
+        match self {
+            ScanError::UnexpectedEof { line, column }
+            | ScanError::Parse { line, column, .. }
+            | ScanError::Io { line, column, .. } => (*line, *column),
+        }
+    }
+
+    // для main: сообщить об ошибке во входных данных и завершиться
+    pub fn exit(self) -> ! {
+        eprintln!("Input error: {}", self);
+        std::process::exit(1)
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::UnexpectedEof { line, column } =>
+                write!(f, "unexpected end of input at {}:{}", line, column),
+            ScanError::Parse { token, expected, line, column } =>
+                write!(f, "failed to parse '{}' as {} at {}:{}", token, expected, line, column),
+            ScanError::Io { error, line, column } =>
+                write!(f, "failed to read input at {}:{}: {}", line, column, error),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScanError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+pub struct Scanner<B> {
+    reader: B,
+    buf_str: String,
+    next_str: String,
+    buf_pos: usize,
+    line: usize,
+}
+
+impl<B: BufRead> Scanner<B> {
+    pub fn new(reader: B) -> Self {
+        Self {
+            reader,
+            buf_str: String::new(),
+            next_str: String::new(),
+            buf_pos: 0,
+            line: 0,
+        }
     }
 
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
+    // позиция следующего непрочитанного символа
+    pub fn position(&self) -> (usize, usize) {
+        let line_end = self.buf_str.trim_end_matches(['\n', '\r']).len();
+        (self.line.max(1), self.column(self.buf_pos.min(line_end)))
+    }
+
+    fn column(&self, byte_pos: usize) -> usize {
+        self.buf_str[..byte_pos].chars().count() + 1
+    }
+
+    fn rest_is_blank(&self) -> bool {
+        self.buf_str[self.buf_pos..].trim().is_empty()
+    }
+
+    // читает следующую строку в буфер, false - если вход закончился
+    // (тогда буфер не меняется, чтобы позиция указывала на конец входа)
+    fn read_line(&mut self) -> Result<bool, ScanError> {
+        self.next_str.clear();
+        match self.reader.read_line(&mut self.next_str) {
+            Ok(0) => Ok(false),
+            Ok(_) => {
+                std::mem::swap(&mut self.buf_str, &mut self.next_str);
+                self.buf_pos = 0;
+                self.line += 1;
+                Ok(true)
+            }
+            Err(error) => {
+                let (line, column) = self.position();
+                Err(ScanError::Io { error, line, column })
             }
         }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
-                }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
+    }
+
+    // границы следующего токена в buf_str
+    fn next_token(&mut self) -> Result<Option<(usize, usize)>, ScanError> {
+        loop {
+            let rest = &self.buf_str[self.buf_pos..];
+            if let Some(offset) = rest.find(|c: char| !c.is_whitespace()) {
+                let start = self.buf_pos + offset;
+                let end = self.buf_str[start..]
+                    .find(char::is_whitespace)
+                    .map_or(self.buf_str.len(), |len| start + len);
+                self.buf_pos = end;
+                return Ok(Some((start, end)));
             }
+            self.buf_pos = self.buf_str.len();
+            if !self.read_line()? { return Ok(None); }
         }
     }
 
-}
\ No newline at end of file
+    fn parse<T: FromStr>(&self, start: usize, end: usize) -> Result<T, ScanError> {
+        let token = &self.buf_str[start..end];
+        token.parse().map_err(|_| ScanError::Parse {
+            token: token.to_string(),
+            expected: std::any::type_name::<T>(),
+            line: self.line,
+            column: self.column(start),
+        })
+    }
+
+    // следующий токен; конец входа - ошибка
+    pub fn token<T: FromStr>(&mut self) -> Result<T, ScanError> {
+        match self.try_token()? {
+            Some(token) => Ok(token),
+            None => {
+                let (line, column) = self.position();
+                Err(ScanError::UnexpectedEof { line, column })
+            }
+        }
+    }
+
+    // следующий токен или None, если вход закончился
+    pub fn try_token<T: FromStr>(&mut self) -> Result<Option<T>, ScanError> {
+        match self.next_token()? {
+            Some((start, end)) => self.parse(start, end).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn tokens<T: FromStr>(&mut self, n: usize) -> Result<Vec<T>, ScanError> {
+        (0..n).map(|_| self.token()).collect()
+    }
+
+    // остаток текущей строки без перевода строки; если он пуст - следующая строка
+    pub fn line(&mut self) -> Result<String, ScanError> {
+        if self.rest_is_blank() && !self.read_line()? {
+            let (line, column) = self.position();
+            return Err(ScanError::UnexpectedEof { line, column });
+        }
+        let line = self.buf_str[self.buf_pos..].trim_end_matches(['\n', '\r']).to_string();
+        self.buf_pos = self.buf_str.len();
+        Ok(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Read};
+
+    fn scanner(input: &str) -> Scanner<&[u8]> {
+        Scanner::new(input.as_bytes())
+    }
+
+    #[test]
+    fn tokens_across_lines() {
+        let mut scan = scanner("3 -1\n\n  2.5\r\nword\t7");
+        assert_eq!(scan.token::<usize>().unwrap(), 3);
+        assert_eq!(scan.token::<i64>().unwrap(), -1);
+        assert_eq!(scan.token::<f64>().unwrap(), 2.5);
+        assert_eq!(scan.token::<String>().unwrap(), "word");
+        assert_eq!(scan.tokens::<u8>(1).unwrap(), [7]);
+        // конец входа повторяется
+        assert_eq!(scan.try_token::<u8>().unwrap(), None);
+        assert_eq!(scan.try_token::<u8>().unwrap(), None);
+    }
+
+    #[test]
+    fn eof_position() {
+        let eof = |input: &str, n: usize| {
+            let mut scan = scanner(input);
+            let tokens: Result<Vec<String>, _> = scan.tokens(n);
+            match tokens.err().unwrap() {
+                ScanError::UnexpectedEof { line, column } => (line, column),
+                error => panic!("unexpected error {}", error),
+            }
+        };
+        assert_eq!(eof("", 1), (1, 1));
+        assert_eq!(eof("1 2\n", 3), (1, 4));
+        assert_eq!(eof("1 2", 3), (1, 4));
+        assert_eq!(eof("1 2\r\n", 3), (1, 4));
+        assert_eq!(eof("1\n22\n\n", 3), (3, 1));
+    }
+
+    #[test]
+    fn parse_error_position() {
+        let mut scan = scanner("1 x\n  ёж 5");
+        assert_eq!(scan.token::<i32>().unwrap(), 1);
+        let error = scan.token::<i32>().err().unwrap();
+        assert_eq!(error.position(), (1, 3));
+        assert_eq!(error.to_string(), "failed to parse 'x' as i32 at 1:3");
+        // колонка считается в символах, а не в байтах
+        assert_eq!(scan.token::<String>().unwrap(), "ёж");
+        let mut scan = scanner("ёж x");
+        scan.token::<String>().unwrap();
+        assert_eq!(scan.token::<u8>().err().unwrap().position(), (1, 4));
+    }
+
+    #[test]
+    fn lines() {
+        let mut scan = scanner("3\nhello world\r\nlast");
+        assert_eq!(scan.token::<u8>().unwrap(), 3);
+        assert_eq!(scan.line().unwrap(), "hello world");
+        assert_eq!(scan.line().unwrap(), "last");
+        assert!(matches!(scan.line(), Err(ScanError::UnexpectedEof { line: 3, column: 5 })));
+    }
+
+    // отдаёт первую строку, затем ошибку чтения
+    struct FailingReader {
+        data: &'static [u8],
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.data.is_empty() { return Err(std::io::Error::other("broken pipe")); }
+            let len = self.data.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn io_error_position() {
+        let mut scan = Scanner::new(BufReader::new(FailingReader { data: b"1 2\n" }));
+        assert_eq!(scan.tokens::<u8>(2).unwrap(), [1, 2]);
+        let error = scan.token::<u8>().err().unwrap();
+        assert!(matches!(error, ScanError::Io { line: 1, column: 4, .. }));
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!(error.to_string(), "failed to read input at 1:4: broken pipe");
+    }
+}
@@ -0,0 +1,571 @@
+// Рюкзак 0-1: предметы, решение и псевдополиномиальные динамики.
+// Каждая динамика есть в двух вариантах: с таблицей решений
+// (n * размер_таблицы бит) и компактный O(размер_таблицы) памяти,
+// восстанавливающий ответ разбиением Хиршберга.
+// Ценности - любой Value; вызывающий заранее проверяет, что сумма всех
+// ценностей представима (Value::checked_total), тогда суммы подмножеств
+// не переполняются.
+use crate::numeric::Value;
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Item<V = usize> {
+    pub volume: usize,
+    pub value: V,
+    pub index: usize, // номер во входных данных
+}
+
+// Сравнение удельных ценностей value / volume без деления и f64.
+// Предмет нулевого объёма - с бесконечной удельной ценностью,
+// два таких сравниваются по ценности.
+pub fn density_cmp<V: Value>(item1: &Item<V>, item2: &Item<V>) -> Ordering {
+    ratio_cmp((item1.value, item1.volume), (item2.value, item2.volume))
+}
+
+// То же для пар (ценность, объём), например приростов выпуклой оболочки.
+// Порядок полный, в том числе для пар с нулевыми объёмом и ценностью.
+pub fn ratio_cmp<V: Value>((value1, volume1): (V, usize), (value2, volume2): (V, usize)) -> Ordering {
+    match (volume1, volume2) {
+        (0, 0) => value1.cmp(&value2),
+        (0, _) => Ordering::Greater,
+        (_, 0) => Ordering::Less,
+        _ => value1.cross_cmp(volume1, value2, volume2),
+    }
+}
+
+// Решение: ценность, суммарный объём и номера предметов в порядке ввода
+#[derive(Debug)]
+pub struct Solution<V = usize> {
+    pub value: V,
+    pub volume: usize,
+    pub items: Vec<usize>,
+}
+
+impl<V: Value> Solution<V> {
+    // chosen - позиции взятых предметов в items
+    pub fn from_chosen<I: IntoIterator<Item = usize>>(items: &[Item<V>], chosen: I) -> Self {
+        let mut solution = Solution { value: V::default(), volume: 0, items: Vec::new() };
+        for pos in chosen {
+            solution.value = solution.value + items[pos].value;
+            solution.volume += items[pos].volume;
+            solution.items.push(items[pos].index);
+        }
+        solution.items.sort_unstable();
+        solution
+    }
+
+    // решение вместе с ещё не входившими в него предметами
+    pub fn with_items<'a, I: IntoIterator<Item = &'a Item<V>>>(mut self, items: I) -> Self
+    where
+        V: 'a,
+    {
+        for item in items {
+            self.value = self.value + item.value;
+            self.volume += item.volume;
+            self.items.push(item.index);
+        }
+        self.items.sort_unstable();
+        self
+    }
+}
+
+// n строк по width бит: был ли взят предмет в клетке динамики
+struct DecisionTable {
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl DecisionTable {
+    fn new(rows: usize, width: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self { words_per_row, bits: vec![0; rows * words_per_row] }
+    }
+
+    fn set(&mut self, row: usize, col: usize) {
+        self.bits[row * self.words_per_row + col / 64] |= 1 << (col % 64);
+    }
+
+    fn get(&self, row: usize, col: usize) -> bool {
+        self.bits[row * self.words_per_row + col / 64] & (1 << (col % 64)) != 0
+    }
+}
+
+// память таблицы решений в байтах, чтобы выбрать вариант динамики
+pub fn decision_table_bytes(n_items: usize, width: usize) -> usize {
+    n_items.saturating_mul(width.div_ceil(64)).saturating_mul(8)
+}
+
+// best[c] - наибольшая ценность при объёме не больше c
+fn best_by_capacity<V: Value>(capacity: usize, items: &[Item<V>]) -> Vec<V> {
+    let mut best = vec![V::default(); capacity + 1];
+    for item in items.iter() {
+        for c in (item.volume..=capacity).rev() {
+            best[c] = best[c].max(best[c - item.volume] + item.value);
+        }
+    }
+    best
+}
+
+// Динамика по вместимости: O(n * W) времени, n * W бит на восстановление.
+pub fn dp_by_capacity<V: Value>(capacity: usize, items: &[Item<V>]) -> Solution<V> {
+    let mut best = vec![V::default(); capacity + 1];
+    let mut taken = DecisionTable::new(items.len(), capacity + 1);
+    for (pos, item) in items.iter().enumerate() {
+        for c in (item.volume..=capacity).rev() {
+            let with_item = best[c - item.volume] + item.value;
+            if with_item > best[c] {
+                best[c] = with_item;
+                taken.set(pos, c);
+            }
+        }
+    }
+
+    let mut chosen = Vec::new();
+    let mut c = capacity;
+    for pos in (0..items.len()).rev() {
+        if taken.get(pos, c) {
+            chosen.push(pos);
+            c -= items[pos].volume;
+        }
+    }
+    Solution::from_chosen(items, chosen)
+}
+
+// То же за O(W) памяти: лучшие ценности двух половин предметов
+// сшиваются по разбиению вместимости, половины решаются рекурсивно.
+pub fn dp_by_capacity_compact<V: Value>(capacity: usize, items: &[Item<V>]) -> Solution<V> {
+    let mut chosen = Vec::new();
+    capacity_split(capacity, items, 0, &mut chosen);
+    Solution::from_chosen(items, chosen)
+}
+
+fn capacity_split<V: Value>(capacity: usize, items: &[Item<V>], offset: usize, chosen: &mut Vec<usize>) {
+    match items {
+        [] => {}
+        [item] => if item.volume <= capacity && !item.value.is_zero() { chosen.push(offset) },
+        _ => {
+            let mid = items.len() / 2;
+            let left = best_by_capacity(capacity, &items[..mid]);
+            let right = best_by_capacity(capacity, &items[mid..]);
+            let left_capacity = (0..=capacity)
+                .max_by_key(|&c| left[c] + right[capacity - c])
+                .unwrap();
+            capacity_split(left_capacity, &items[..mid], offset, chosen);
+            capacity_split(capacity - left_capacity, &items[mid..], offset + mid, chosen);
+        }
+    }
+}
+
+// lightest[v] - наименьший объём, набирающий ценность не меньше v
+// (usize::MAX - недостижимо); массив не убывает по v
+fn lightest_by_value(items: &[Item]) -> Vec<usize> {
+    let total_value: usize = items.iter().map(|item| item.value).sum();
+    let mut lightest = vec![usize::MAX; total_value + 1];
+    lightest[0] = 0;
+    for item in items.iter() {
+        for v in (1..=total_value).rev() {
+            let prev = lightest[v.saturating_sub(item.value)];
+            if prev != usize::MAX && prev + item.volume < lightest[v] {
+                lightest[v] = prev + item.volume;
+            }
+        }
+    }
+    lightest
+}
+
+// наибольшая ценность v, для которой lightest[v] <= capacity
+fn best_value(lightest: &[usize], capacity: usize) -> usize {
+    lightest.partition_point(|&volume| volume <= capacity) - 1
+}
+
+// Динамика по ценности: O(n * V) времени, где V - сумма ценностей.
+// Выгодна при большой вместимости и небольших ценностях.
+pub fn dp_by_value(capacity: usize, items: &[Item]) -> Solution {
+    let total_value: usize = items.iter().map(|item| item.value).sum();
+    let mut lightest = vec![usize::MAX; total_value + 1];
+    lightest[0] = 0;
+    let mut taken = DecisionTable::new(items.len(), total_value + 1);
+    for (pos, item) in items.iter().enumerate() {
+        for v in (1..=total_value).rev() {
+            let prev = lightest[v.saturating_sub(item.value)];
+            if prev != usize::MAX && prev + item.volume < lightest[v] {
+                lightest[v] = prev + item.volume;
+                taken.set(pos, v);
+            }
+        }
+    }
+
+    let mut chosen = Vec::new();
+    let mut v = best_value(&lightest, capacity);
+    for pos in (0..items.len()).rev() {
+        if v > 0 && taken.get(pos, v) {
+            chosen.push(pos);
+            v = v.saturating_sub(items[pos].value);
+        }
+    }
+    Solution::from_chosen(items, chosen)
+}
+
+// То же за O(V) памяти. Половины сшиваются двумя указателями:
+// lightest не убывает, поэтому при росте v1 лучшее v2 только уменьшается.
+pub fn dp_by_value_compact(capacity: usize, items: &[Item]) -> Solution {
+    let mut chosen = Vec::new();
+    value_split(capacity, items, 0, &mut chosen);
+    Solution::from_chosen(items, chosen)
+}
+
+fn value_split(capacity: usize, items: &[Item], offset: usize, chosen: &mut Vec<usize>) {
+    match items {
+        [] => {}
+        [item] => if item.volume <= capacity && item.value > 0 { chosen.push(offset) },
+        _ => {
+            let mid = items.len() / 2;
+            let left = lightest_by_value(&items[..mid]);
+            let right = lightest_by_value(&items[mid..]);
+
+            let mut best = (0, 0);
+            let mut v2 = right.len() - 1;
+            for (v1, &volume1) in left.iter().enumerate() {
+                if volume1 > capacity { break; }
+                while right[v2] > capacity - volume1 { v2 -= 1; }
+                if v1 + v2 > best.0 + best.1 { best = (v1, v2); }
+            }
+            // половины получают ровно тот объём, которым набирается их часть
+            let (v1, v2) = best;
+            value_split(left[v1], &items[..mid], offset, chosen);
+            value_split(right[v2], &items[mid..], offset + mid, chosen);
+        }
+    }
+}
+
+// Решение рюкзака с копиями: counts[i] - сколько копий items[i] взято
+#[derive(Debug)]
+pub struct MultiSolution {
+    pub value: usize,
+    pub volume: usize,
+    pub counts: Vec<usize>,
+}
+
+impl MultiSolution {
+    fn from_counts(items: &[Item], counts: Vec<usize>) -> Result<Self, MultiKnapsackError> {
+        let mut value: usize = 0;
+        let mut volume: usize = 0;
+        for (item, &count) in items.iter().zip(&counts) {
+            value = add_product(value, item.value, count)?;
+            volume = add_product(volume, item.volume, count)?;
+        }
+        Ok(MultiSolution { value, volume, counts })
+    }
+}
+
+// total + a * b без переполнения
+fn add_product(total: usize, a: usize, b: usize) -> Result<usize, MultiKnapsackError> {
+    a.checked_mul(b).and_then(|product| total.checked_add(product)).ok_or(MultiKnapsackError::Overflow)
+}
+
+// Рюкзак с копиями не решается: ценность не ограничена или не помещается в usize
+#[derive(Debug, PartialEq)]
+pub enum MultiKnapsackError {
+    Unbounded,
+    Overflow,
+}
+
+impl fmt::Display for MultiKnapsackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiKnapsackError::Unbounded =>
+                write!(f, "an item with zero volume and positive value has unlimited copies"),
+            MultiKnapsackError::Overflow => write!(f, "total value of the items overflows usize"),
+        }
+    }
+}
+
+impl std::error::Error for MultiKnapsackError {}
+
+// размеры частей двоичного разбиения: 1, 2, 4, ..., остаток
+fn binary_parts(mut copies: usize) -> impl Iterator<Item = usize> {
+    let mut part = 1;
+    std::iter::from_fn(move || {
+        if copies == 0 { return None; }
+        let size = part.min(copies);
+        copies -= size;
+        part *= 2;
+        Some(size)
+    })
+}
+
+// Ограниченный рюкзак: items[i] есть в copies[i] экземплярах.
+// Двоичное разбиение: из частей 1, 2, 4, ..., остаток набирается любое
+// число копий от 0 до c, поэтому части решаются как рюкзак 0-1
+// (компактной динамикой, если таблица решений больше max_table_bytes).
+// Сумма ценностей всех частей должна помещаться в usize, иначе Overflow.
+pub fn bounded_knapsack(
+    capacity: usize,
+    items: &[Item],
+    copies: &[usize],
+    max_table_bytes: usize,
+) -> Result<MultiSolution, MultiKnapsackError> {
+    let mut counts = vec![0; items.len()];
+    let mut parts = Vec::new();
+    let mut owners = Vec::new(); // (позиция предмета, число копий в части)
+    let mut total_value: usize = 0;
+    for (pos, (item, &copies)) in items.iter().zip(copies).enumerate() {
+        // предметы без объёма берутся все
+        let usable = capacity.checked_div(item.volume).map_or(copies, |fit| copies.min(fit));
+        total_value = add_product(total_value, item.value, usable)?;
+        if item.volume == 0 {
+            counts[pos] = copies;
+            continue;
+        }
+        // объём части не больше capacity, ценность - не больше total_value
+        for size in binary_parts(usable) {
+            parts.push(Item { volume: item.volume * size, value: item.value * size, index: parts.len() });
+            owners.push((pos, size));
+        }
+    }
+
+    let solution = if decision_table_bytes(parts.len(), capacity + 1) > max_table_bytes {
+        dp_by_capacity_compact(capacity, &parts)
+    } else {
+        dp_by_capacity(capacity, &parts)
+    };
+    for part_idx in solution.items {
+        let (pos, size) = owners[part_idx];
+        counts[pos] += size;
+    }
+    MultiSolution::from_counts(items, counts)
+}
+
+// Неограниченный рюкзак: копий каждого предмета сколько угодно.
+// best[c] = max(best[c - 1], best[c - w_i] + p_i). Пусть b - предмет
+// с наибольшей удельной ценностью. Если best[c] = best[c - w_b] + p_b
+// для w_max подряд идущих c, то и для всех больших c: дальше ответ
+// периодичен, и вместимость уменьшается добавлением копий b.
+// Unbounded - ценный предмет нулевого объёма, Overflow - ценность
+// решения не помещается в usize.
+pub fn unbounded_knapsack(capacity: usize, items: &[Item]) -> Result<MultiSolution, MultiKnapsackError> {
+    if items.iter().any(|item| item.volume == 0 && item.value > 0) { return Err(MultiKnapsackError::Unbounded); }
+    let useful: Vec<usize> = (0..items.len())
+        .filter(|&pos| items[pos].volume > 0 && items[pos].volume <= capacity && items[pos].value > 0)
+        .collect();
+    let mut counts = vec![0; items.len()];
+    let best_item = match useful.iter().copied().max_by(|&pos1, &pos2| density_cmp(&items[pos1], &items[pos2])) {
+        Some(pos) => pos,
+        None => return MultiSolution::from_counts(items, counts),
+    };
+    let (best_volume, best_value) = (items[best_item].volume, items[best_item].value);
+    let max_volume = useful.iter().map(|&pos| items[pos].volume).max().unwrap();
+
+    // choice[c] - предмет, взятый последним при вместимости c (None - best[c - 1])
+    let mut best = vec![0; 1];
+    let mut choice = vec![None; 1];
+    let mut periodic_run = 0;
+    let mut last = 0;
+    while last < capacity && periodic_run < max_volume {
+        let c = last + 1;
+        let (mut value, mut taken) = (best[c - 1], None);
+        for &pos in useful.iter() {
+            let item = &items[pos];
+            if item.volume > c { continue; }
+            let with_item = best[c - item.volume].checked_add(item.value).ok_or(MultiKnapsackError::Overflow)?;
+            if with_item > value {
+                value = with_item;
+                taken = Some(pos);
+            }
+        }
+        best.push(value);
+        choice.push(taken);
+        periodic_run = if c >= best_volume && value == best[c - best_volume] + best_value { periodic_run + 1 } else { 0 };
+        last = c;
+    }
+
+    // вместимость сверх посчитанной заполняется копиями b
+    let extra = (capacity - last).div_ceil(best_volume);
+    counts[best_item] += extra;
+    let mut c = capacity - extra * best_volume;
+    while c > 0 {
+        match choice[c] {
+            Some(pos) => {
+                counts[pos] += 1;
+                c -= items[pos].volume;
+            }
+            None => c -= 1,
+        }
+    }
+    MultiSolution::from_counts(items, counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Xoshiro256ss;
+
+    // есть предметы нулевого объёма, нулевой ценности и не влезающие в рюкзак
+    fn random_items(rng: &mut Xoshiro256ss, num_items: usize, max_volume: usize) -> Vec<Item> {
+        (0..num_items)
+            .map(|index| Item { volume: rng.gen_range(0..max_volume + 1), value: rng.gen_range(0..30), index })
+            .collect()
+    }
+
+    fn brute_force(capacity: usize, items: &[Item]) -> usize {
+        (0..1usize << items.len())
+            .filter_map(|mask| {
+                let chosen = items.iter().enumerate().filter(|(pos, _)| mask >> pos & 1 == 1);
+                let (volume, value) = chosen.fold((0, 0), |(volume, value), (_, item)| (volume + item.volume, value + item.value));
+                (volume <= capacity).then_some(value)
+            })
+            .max()
+            .unwrap()
+    }
+
+    // набор предметов допустим и даёт заявленные ценность и объём
+    fn check_solution(capacity: usize, items: &[Item], solution: &Solution) {
+        for window in solution.items.windows(2) {
+            assert!(window[0] < window[1], "items are not distinct: {:?}", solution.items);
+        }
+        let volume: usize = solution.items.iter().map(|&index| items[index].volume).sum();
+        let value: usize = solution.items.iter().map(|&index| items[index].value).sum();
+        assert_eq!(volume, solution.volume);
+        assert_eq!(value, solution.value);
+        assert!(volume <= capacity);
+    }
+
+    #[test]
+    fn compact_dp_match_full_tables() {
+        let mut rng = Xoshiro256ss::new(13);
+        for _ in 0..400 {
+            let num_items = rng.gen_range(0..13);
+            let capacity = rng.gen_range(0..40);
+            let items = random_items(&mut rng, num_items, 30);
+            let optimum = brute_force(capacity, &items);
+
+            let solutions = [
+                dp_by_capacity(capacity, &items),
+                dp_by_capacity_compact(capacity, &items),
+                dp_by_value(capacity, &items),
+                dp_by_value_compact(capacity, &items),
+            ];
+            for solution in &solutions {
+                check_solution(capacity, &items, solution);
+                assert_eq!(solution.value, optimum, "capacity {}, items {:?}", capacity, items);
+            }
+        }
+    }
+
+    // сотни предметов: глубокая рекурсия сшивания половин
+    #[test]
+    fn compact_dp_on_many_items() {
+        let mut rng = Xoshiro256ss::new(14);
+        for _ in 0..10 {
+            let num_items = rng.gen_range(100..300);
+            let capacity = rng.gen_range(50..500);
+            let items = random_items(&mut rng, num_items, 100);
+
+            let full = dp_by_capacity(capacity, &items);
+            let compact = dp_by_capacity_compact(capacity, &items);
+            check_solution(capacity, &items, &compact);
+            assert_eq!(compact.value, full.value);
+
+            let full = dp_by_value(capacity, &items);
+            let compact = dp_by_value_compact(capacity, &items);
+            check_solution(capacity, &items, &compact);
+            assert_eq!(compact.value, full.value);
+        }
+    }
+
+    #[test]
+    fn ratio_order_is_total() {
+        let pairs: [(usize, usize); 7] = [(0, 0), (3, 0), (0, 5), (2, 4), (1, 2), (3, 2), (5, 0)];
+        for a in pairs {
+            for b in pairs {
+                assert_eq!(ratio_cmp(a, b), ratio_cmp(b, a).reverse());
+                for c in pairs {
+                    if ratio_cmp(a, b).is_le() && ratio_cmp(b, c).is_le() {
+                        assert!(ratio_cmp(a, c).is_le(), "{:?} {:?} {:?}", a, b, c);
+                    }
+                }
+            }
+        }
+        assert_eq!(ratio_cmp((1usize, 2), (2, 4)), Ordering::Equal);
+        assert_eq!(ratio_cmp((0usize, 0), (9, 1)), Ordering::Greater);
+        assert_eq!(ratio_cmp((3usize, 0), (5, 0)), Ordering::Less);
+    }
+
+    // лучшая ценность перебором числа копий каждого предмета
+    fn brute_force_copies(capacity: usize, items: &[Item], copies: &[usize]) -> usize {
+        match items.split_first() {
+            None => 0,
+            Some((item, rest)) => (0..=copies[0])
+                .take_while(|&count| item.volume * count <= capacity)
+                .map(|count| item.value * count + brute_force_copies(capacity - item.volume * count, rest, &copies[1..]))
+                .max()
+                .unwrap(),
+        }
+    }
+
+    fn check_multi_solution(capacity: usize, items: &[Item], copies: &[usize], solution: &MultiSolution) {
+        let volume: usize = items.iter().zip(&solution.counts).map(|(item, count)| item.volume * count).sum();
+        let value: usize = items.iter().zip(&solution.counts).map(|(item, count)| item.value * count).sum();
+        assert_eq!((volume, value), (solution.volume, solution.value));
+        assert!(volume <= capacity);
+        assert!(solution.counts.iter().zip(copies).all(|(count, copies)| count <= copies));
+    }
+
+    #[test]
+    fn bounded_knapsack_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(16);
+        for round in 0..300 {
+            let num_items = rng.gen_range(0..5);
+            let capacity = rng.gen_range(0..30);
+            let items = random_items(&mut rng, num_items, 12);
+            let copies: Vec<usize> = (0..num_items).map(|_| rng.gen_range(0..6)).collect();
+            let optimum = brute_force_copies(capacity, &items, &copies);
+            // компактная динамика - при нулевом пределе таблицы
+            let max_table_bytes = if round % 2 == 0 { usize::MAX } else { 0 };
+            let solution = bounded_knapsack(capacity, &items, &copies, max_table_bytes).unwrap();
+            check_multi_solution(capacity, &items, &copies, &solution);
+            assert_eq!(solution.value, optimum, "capacity {}, items {:?}, copies {:?}", capacity, items, copies);
+        }
+    }
+
+    #[test]
+    fn unbounded_knapsack_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(17);
+        for _ in 0..300 {
+            let num_items = rng.gen_range(0..5);
+            let capacity = rng.gen_range(0..60);
+            let mut items = random_items(&mut rng, num_items, 12);
+            // ценный предмет без объёма делает ценность неограниченной
+            for item in items.iter_mut().filter(|item| item.volume == 0) {
+                item.value = 0;
+            }
+            let copies = vec![capacity; num_items];
+            let optimum = brute_force_copies(capacity, &items, &copies);
+            let solution = unbounded_knapsack(capacity, &items).unwrap();
+            check_multi_solution(capacity, &items, &vec![usize::MAX; num_items], &solution);
+            assert_eq!(solution.value, optimum, "capacity {}, items {:?}", capacity, items);
+        }
+        // большая вместимость: ответ дополняется копиями самого выгодного предмета
+        let items = [Item { volume: 7, value: 10, index: 0 }, Item { volume: 5, value: 7, index: 1 }];
+        let solution = unbounded_knapsack(1_000_003, &items).unwrap();
+        check_multi_solution(1_000_003, &items, &[usize::MAX; 2], &solution);
+        let best_of_small = brute_force_copies(1_000_003 % 35 + 35, &items, &[20, 20]);
+        assert_eq!(solution.value, (1_000_003 / 35 - 1) * 50 + best_of_small);
+    }
+
+    #[test]
+    fn multi_knapsack_errors() {
+        let free = Item { volume: 0, value: 1, index: 0 };
+        assert_eq!(unbounded_knapsack(10, &[free]).unwrap_err(), MultiKnapsackError::Unbounded);
+        // все копии предмета без объёма берутся, их ценность переполняется
+        let rich = Item { volume: 0, value: usize::MAX / 2, index: 0 };
+        assert_eq!(bounded_knapsack(10, &[rich], &[3], usize::MAX).unwrap_err(), MultiKnapsackError::Overflow);
+        assert_eq!(bounded_knapsack(10, &[rich], &[2], usize::MAX).unwrap().value, usize::MAX - 1);
+        // сумма частей двоичного разбиения переполняется
+        let heavy = Item { volume: 1, value: usize::MAX / 4, index: 0 };
+        assert_eq!(bounded_knapsack(10, &[heavy], &[100], usize::MAX).unwrap_err(), MultiKnapsackError::Overflow);
+        assert_eq!(unbounded_knapsack(10, &[heavy]).unwrap_err(), MultiKnapsackError::Overflow);
+        assert_eq!(unbounded_knapsack(3, &[heavy]).unwrap().value, usize::MAX / 4 * 3);
+    }
+}
@@ -0,0 +1,20 @@
+//! Общая библиотека для решений курса.
+//!
+//! Каждый модуль самодостаточен: `bundle` вставляет нужные модули
+//! в файл решения, чтобы его можно было отправить одним файлом.
+
+pub mod io;
+pub mod dzn;
+pub mod matrix;
+pub mod graphs;
+pub mod distance;
+pub mod graph;
+pub mod full_graph;
+pub mod paths;
+pub mod mst;
+pub mod euler;
+pub mod numeric;
+pub mod knapsack;
+pub mod bin_packing;
+pub mod rng;
+pub mod run;
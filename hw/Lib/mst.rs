@@ -0,0 +1,152 @@
+use crate::graphs::AdjacencyGraph;
+use std::cmp::Ordering;
+
+// Алгоритм Прима за O(n^2 + m): подходит и для полных графов, и для
+// разреженных. Для несвязного графа строит остовный лес.
+// Возвращает рёбра (вершина, родитель в дереве, вес).
+pub fn prim_mst<G>(graph: &G) -> Vec<(usize, usize, G::Edge)>
+where
+    G: AdjacencyGraph,
+    G::Edge: PartialOrd,
+{
+    let n_nodes = graph.num_nodes();
+    let mut in_mst = vec![false; n_nodes];
+    let mut remaining: Vec<usize> = (0..n_nodes).rev().collect();
+    // лучшее ребро (родитель, вес) из дерева в вершину
+    let mut best_edges: Vec<Option<(usize, G::Edge)>> = vec![None; n_nodes];
+
+    let improves = |best_edge: Option<(usize, G::Edge)>, weight: G::Edge| match best_edge {
+        Some((_, best_weight)) => weight < best_weight,
+        None => true,
+    };
+
+    while !remaining.is_empty() {
+        let (pos, _) = remaining.iter()
+            .enumerate()
+            .min_by(|(_, &node1), (_, &node2)| match (best_edges[node1], best_edges[node2]) {
+                (Some((_, w1)), Some((_, w2))) => w1.partial_cmp(&w2).unwrap_or(Ordering::Less),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => node1.cmp(&node2),
+            })
+            .unwrap();
+        let new_node_idx = remaining.swap_remove(pos);
+        in_mst[new_node_idx] = true;
+
+        // в полном графе перебираем только вершины вне дерева,
+        // чтобы не считать лишние веса
+        if graph.is_complete() {
+            for &next_idx in remaining.iter() {
+                let weight = graph.weight(new_node_idx, next_idx).unwrap();
+                if improves(best_edges[next_idx], weight) {
+                    best_edges[next_idx] = Some((new_node_idx, weight));
+                }
+            }
+        } else {
+            for (next_idx, weight) in graph.neighbors(new_node_idx) {
+                if !in_mst[next_idx] && improves(best_edges[next_idx], weight) {
+                    best_edges[next_idx] = Some((new_node_idx, weight));
+                }
+            }
+        }
+    }
+
+    best_edges.into_iter()
+        .enumerate()
+        .filter_map(|(node_idx, edge)| edge.map(|(parent, weight)| (node_idx, parent, weight)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::full_graph::FullGraph;
+    use crate::graph::UndirectedGraph;
+    use crate::rng::Xoshiro256ss;
+
+    fn find(parents: &mut [usize], node: usize) -> usize {
+        let mut root = node;
+        while parents[root] != root { root = parents[root]; }
+        parents[node] = root;
+        root
+    }
+
+    // вес и число рёбер остовного леса по Краскалу
+    fn kruskal(n_nodes: usize, mut edges: Vec<(usize, usize, u32)>) -> (u32, usize) {
+        edges.sort_unstable_by_key(|&(_, _, weight)| weight);
+        let mut parents: Vec<usize> = (0..n_nodes).collect();
+        let (mut total, mut count) = (0, 0);
+        for (node1, node2, weight) in edges {
+            let (root1, root2) = (find(&mut parents, node1), find(&mut parents, node2));
+            if root1 == root2 { continue; }
+            parents[root1] = root2;
+            total += weight;
+            count += 1;
+        }
+        (total, count)
+    }
+
+    // рёбра леса не образуют циклов, вес - сумма весов рёбер
+    fn forest_weight(n_nodes: usize, forest: &[(usize, usize, u32)]) -> (u32, usize) {
+        let mut parents: Vec<usize> = (0..n_nodes).collect();
+        for &(node, parent, _) in forest {
+            let (root1, root2) = (find(&mut parents, node), find(&mut parents, parent));
+            assert_ne!(root1, root2, "cycle in {:?}", forest);
+            parents[root1] = root2;
+        }
+        (forest.iter().map(|&(_, _, weight)| weight).sum(), forest.len())
+    }
+
+    #[test]
+    fn sparse_graph_matches_kruskal() {
+        let mut rng = Xoshiro256ss::new(6);
+        for _ in 0..300 {
+            let n_nodes = rng.gen_range(0..9);
+            let mut graph: UndirectedGraph<(), u32> = UndirectedGraph::from_nodes(vec![(); n_nodes]);
+            let mut edges = Vec::new();
+            // бывают петли, кратные рёбра и несвязные графы
+            for _ in 0..rng.gen_range(0..3 * n_nodes + 1) {
+                let (node1, node2, weight) = (rng.gen_range(0..n_nodes), rng.gen_range(0..n_nodes), rng.gen_range(0..20) as u32);
+                graph.add_edge(node1, node2, weight);
+                edges.push((node1, node2, weight));
+            }
+            let forest = prim_mst(&graph);
+            for &(node, parent, weight) in &forest {
+                assert!(graph.neigh(node).contains(&(parent, weight)));
+            }
+            assert_eq!(forest_weight(n_nodes, &forest), kruskal(n_nodes, edges));
+        }
+    }
+
+    #[test]
+    fn full_graph_matches_kruskal() {
+        let mut rng = Xoshiro256ss::new(7);
+        for _ in 0..300 {
+            let n_nodes = rng.gen_range(0..9);
+            let mut graph: FullGraph<(), u32> = FullGraph::new(n_nodes);
+            let mut edges = Vec::new();
+            for node1 in 0..n_nodes {
+                for node2 in node1 + 1..n_nodes {
+                    let weight = rng.gen_range(0..20) as u32;
+                    graph[(node1, node2)] = weight;
+                    graph[(node2, node1)] = weight;
+                    edges.push((node1, node2, weight));
+                }
+            }
+            let forest = prim_mst(&graph);
+            assert_eq!(forest_weight(n_nodes, &forest), kruskal(n_nodes, edges));
+            assert_eq!(forest.len(), n_nodes.saturating_sub(1));
+        }
+    }
+
+    #[test]
+    fn square_with_diagonal() {
+        let mut graph: UndirectedGraph<(), u32> = UndirectedGraph::from_nodes(vec![(); 4]);
+        for (node1, node2, weight) in [(0, 1, 1), (1, 2, 2), (2, 3, 1), (3, 0, 5), (0, 2, 3)] {
+            graph.add_edge(node1, node2, weight);
+        }
+        let mut forest = prim_mst(&graph);
+        forest.sort_unstable();
+        assert_eq!(forest, [(1, 0, 1), (2, 1, 2), (3, 2, 1)]);
+    }
+}
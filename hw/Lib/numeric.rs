@@ -0,0 +1,412 @@
+// Числа для ценностей: целые usize, u64, u128 и точные дроби Rational.
+// Все операции проверяемые: переполнение - None, а не молчаливый перенос.
+// Удельные ценности сравниваются точно, перекрёстным умножением
+// в 256 битах, без f64.
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display};
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+// Ценность предмета. Операторы + и - паникуют при переполнении: ими
+// пользуются только для сумм подмножеств, когда checked_total всех
+// ценностей уже проверен.
+pub trait Value:
+    Copy + Ord + Default + Debug + Display + FromStr + Add<Output = Self> + Sub<Output = Self>
+{
+    fn from_u64(value: u64) -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+
+    // self * part / whole, для целых с округлением вниз или вверх,
+    // для дробей точно; None - результат не представим
+    fn fraction_floor(self, part: usize, whole: usize) -> Option<Self>;
+    fn fraction_ceil(self, part: usize, whole: usize) -> Option<Self>;
+
+    // self / volume против other / other_volume: точное сравнение
+    // self * other_volume с other * volume
+    fn cross_cmp(self, volume: usize, other: Self, other_volume: usize) -> Ordering;
+
+    // целое значение (индекс в таблице динамики по ценности)
+    fn to_usize(self) -> Option<usize>;
+
+    // Сумма ценностей; Some - любая сумма подмножества тоже представима
+    fn checked_total<I: IntoIterator<Item = Self>>(values: I) -> Option<Self> {
+        values.into_iter().try_fold(Self::default(), Self::checked_add)
+    }
+
+    fn is_zero(self) -> bool {
+        self == Self::default()
+    }
+}
+
+// Полное произведение a * b: (старшие 128 бит, младшие 128 бит)
+pub fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & LOW);
+    let (b1, b0) = (b >> 64, b & LOW);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (p00 >> 64) + (p01 & LOW) + (p10 & LOW);
+    let low = (p00 & LOW) | (mid << 64);
+    let high = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (high, low)
+}
+
+// value * part / whole для целого value без промежуточного переполнения:
+// value = q * whole + r, r * part < 2^128. (floor, есть ли остаток);
+// None - переполнение или whole = 0, как у Rational
+fn scale_u128(value: u128, part: usize, whole: usize) -> Option<(u128, bool)> {
+    if whole == 0 { return None; }
+    let (part, whole) = (part as u128, whole as u128);
+    let (q, r) = (value / whole, value % whole);
+    let rest = r * part;
+    let floor = q.checked_mul(part)?.checked_add(rest / whole)?;
+    Some((floor, rest % whole != 0))
+}
+
+macro_rules! impl_integer_value {
+    ($($t:ty),*) => {$(
+        impl Value for $t {
+            fn from_u64(value: u64) -> Self {
+                value as $t
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+
+            fn fraction_floor(self, part: usize, whole: usize) -> Option<Self> {
+                let (floor, _) = scale_u128(self as u128, part, whole)?;
+                <$t>::try_from(floor).ok()
+            }
+
+            fn fraction_ceil(self, part: usize, whole: usize) -> Option<Self> {
+                let (floor, inexact) = scale_u128(self as u128, part, whole)?;
+                <$t>::try_from(floor.checked_add(inexact as u128)?).ok()
+            }
+
+            fn cross_cmp(self, volume: usize, other: Self, other_volume: usize) -> Ordering {
+                mul_wide(self as u128, other_volume as u128).cmp(&mul_wide(other as u128, volume as u128))
+            }
+
+            fn to_usize(self) -> Option<usize> {
+                usize::try_from(self).ok()
+            }
+        }
+    )*};
+}
+
+impl_integer_value!(usize, u64, u128);
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Неотрицательная несократимая дробь num / den. Знаменатель не больше
+// u64::MAX: тогда den * объём помещается в u128, и сравнение удельных
+// ценностей укладывается в 256 бит.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rational {
+    num: u128,
+    den: u128,
+}
+
+impl Rational {
+    // None - нулевой знаменатель или он слишком велик после сокращения
+    pub fn new(num: u128, den: u128) -> Option<Self> {
+        if den == 0 { return None; }
+        let g = gcd(num, den);
+        let (num, den) = (num / g, den / g);
+        (den <= u64::MAX as u128).then_some(Rational { num, den })
+    }
+
+    pub fn numer(&self) -> u128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> u128 {
+        self.den
+    }
+
+    // общий знаменатель двух дробей и числители, приведённые к нему
+    fn common(self, other: Self) -> Option<(u128, u128, u128)> {
+        let lcm = self.den / gcd(self.den, other.den) * other.den;
+        let num1 = self.num.checked_mul(lcm / self.den)?;
+        let num2 = other.num.checked_mul(lcm / other.den)?;
+        Some((num1, num2, lcm))
+    }
+
+    fn scale(self, part: usize, whole: usize) -> Option<Self> {
+        if whole == 0 { return None; }
+        // сокращаем заранее, чтобы реже переполняться
+        let g1 = gcd(part as u128, whole as u128).max(1);
+        let (part, whole) = (part as u128 / g1, whole as u128 / g1);
+        let g2 = gcd(self.num, whole).max(1);
+        let g3 = gcd(part, self.den).max(1);
+        let num = (self.num / g2).checked_mul(part / g3)?;
+        let den = (self.den / g3).checked_mul(whole / g2)?;
+        Rational::new(num, den)
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Rational { num: 0, den: 1 }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        mul_wide(self.num, other.den).cmp(&mul_wide(other.num, self.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Value::checked_add(self, other).expect("rational overflow")
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Value::checked_sub(self, other).expect("rational overflow")
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseRationalError;
+
+impl Display for ParseRationalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected a non-negative number like 7, 7/3 or 2.25")
+    }
+}
+
+// "7", "7/3" или десятичная запись "2.25"
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        let (num, den) = if let Some((num, den)) = token.split_once('/') {
+            if !digits(num) || !digits(den) { return Err(ParseRationalError); }
+            (num.parse().map_err(|_| ParseRationalError)?, den.parse().map_err(|_| ParseRationalError)?)
+        } else if let Some((whole, frac)) = token.split_once('.') {
+            if !digits(whole) || !digits(frac) { return Err(ParseRationalError); }
+            let den = 10u128.checked_pow(frac.len() as u32).ok_or(ParseRationalError)?;
+            let num = format!("{}{}", whole, frac).parse().map_err(|_| ParseRationalError)?;
+            (num, den)
+        } else {
+            if !digits(token) { return Err(ParseRationalError); }
+            (token.parse().map_err(|_| ParseRationalError)?, 1)
+        };
+        Rational::new(num, den).ok_or(ParseRationalError)
+    }
+}
+
+impl Value for Rational {
+    fn from_u64(value: u64) -> Self {
+        Rational { num: value as u128, den: 1 }
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let (num1, num2, den) = self.common(other)?;
+        Rational::new(num1.checked_add(num2)?, den)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        let (num1, num2, den) = self.common(other)?;
+        Rational::new(num1.checked_sub(num2)?, den)
+    }
+
+    fn fraction_floor(self, part: usize, whole: usize) -> Option<Self> {
+        self.scale(part, whole)
+    }
+
+    fn fraction_ceil(self, part: usize, whole: usize) -> Option<Self> {
+        self.scale(part, whole)
+    }
+
+    // den * объём < 2^128, остальное - одно умножение 128 x 128
+    fn cross_cmp(self, volume: usize, other: Self, other_volume: usize) -> Ordering {
+        let lhs = mul_wide(self.num, other.den * other_volume as u128);
+        let rhs = mul_wide(other.num, self.den * volume as u128);
+        lhs.cmp(&rhs)
+    }
+
+    fn to_usize(self) -> Option<usize> {
+        if self.den == 1 { usize::try_from(self.num).ok() } else { None }
+    }
+
+    // Знаменатель суммы любого подмножества делит НОК L всех знаменателей,
+    // а числитель при знаменателе L не больше total * L: если оба
+    // представимы, то и все частичные суммы (сложение идёт через НОК).
+    fn checked_total<I: IntoIterator<Item = Self>>(values: I) -> Option<Self> {
+        let mut total = Rational::default();
+        let mut lcm: u128 = 1;
+        for value in values {
+            total = Value::checked_add(total, value)?;
+            lcm = (lcm / gcd(lcm, value.den)).checked_mul(value.den)?;
+            if lcm > u64::MAX as u128 { return None; }
+        }
+        total.num.checked_mul(lcm / total.den)?;
+        Some(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Xoshiro256ss;
+
+    fn ratio(num: u128, den: u128) -> Rational {
+        Rational::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn rational_is_normalised() {
+        let half = ratio(6, 12);
+        assert_eq!((half.numer(), half.denom()), (1, 2));
+        assert_eq!(ratio(0, 7), Rational::default());
+        assert_eq!(ratio(10, 5).to_usize(), Some(2));
+        assert_eq!(ratio(5, 10).to_usize(), None);
+        assert_eq!(Rational::new(1, 0), None);
+        // знаменатель после сокращения не больше u64::MAX
+        assert_eq!(Rational::new(1, u64::MAX as u128 + 1), None);
+        assert_eq!(ratio(2, 2 * u64::MAX as u128).denom(), u64::MAX as u128);
+    }
+
+    #[test]
+    fn rational_parse_and_display() {
+        for (token, expected) in [("7", "7"), ("14/4", "7/2"), ("2.25", "9/4"), ("0.50", "1/2"), ("0/3", "0"), ("007", "7")] {
+            assert_eq!(token.parse::<Rational>().unwrap().to_string(), expected, "token {}", token);
+        }
+        // дроби неотрицательные: знаки не принимаются
+        for token in ["-1", "-1/2", "1/-2", "+1", "-0.5", "", "1/", "/2", "1.", ".5", "1/0", "1.2.3", "a"] {
+            assert_eq!(token.parse::<Rational>(), Err(ParseRationalError), "token {:?}", token);
+        }
+        let max = u128::MAX.to_string();
+        assert_eq!(max.parse::<Rational>().unwrap().numer(), u128::MAX);
+        assert!(format!("{}0", max).parse::<Rational>().is_err());
+    }
+
+    #[test]
+    fn rational_ordering() {
+        assert!(ratio(1, 3) < ratio(1, 2));
+        assert_eq!(ratio(2, 4).cmp(&ratio(1, 2)), Ordering::Equal);
+        assert!(Rational::default() < ratio(1, u64::MAX as u128));
+        // произведения крест-накрест не помещаются в 128 бит
+        let x = ratio(u128::MAX, 7);
+        let y = ratio(u128::MAX - 2, 7);
+        let z = ratio(u128::MAX - 2, 5);
+        assert!(y < x && x < z);
+
+        let mut rng = Xoshiro256ss::new(20);
+        for _ in 0..1000 {
+            let (a, b) = (ratio(rng.rand() as u128, rng.rand() as u128 | 1), ratio(rng.rand() as u128, rng.rand() as u128 | 1));
+            let expected = (a.numer() * b.denom()).cmp(&(b.numer() * a.denom()));
+            assert_eq!(a.cmp(&b), expected);
+        }
+    }
+
+    #[test]
+    fn wide_multiplication() {
+        assert_eq!(mul_wide(0, u128::MAX), (0, 0));
+        assert_eq!(mul_wide(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(mul_wide(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        let mut rng = Xoshiro256ss::new(21);
+        for _ in 0..1000 {
+            let a = (rng.rand() as u128) << 64 | rng.rand() as u128;
+            let b = (rng.rand() as u128) << 64 | rng.rand() as u128;
+            let (high, low) = mul_wide(a, b);
+            assert_eq!(low, a.wrapping_mul(b));
+            // умножение на 2^k - сдвиг
+            let k = rng.gen_range(1..128) as u32;
+            assert_eq!(mul_wide(a, 1 << k), (a >> (128 - k), a << k));
+            // старшая часть совпадает с произведением старших половин с точностью до переносов
+            let upper = (a >> 64) * (b >> 64);
+            assert!(high >= upper && high - upper <= (a >> 64) + (b >> 64) + 1);
+            assert_eq!(mul_wide(b, a), (high, low));
+        }
+    }
+
+    #[test]
+    fn rational_arithmetic_and_sign() {
+        assert_eq!(ratio(1, 2) + ratio(1, 3), ratio(5, 6));
+        assert_eq!(ratio(1, 2) - ratio(1, 3), ratio(1, 6));
+        assert_eq!(ratio(1, 2) - ratio(1, 2), Rational::default());
+        // отрицательных дробей нет: вычитание большего - None
+        assert_eq!(Value::checked_sub(ratio(1, 3), ratio(1, 2)), None);
+        assert_eq!(Value::checked_add(ratio(u128::MAX, 1), ratio(1, 1)), None);
+        // знаменатель суммы больше u64::MAX
+        assert_eq!(Value::checked_add(ratio(1, 1 << 40), ratio(1, 3u128.pow(26))), None);
+        assert_eq!(ratio(3, 4).fraction_floor(2, 3), Some(ratio(1, 2)));
+        assert_eq!(ratio(3, 4).fraction_ceil(2, 0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "rational overflow")]
+    fn rational_sub_below_zero_panics() {
+        let _ = ratio(1, 3) - ratio(1, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "rational overflow")]
+    fn rational_add_overflow_panics() {
+        let _ = ratio(u128::MAX, 1) + ratio(1, 2);
+    }
+
+    #[test]
+    fn checked_total_overflow() {
+        assert_eq!(Rational::checked_total([ratio(1, 2), ratio(1, 3), ratio(1, 6)]), Some(ratio(1, 1)));
+        assert_eq!(Rational::checked_total([ratio(u128::MAX - 1, 1), ratio(2, 1)]), None);
+        // НОК знаменателей не помещается в u64, хотя у суммы знаменатель мал
+        let big = 1u128 << 63;
+        assert_eq!(Rational::checked_total([ratio(1, big), ratio(big - 1, big), ratio(1, 3), ratio(2, 3)]), None);
+        // сумма представима, но числитель при НОК знаменателей - нет
+        assert_eq!(Rational::checked_total([ratio(1, 2), ratio(1, 2), ratio(u128::MAX / 2, 1)]), None);
+        assert!(Rational::checked_total([ratio(1, 2), ratio(u128::MAX / 2, 1)]).is_some());
+        assert_eq!(u64::checked_total([u64::MAX - 1, 1]), Some(u64::MAX));
+        assert_eq!(u64::checked_total([u64::MAX, 1]), None);
+    }
+
+    #[test]
+    fn integer_fractions_round() {
+        assert_eq!(7u64.fraction_floor(2, 3), Some(4));
+        assert_eq!(7u64.fraction_ceil(2, 3), Some(5));
+        assert_eq!(6u64.fraction_ceil(2, 3), Some(4));
+        assert_eq!(u128::MAX.fraction_floor(3, 4), Some(u128::MAX / 4 * 3 + 2));
+        assert_eq!(u64::MAX.fraction_floor(2, 1), None);
+        assert_eq!(7u64.fraction_floor(2, 0), None);
+        assert_eq!(7usize.fraction_ceil(0, 0), None);
+        assert_eq!(0u128.fraction_ceil(1, 0), None);
+        assert_eq!(3u64.cross_cmp(2, 5, 3), Ordering::Less);
+        assert_eq!(u128::MAX.cross_cmp(usize::MAX, u128::MAX - 1, usize::MAX), Ordering::Greater);
+    }
+}
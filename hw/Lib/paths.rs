@@ -0,0 +1,534 @@
+use crate::graphs::AdjacencyGraph;
+use crate::matrix::Matrix;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::marker::PhantomData;
+use std::ops::Add;
+
+// Правило сложения путей: вес пустого пути, продление пути ребром
+// и сравнение "a строго лучше b". MinSum - обычные кратчайшие пути,
+// MaxSum - самые выгодные (например, прибыль пиратов в pa3).
+pub trait Semiring<W: Copy> {
+    fn identity() -> W;
+    fn extend(path: W, edge: W) -> W;
+    fn better(a: W, b: W) -> bool;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct MinSum;
+
+#[derive(Clone, Copy, Default)]
+pub struct MaxSum;
+
+impl<W> Semiring<W> for MinSum
+where W: Copy + Default + PartialOrd + Add<Output = W>,
+{
+    fn identity() -> W { W::default() }
+    fn extend(path: W, edge: W) -> W { path + edge }
+    fn better(a: W, b: W) -> bool { a < b }
+}
+
+impl<W> Semiring<W> for MaxSum
+where W: Copy + Default + PartialOrd + Add<Output = W>,
+{
+    fn identity() -> W { W::default() }
+    fn extend(path: W, edge: W) -> W { path + edge }
+    fn better(a: W, b: W) -> bool { a > b }
+}
+
+// Пути из одной вершины: dist[v] - вес лучшего пути (None - недостижима),
+// parent[v] - предыдущая вершина на этом пути.
+pub struct ShortestPaths<W> {
+    pub source: usize,
+    pub dist: Vec<Option<W>>,
+    pub parent: Vec<Option<usize>>,
+}
+
+impl<W: Copy> ShortestPaths<W> {
+    fn new(n_nodes: usize, source: usize, identity: W) -> Self {
+        let mut dist = vec![None; n_nodes];
+        dist[source] = Some(identity);
+        Self { source, dist, parent: vec![None; n_nodes] }
+    }
+
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target]?;
+        let mut path = vec![target];
+        let mut node = target;
+        while let Some(prev) = self.parent[node] {
+            path.push(prev);
+            node = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// true, если вес изменился
+fn relax<S, W>(dist: &mut [Option<W>], parent: &mut [Option<usize>], from: usize, to: usize, edge: W) -> bool
+where
+    S: Semiring<W>,
+    W: Copy,
+{
+    let from_dist = match dist[from] {
+        Some(d) => d,
+        None => return false,
+    };
+    let new_dist = S::extend(from_dist, edge);
+    match dist[to] {
+        Some(old_dist) if !S::better(new_dist, old_dist) => false,
+        _ => {
+            dist[to] = Some(new_dist);
+            parent[to] = Some(from);
+            true
+        }
+    }
+}
+
+struct HeapEntry<S, W> {
+    dist: W,
+    node: usize,
+    semiring: PhantomData<S>,
+}
+
+// BinaryHeap достаёт максимум, поэтому "больше" = "лучше"
+impl<S: Semiring<W>, W: Copy> Ord for HeapEntry<S, W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if S::better(self.dist, other.dist) { Ordering::Greater }
+        else if S::better(other.dist, self.dist) { Ordering::Less }
+        else { other.node.cmp(&self.node) }
+    }
+}
+
+impl<S: Semiring<W>, W: Copy> PartialOrd for HeapEntry<S, W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: Semiring<W>, W: Copy> PartialEq for HeapEntry<S, W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S: Semiring<W>, W: Copy> Eq for HeapEntry<S, W> {}
+
+// Дейкстра на двоичной куче. Корректна, только если ребро не может
+// улучшить путь: для MinSum веса >= 0, для MaxSum веса <= 0.
+pub fn dijkstra<G, S>(graph: &G, source: usize, _semiring: S) -> ShortestPaths<G::Edge>
+where
+    G: AdjacencyGraph,
+    S: Semiring<G::Edge>,
+{
+    let n_nodes = graph.num_nodes();
+    let mut paths = ShortestPaths::new(n_nodes, source, S::identity());
+    let mut done = vec![false; n_nodes];
+
+    let mut heap = BinaryHeap::new();
+    heap.push(HeapEntry::<S, G::Edge> { dist: S::identity(), node: source, semiring: PhantomData });
+
+    while let Some(HeapEntry { node, .. }) = heap.pop() {
+        if done[node] { continue; }
+        done[node] = true;
+
+        for (next, edge) in graph.neighbors(node) {
+            if !done[next] && relax::<S, G::Edge>(&mut paths.dist, &mut paths.parent, node, next, edge) {
+                let dist = paths.dist[next].unwrap();
+                heap.push(HeapEntry { dist, node: next, semiring: PhantomData });
+            }
+        }
+    }
+    paths
+}
+
+// Беллман-Форд. Если из source достижим улучшающий цикл
+// (отрицательный для MinSum, положительный для MaxSum),
+// возвращает его вершины в порядке обхода.
+pub fn bellman_ford<G, S>(graph: &G, source: usize, _semiring: S) -> Result<ShortestPaths<G::Edge>, Vec<usize>>
+where
+    G: AdjacencyGraph,
+    S: Semiring<G::Edge>,
+{
+    let n_nodes = graph.num_nodes();
+    let mut paths = ShortestPaths::new(n_nodes, source, S::identity());
+
+    let mut last_relaxed = None;
+    for _ in 0..n_nodes {
+        last_relaxed = None;
+        for node in 0..n_nodes {
+            for (next, edge) in graph.neighbors(node) {
+                if relax::<S, G::Edge>(&mut paths.dist, &mut paths.parent, node, next, edge) {
+                    last_relaxed = Some(next);
+                }
+            }
+        }
+        if last_relaxed.is_none() { return Ok(paths); }
+    }
+
+    // после n шагов по родителям гарантированно оказываемся на цикле
+    let mut node = last_relaxed.unwrap();
+    for _ in 0..n_nodes {
+        node = paths.parent[node].unwrap();
+    }
+    let mut cycle = vec![node];
+    let mut curr = paths.parent[node].unwrap();
+    while curr != node {
+        cycle.push(curr);
+        curr = paths.parent[curr].unwrap();
+    }
+    cycle.reverse();
+    Err(cycle)
+}
+
+// Лучшие пути между всеми парами вершин с восстановлением пути.
+pub struct AllPairsPaths<W: Copy> {
+    dist: Matrix<Option<W>>,
+    next: Matrix<Option<usize>>,
+}
+
+impl<W: Copy> AllPairsPaths<W> {
+    pub fn distance(&self, from: usize, to: usize) -> Option<W> {
+        self.dist[(from, to)]
+    }
+
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        self.dist[(from, to)]?;
+        let (n_nodes, _) = self.dist.shape();
+        let mut path = vec![from];
+        let mut node = from;
+        while node != to {
+            node = self.next[(node, to)]?;
+            path.push(node);
+            // при улучшающих циклах путь не определён
+            if path.len() > n_nodes { return None; }
+        }
+        Some(path)
+    }
+
+    pub fn has_improving_cycle<S: Semiring<W>>(&self, _semiring: S) -> bool {
+        let (n_nodes, _) = self.dist.shape();
+        (0..n_nodes).any(|node| match self.dist[(node, node)] {
+            Some(d) => S::better(d, S::identity()),
+            None => false,
+        })
+    }
+}
+
+// Флойд-Уоршелл. При улучшающих циклах веса не имеют смысла,
+// такие циклы видны по AllPairsPaths::has_improving_cycle.
+pub fn floyd_warshall<G, S>(graph: &G, _semiring: S) -> AllPairsPaths<G::Edge>
+where
+    G: AdjacencyGraph,
+    S: Semiring<G::Edge>,
+{
+    let n_nodes = graph.num_nodes();
+    let mut dist: Matrix<Option<G::Edge>> = Matrix::empty_squared(n_nodes);
+    let mut next: Matrix<Option<usize>> = Matrix::empty_squared(n_nodes);
+
+    for node in 0..n_nodes {
+        dist[(node, node)] = Some(S::identity());
+        next[(node, node)] = Some(node);
+    }
+    for node in 0..n_nodes {
+        for (to, edge) in graph.neighbors(node) {
+            let improves = match dist[(node, to)] {
+                Some(old) => S::better(edge, old),
+                None => true,
+            };
+            if improves {
+                dist[(node, to)] = Some(edge);
+                next[(node, to)] = Some(to);
+            }
+        }
+    }
+
+    for k in 0..n_nodes {
+        for i in 0..n_nodes {
+            if i == k { continue; }
+            let to_k = match dist[(i, k)] {
+                Some(d) => d,
+                None => continue,
+            };
+            for j in 0..n_nodes {
+                if j == k { continue; }
+                let from_k = match dist[(k, j)] {
+                    Some(d) => d,
+                    None => continue,
+                };
+                let through_k = S::extend(to_k, from_k);
+                let improves = match dist[(i, j)] {
+                    Some(old) => S::better(through_k, old),
+                    None => true,
+                };
+                if improves {
+                    dist[(i, j)] = Some(through_k);
+                    next[(i, j)] = next[(i, k)];
+                }
+            }
+        }
+    }
+    AllPairsPaths { dist, next }
+}
+
+// Поиск в ширину: dist - число рёбер в кратчайшем пути.
+pub fn bfs<G: AdjacencyGraph>(graph: &G, source: usize) -> ShortestPaths<usize> {
+    let n_nodes = graph.num_nodes();
+    let mut paths = ShortestPaths::new(n_nodes, source, 0);
+    let mut queue = VecDeque::with_capacity(n_nodes);
+    queue.push_back(source);
+
+    while let Some(node) = queue.pop_front() {
+        let next_dist = paths.dist[node].unwrap() + 1;
+        for (next, _) in graph.neighbors(node) {
+            if paths.dist[next].is_none() {
+                paths.dist[next] = Some(next_dist);
+                paths.parent[next] = Some(node);
+                queue.push_back(next);
+            }
+        }
+    }
+    paths
+}
+
+// 0-1 BFS: веса рёбер - W::default() (ноль) или одно и то же
+// положительное значение.
+pub fn zero_one_bfs<G, W>(graph: &G, source: usize) -> ShortestPaths<W>
+where
+    G: AdjacencyGraph<Edge = W>,
+    W: Copy + Default + PartialOrd + Add<Output = W>,
+{
+    let n_nodes = graph.num_nodes();
+    let mut paths = ShortestPaths::new(n_nodes, source, W::default());
+    let mut done = vec![false; n_nodes];
+    let mut deque = VecDeque::with_capacity(n_nodes);
+    deque.push_back(source);
+
+    while let Some(node) = deque.pop_front() {
+        if done[node] { continue; }
+        done[node] = true;
+
+        for (next, edge) in graph.neighbors(node) {
+            if !done[next] && relax::<MinSum, W>(&mut paths.dist, &mut paths.parent, node, next, edge) {
+                if edge == W::default() { deque.push_front(next); }
+                else { deque.push_back(next); }
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::rng::Xoshiro256ss;
+
+    type TestGraph = Graph<(), i64>;
+
+    // ориентированный граф без петель и кратных рёбер; dag - только рёбра i -> j, i < j
+    fn random_graph(rng: &mut Xoshiro256ss, n_nodes: usize, dag: bool, mut weight: impl FnMut(&mut Xoshiro256ss) -> i64) -> TestGraph {
+        let mut graph = TestGraph::from_nodes(vec![(); n_nodes]).with_hashed_adjacency();
+        for from in 0..n_nodes {
+            for to in 0..n_nodes {
+                if from != to && (!dag || from < to) && rng.gen_bool(0.4) {
+                    graph.add_edge(from, to, weight(rng));
+                }
+            }
+        }
+        graph
+    }
+
+    // Лучшие простые пути перебором. Без улучшающих циклов лучший путь простой.
+    fn brute_force<S: Semiring<i64>>(graph: &TestGraph, source: usize) -> Vec<Option<i64>> {
+        fn walk<S: Semiring<i64>>(graph: &TestGraph, node: usize, dist: i64, visited: &mut [bool], best: &mut [Option<i64>]) {
+            if best[node].is_none_or(|old| S::better(dist, old)) {
+                best[node] = Some(dist);
+            }
+            visited[node] = true;
+            for &(next, edge) in graph.neigh(node) {
+                if !visited[next] {
+                    walk::<S>(graph, next, S::extend(dist, edge), visited, best);
+                }
+            }
+            visited[node] = false;
+        }
+        let (n_nodes, _) = graph.size();
+        let mut best = vec![None; n_nodes];
+        walk::<S>(graph, source, S::identity(), &mut vec![false; n_nodes], &mut best);
+        best
+    }
+
+    // вес пути по рёбрам графа
+    fn path_weight(graph: &TestGraph, path: &[usize]) -> i64 {
+        path.windows(2).map(|pair| *graph.edge(pair[0], pair[1]).expect("path uses a missing edge")).sum()
+    }
+
+    // пути из source ведут в нужные вершины и весят dist
+    fn check_paths(graph: &TestGraph, paths: &ShortestPaths<i64>, expected: &[Option<i64>]) {
+        assert_eq!(paths.dist, expected);
+        for (target, dist) in expected.iter().enumerate() {
+            match paths.path_to(target) {
+                Some(path) => {
+                    assert_eq!((path[0], *path.last().unwrap()), (paths.source, target));
+                    assert_eq!(Some(path_weight(graph, &path)), *dist);
+                }
+                None => assert_eq!(*dist, None),
+            }
+        }
+    }
+
+    // цикл замкнут по рёбрам графа и строго улучшает путь
+    fn check_cycle<S: Semiring<i64>>(graph: &TestGraph, cycle: &[usize]) {
+        assert!(!cycle.is_empty());
+        let mut closed = cycle.to_vec();
+        closed.push(cycle[0]);
+        assert!(S::better(path_weight(graph, &closed), 0), "cycle {:?} does not improve", cycle);
+        let mut sorted = cycle.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), cycle.len(), "cycle {:?} repeats a node", cycle);
+    }
+
+    #[test]
+    fn dijkstra_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(4);
+        for _ in 0..200 {
+            let n_nodes = rng.gen_range(1..8);
+            let source = rng.gen_range(0..n_nodes);
+            let graph = random_graph(&mut rng, n_nodes, false, |rng| rng.gen_range(0..20) as i64);
+            check_paths(&graph, &dijkstra(&graph, source, MinSum), &brute_force::<MinSum>(&graph, source));
+            // для MaxSum Дейкстра корректна на неположительных весах
+            let graph = random_graph(&mut rng, n_nodes, false, |rng| -(rng.gen_range(0..20) as i64));
+            check_paths(&graph, &dijkstra(&graph, source, MaxSum), &brute_force::<MaxSum>(&graph, source));
+        }
+    }
+
+    #[test]
+    fn bellman_ford_matches_brute_force_on_dags() {
+        let mut rng = Xoshiro256ss::new(5);
+        for _ in 0..200 {
+            let n_nodes = rng.gen_range(1..8);
+            let source = rng.gen_range(0..n_nodes);
+            let graph = random_graph(&mut rng, n_nodes, true, |rng| rng.gen_range(0..41) as i64 - 20);
+            let paths = bellman_ford(&graph, source, MinSum).unwrap();
+            check_paths(&graph, &paths, &brute_force::<MinSum>(&graph, source));
+            let paths = bellman_ford(&graph, source, MaxSum).unwrap();
+            check_paths(&graph, &paths, &brute_force::<MaxSum>(&graph, source));
+        }
+    }
+
+    #[test]
+    fn bellman_ford_returns_improving_cycle() {
+        // 0 -> 1 -> 2 -> 3 -> 1: цикл весом -1, вершина 4 вне его
+        let mut graph = TestGraph::from_nodes(vec![(); 5]);
+        graph.add_edge(0, 1, 4);
+        graph.add_edge(1, 2, 2);
+        graph.add_edge(2, 3, -5);
+        graph.add_edge(3, 1, 2);
+        graph.add_edge(3, 4, 1);
+        let cycle = bellman_ford(&graph, 0, MinSum).err().unwrap();
+        check_cycle::<MinSum>(&graph, &cycle);
+        assert_eq!(cycle.len(), 3);
+        // для MaxSum тот же цикл ухудшающий, но цикл 1 -> 2 -> 1 выгоден
+        assert!(bellman_ford(&graph, 0, MaxSum).is_ok());
+        graph.add_edge(2, 1, 1);
+        let cycle = bellman_ford(&graph, 0, MaxSum).err().unwrap();
+        check_cycle::<MaxSum>(&graph, &cycle);
+        assert_eq!(cycle.len(), 2);
+        // недостижимый из source цикл не мешает
+        assert!(bellman_ford(&graph, 4, MinSum).is_ok());
+
+        let mut rng = Xoshiro256ss::new(6);
+        for _ in 0..300 {
+            let n_nodes = rng.gen_range(1..8);
+            let source = rng.gen_range(0..n_nodes);
+            let graph = random_graph(&mut rng, n_nodes, false, |rng| rng.gen_range(0..41) as i64 - 8);
+            match bellman_ford(&graph, source, MinSum) {
+                Ok(paths) => check_paths(&graph, &paths, &brute_force::<MinSum>(&graph, source)),
+                Err(cycle) => check_cycle::<MinSum>(&graph, &cycle),
+            }
+            match bellman_ford(&graph, source, MaxSum) {
+                Ok(paths) => check_paths(&graph, &paths, &brute_force::<MaxSum>(&graph, source)),
+                Err(cycle) => check_cycle::<MaxSum>(&graph, &cycle),
+            }
+        }
+    }
+
+    fn check_all_pairs<S: Semiring<i64>>(graph: &TestGraph, all_pairs: &AllPairsPaths<i64>) {
+        let (n_nodes, _) = graph.size();
+        for from in 0..n_nodes {
+            let expected = brute_force::<S>(graph, from);
+            for (to, &dist) in expected.iter().enumerate() {
+                assert_eq!(all_pairs.distance(from, to), dist);
+                match all_pairs.path(from, to) {
+                    Some(path) => {
+                        assert_eq!((path[0], *path.last().unwrap()), (from, to));
+                        assert_eq!(Some(path_weight(graph, &path)), dist);
+                    }
+                    None => assert_eq!(dist, None),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn floyd_warshall_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(7);
+        for _ in 0..200 {
+            let n_nodes = rng.gen_range(1..7);
+            let graph = random_graph(&mut rng, n_nodes, false, |rng| rng.gen_range(0..20) as i64);
+            let all_pairs = floyd_warshall(&graph, MinSum);
+            assert!(!all_pairs.has_improving_cycle(MinSum));
+            check_all_pairs::<MinSum>(&graph, &all_pairs);
+
+            let graph = random_graph(&mut rng, n_nodes, true, |rng| rng.gen_range(0..41) as i64 - 20);
+            let all_pairs = floyd_warshall(&graph, MaxSum);
+            assert!(!all_pairs.has_improving_cycle(MaxSum));
+            check_all_pairs::<MaxSum>(&graph, &all_pairs);
+        }
+    }
+
+    #[test]
+    fn floyd_warshall_detects_improving_cycle() {
+        let mut graph = TestGraph::from_nodes(vec![(); 3]);
+        graph.add_edge(0, 1, 3);
+        graph.add_edge(1, 2, -2);
+        graph.add_edge(2, 1, 2);
+        // цикл 1 -> 2 -> 1 нулевого веса не улучшает путь
+        assert!(!floyd_warshall(&graph, MinSum).has_improving_cycle(MinSum));
+        assert!(!floyd_warshall(&graph, MaxSum).has_improving_cycle(MaxSum));
+        graph.add_edge(2, 0, -2);
+        assert!(floyd_warshall(&graph, MinSum).has_improving_cycle(MinSum));
+        assert!(!floyd_warshall(&graph, MaxSum).has_improving_cycle(MaxSum));
+        graph.add_edge(1, 0, 5);
+        assert!(floyd_warshall(&graph, MaxSum).has_improving_cycle(MaxSum));
+    }
+
+    #[test]
+    fn zero_one_bfs_matches_brute_force() {
+        let mut rng = Xoshiro256ss::new(8);
+        for _ in 0..300 {
+            let n_nodes = rng.gen_range(1..8);
+            let source = rng.gen_range(0..n_nodes);
+            let graph = random_graph(&mut rng, n_nodes, false, |rng| if rng.gen_bool(0.5) { 0 } else { 3 });
+            check_paths(&graph, &zero_one_bfs(&graph, source), &brute_force::<MinSum>(&graph, source));
+            // bfs - то же для единичных весов
+            let hops: Vec<Option<usize>> = brute_force::<MinSum>(&unit_weights(&graph), source)
+                .into_iter()
+                .map(|dist| dist.map(|dist| dist as usize))
+                .collect();
+            assert_eq!(bfs(&graph, source).dist, hops);
+        }
+    }
+
+    // тот же граф с весом 1 на каждом ребре
+    fn unit_weights(graph: &TestGraph) -> TestGraph {
+        let (n_nodes, _) = graph.size();
+        let mut unit = TestGraph::from_nodes(vec![(); n_nodes]);
+        for (from, to, _) in graph.edges() {
+            unit.add_edge(from, to, 1);
+        }
+        unit
+    }
+}
@@ -1,51 +1,252 @@
-mod rng {
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 
-    pub struct Xoshiro256ss {
-        state: [u64; 4],
+// коэффициенты jump/long_jump из эталонной реализации xoshiro256**
+const JUMP: [u64; 4] = [0x180EC6D33CFD0ABA, 0xD5A61266F0C9392C, 0xA9582618E03FC9AA, 0x39ABDC4529B1661C];
+const LONG_JUMP: [u64; 4] = [0x76E15D3EFEFDCBBF, 0xC5004E441C522FB3, 0x77710069854EE241, 0x39109BB02ACBE635];
+
+pub struct Xoshiro256ss {
+    state: [u64; 4],
+}
+
+impl Xoshiro256ss {
+    pub fn new(seed: u64) -> Self {
+        let mut state = [0; 4];
+        let mut split_mix = SplitMix64 { state: seed };
+        state[0] = split_mix.rand();
+        state[1] = split_mix.rand();
+        state[2] = split_mix.rand();
+        state[3] = split_mix.rand();
+        Self { state }
+    }
+
+    // состояние из state() или из строки Display; нулевое состояние недопустимо
+    pub fn from_state(state: [u64; 4]) -> Self {
+        assert!(state != [0; 4], "Xoshiro256ss state must not be all zeros");
+        Self { state }
+    }
+
+    pub fn state(&self) -> [u64; 4] {
+        self.state
+    }
+
+    pub fn rand(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
     }
 
-    impl Xoshiro256ss {
-        pub fn new(seed: u64) -> Self {
-            let mut state = [0; 4];
-            let mut split_mix = SplitMix64 { state: seed };
-            state[0] = split_mix.rand();
-            state[1] = split_mix.rand();
-            state[2] = split_mix.rand();
-            state[3] = split_mix.rand();
-            Self { state }
+    // равномерно в [0, bound) без смещения (метод Лемира)
+    pub fn gen_below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Bound must be positive");
+        let mut product = self.rand() as u128 * bound as u128;
+        if (product as u64) < bound {
+            let threshold = bound.wrapping_neg() % bound;
+            while (product as u64) < threshold {
+                product = self.rand() as u128 * bound as u128;
+            }
         }
+        (product >> 64) as u64
+    }
+
+    // равномерно в [range.start, range.end); пустой диапазон - паника
+    pub fn gen_range(&mut self, range: Range<usize>) -> usize {
+        assert!(range.start < range.end, "Empty range {:?}", range);
+        range.start + self.gen_below((range.end - range.start) as u64) as usize
+    }
 
-        fn rol64(x: u64, k: i64) ->u64 {
-            (x << k) | (x >> (64 - k))
+    // равномерно в [0, 1) с шагом 2^-53
+    pub fn gen_f64(&mut self) -> f64 {
+        (self.rand() >> 11) as f64 * (1.0 / (1_u64 << 53) as f64)
+    }
+
+    pub fn gen_bool(&mut self, p: f64) -> bool {
+        self.gen_f64() < p
+    }
+
+    // нормальное распределение, преобразование Бокса-Мюллера
+    pub fn gen_normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        let u1 = 1.0 - self.gen_f64();
+        let u2 = self.gen_f64();
+        let radius = (-2.0 * u1.ln()).sqrt();
+        mean + std_dev * radius * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    // экспоненциальное распределение с интенсивностью lambda
+    pub fn gen_exp(&mut self, lambda: f64) -> f64 {
+        -(1.0 - self.gen_f64()).ln() / lambda
+    }
+
+    // перемешивание Фишера-Йетса
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.gen_range(0..i + 1);
+            slice.swap(i, j);
         }
+    }
 
-        pub fn rand(&mut self) -> u64 {
-            let result = Self::rol64(self.state[1].wrapping_mul(5), 7).wrapping_mul(9);
-            let t = self.state[1] << 17;
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        if slice.is_empty() { return None; }
+        Some(&slice[self.gen_range(0..slice.len())])
+    }
 
-            self.state[2] ^= self.state[0];
-            self.state[3] ^= self.state[1];
-            self.state[1] ^= self.state[2];
-            self.state[0] ^= self.state[3];
+    // индекс с вероятностью, пропорциональной весу; None - если
+    // все веса нулевые, есть отрицательные или NaN
+    pub fn weighted_index(&mut self, weights: &[f64]) -> Option<usize> {
+        if weights.iter().any(|w| w.is_nan() || *w < 0.0) { return None; }
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 || !total.is_finite() { return None; }
 
-            self.state[2] ^= t;
-            self.state[3] = Self::rol64(self.state[3], 45);
+        let mut target = self.gen_f64() * total;
+        let mut last_positive = None;
+        for (idx, &weight) in weights.iter().enumerate() {
+            if weight == 0.0 { continue; }
+            if target < weight { return Some(idx); }
+            target -= weight;
+            last_positive = Some(idx);
+        }
+        // из-за округления target мог не попасть ни в один вес
+        last_positive
+    }
 
-            return result;
+    // эквивалент 2^128 вызовов rand(): независимые потоки для 2^128 задач
+    pub fn jump(&mut self) {
+        self.apply_jump(&JUMP);
+    }
+
+    // эквивалент 2^192 вызовов rand()
+    pub fn long_jump(&mut self) {
+        self.apply_jump(&LONG_JUMP);
+    }
+
+    fn apply_jump(&mut self, polynomial: &[u64; 4]) {
+        let mut new_state = [0; 4];
+        for &word in polynomial.iter() {
+            for bit in 0..64 {
+                if word & (1 << bit) != 0 {
+                    for (new, old) in new_state.iter_mut().zip(self.state.iter()) {
+                        *new ^= *old;
+                    }
+                }
+                self.rand();
+            }
         }
+        self.state = new_state;
+    }
+}
+
+// состояние в виде четырёх 16-ричных слов через '-'
+impl fmt::Display for Xoshiro256ss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [s0, s1, s2, s3] = self.state;
+        write!(f, "{:016x}-{:016x}-{:016x}-{:016x}", s0, s1, s2, s3)
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseStateError;
+
+impl fmt::Display for ParseStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected four non-zero hex words separated by '-'")
+    }
+}
+
+impl std::error::Error for ParseStateError {}
+
+impl FromStr for Xoshiro256ss {
+    type Err = ParseStateError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let words = text.trim()
+            .split('-')
+            .map(|word| u64::from_str_radix(word, 16).map_err(|_| ParseStateError))
+            .collect::<Result<Vec<u64>, _>>()?;
+        let state: [u64; 4] = words.try_into().map_err(|_| ParseStateError)?;
+        if state == [0; 4] { return Err(ParseStateError); }
+        Ok(Self { state })
+    }
+}
+
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn rand(&mut self) -> u64 {
+        let mut result = self.state;
+        self.state = result.wrapping_add(0x9E3779B97F4A7C15);
+        result = (result ^ (result >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        result = (result ^ (result >> 27)).wrapping_mul(0x94D049BB133111EB);
+        result ^ (result >> 31)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // векторы посчитаны эталонной реализацией xoshiro256** на C
+    // (и независимо возведением матрицы перехода в степень 2^128 и 2^192)
+    const START: [u64; 4] = [1, 2, 3, 4];
+
+    #[test]
+    fn rand_matches_reference() {
+        let mut rng = Xoshiro256ss::from_state(START);
+        let outputs: Vec<u64> = (0..4).map(|_| rng.rand()).collect();
+        assert_eq!(outputs, [0x2d00, 0, 0x5a007080, 0x10e0000000009d80]);
+    }
+
+    #[test]
+    fn jumps_match_reference() {
+        let mut rng = Xoshiro256ss::from_state(START);
+        rng.jump();
+        assert_eq!(rng.state(), [0x8c7a153956b5f3d1, 0x701f1a713401d85e, 0x6527f66a65469085, 0x8386b786c4408050]);
 
-    struct SplitMix64 {
-        state: u64,
+        let mut rng = Xoshiro256ss::from_state(START);
+        rng.long_jump();
+        assert_eq!(rng.state(), [0x096a8eb71295a400, 0xdbf84991e50f4516, 0x534ee745810d2a0e, 0x31655ca1a2215bf1]);
     }
 
-    impl SplitMix64 {
-        fn rand(&mut self) -> u64 {
-            let mut result = self.state;
-            self.state = result.wrapping_add(0x9E3779B97f4A7C15);
-            result = (result ^ (result >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
-            result = (result ^ (result >> 27)).wrapping_mul(0x94D049BB133111EB);
-            return result ^ (result >> 31);
+    #[test]
+    fn gen_below_stays_in_bounds() {
+        let mut rng = Xoshiro256ss::new(7);
+        for bound in [1, 2, 3, 7, 1000, 1 << 32, (1 << 63) - 1, 1 << 63, (1 << 63) + 1, u64::MAX] {
+            for _ in 0..10_000 {
+                assert!(rng.gen_below(bound) < bound);
+            }
         }
+        for _ in 0..10_000 {
+            let start = rng.gen_range(0..100);
+            let end = start + 1 + rng.gen_range(0..100);
+            assert!((start..end).contains(&rng.gen_range(start..end)));
+        }
+    }
+
+    // при малой границе все значения встречаются примерно поровну
+    #[test]
+    fn gen_below_is_uniform() {
+        let mut rng = Xoshiro256ss::new(8);
+        let mut counts = [0; 3];
+        for _ in 0..30_000 {
+            counts[rng.gen_below(3) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| (9_500..10_500).contains(&count)), "{:?}", counts);
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty range")]
+    fn empty_range_panics() {
+        Xoshiro256ss::new(9).gen_range(3..3);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,231 @@
+use crate::rng::Xoshiro256ss;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// Параметры запуска эвристик. Аргументы командной строки важнее
+// переменных окружения:
+//   --seed N            DOPT_SEED         зерно генератора
+//   --rng-state STATE   DOPT_RNG_STATE    состояние генератора из --dump-rng
+//   --iterations N      DOPT_ITERATIONS   остановка по числу итераций, а не по времени
+//   --dump-rng          DOPT_DUMP_RNG=1   печатать состояние генератора в stderr
+// С одинаковыми зерном и числом итераций запуск повторяется в точности.
+#[derive(Default)]
+pub struct RunOptions {
+    seed: Option<u64>,
+    rng_state: Option<[u64; 4]>,
+    iterations: Option<u64>,
+    dump_rng: bool,
+}
+
+// собственные параметры программы: (параметр, значение) в порядке записи
+pub type ExtraOptions = Vec<(&'static str, String)>;
+
+#[derive(Debug)]
+pub enum OptionsError {
+    Unknown(String),
+    MissingValue(&'static str),
+    Invalid { option: &'static str, value: String },
+}
+
+impl OptionsError {
+    pub fn exit(self) -> ! {
+        eprintln!("Options error: {}", self);
+        std::process::exit(2)
+    }
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::Unknown(arg) => write!(f, "unknown argument '{}'", arg),
+            OptionsError::MissingValue(option) => write!(f, "missing value for {}", option),
+            OptionsError::Invalid { option, value } => write!(f, "invalid value '{}' for {}", value, option),
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {}
+
+const OPTIONS: [(&str, &str); 4] = [
+    ("--seed", "DOPT_SEED"),
+    ("--rng-state", "DOPT_RNG_STATE"),
+    ("--iterations", "DOPT_ITERATIONS"),
+    ("--dump-rng", "DOPT_DUMP_RNG"),
+];
+
+impl RunOptions {
+    // аргументы процесса и переменные окружения
+    pub fn from_env() -> Result<Self, OptionsError> {
+        Self::from_env_with(&[]).map(|(options, _)| options)
+    }
+
+    // то же и с собственными параметрами программы extra (у каждого есть
+    // значение), их значения возвращаются отдельно
+    pub fn from_env_with(extra: &[&'static str]) -> Result<(Self, ExtraOptions), OptionsError> {
+        Self::from_sources(|var| std::env::var(var).ok(), std::env::args().skip(1), extra)
+    }
+
+    // env(имя) - значение переменной окружения; args - аргументы без имени программы
+    fn from_sources<V, I>(env: V, args: I, extra: &[&'static str]) -> Result<(Self, ExtraOptions), OptionsError>
+    where
+        V: Fn(&str) -> Option<String>,
+        I: Iterator<Item = String>,
+    {
+        let env_vars = OPTIONS.iter()
+            .filter_map(|&(option, var)| env(var).map(|value| (option, value)));
+        let mut options = Self::default();
+        for (option, value) in env_vars {
+            options.set(option, value)?;
+        }
+        let extra_values = options.parse_args_with(args, extra)?;
+        Ok((options, extra_values))
+    }
+
+    pub fn parse_args<I: Iterator<Item = String>>(&mut self, args: I) -> Result<(), OptionsError> {
+        self.parse_args_with(args, &[]).map(|_| ())
+    }
+
+    fn parse_args_with<I: Iterator<Item = String>>(
+        &mut self,
+        mut args: I,
+        extra: &[&'static str],
+    ) -> Result<ExtraOptions, OptionsError> {
+        let mut extra_values = Vec::new();
+        while let Some(arg) = args.next() {
+            if let Some(&option) = extra.iter().find(|&&option| option == arg) {
+                let value = args.next().ok_or(OptionsError::MissingValue(option))?;
+                extra_values.push((option, value));
+                continue;
+            }
+            let option = match OPTIONS.iter().find(|(option, _)| *option == arg) {
+                Some(&(option, _)) => option,
+                None => return Err(OptionsError::Unknown(arg)),
+            };
+            if option == "--dump-rng" {
+                self.dump_rng = true;
+                continue;
+            }
+            let value = args.next().ok_or(OptionsError::MissingValue(option))?;
+            self.set(option, value)?;
+        }
+        Ok(extra_values)
+    }
+
+    fn set(&mut self, option: &'static str, value: String) -> Result<(), OptionsError> {
+        let invalid = |value: String| OptionsError::Invalid { option, value };
+        match option {
+            "--seed" => self.seed = Some(value.parse().map_err(|_| invalid(value))?),
+            "--rng-state" => {
+                let rng: Xoshiro256ss = value.parse().map_err(|_| invalid(value))?;
+                self.rng_state = Some(rng.state());
+            }
+            "--iterations" => self.iterations = Some(value.parse().map_err(|_| invalid(value))?),
+            // флаг из окружения включается любым значением, кроме "0"
+            "--dump-rng" => self.dump_rng = value != "0",
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // генератор: сохранённое состояние, заданное зерно или default_seed
+    pub fn rng(&self, default_seed: u64) -> Xoshiro256ss {
+        let rng = match self.rng_state {
+            Some(state) => Xoshiro256ss::from_state(state),
+            None => Xoshiro256ss::new(self.seed.unwrap_or(default_seed)),
+        };
+        self.dump("start", &rng);
+        rng
+    }
+
+    // условие остановки: --iterations, если задано, иначе лимит времени от start_time
+    pub fn stop_rule(&self, time_limit: Duration, start_time: Instant) -> StopRule {
+        match self.iterations {
+            Some(iterations) => StopRule::Iterations { done: 0, limit: iterations },
+            None => StopRule::Time { start_time, limit: time_limit },
+        }
+    }
+
+    // печатает состояние генератора, если включено --dump-rng
+    pub fn dump(&self, label: &str, rng: &Xoshiro256ss) {
+        if self.dump_rng { eprintln!("rng {}: {}", label, rng); }
+    }
+}
+
+pub enum StopRule {
+    Time { start_time: Instant, limit: Duration },
+    Iterations { done: u64, limit: u64 },
+}
+
+impl StopRule {
+    // вызывается перед каждой итерацией поиска
+    pub fn should_stop(&mut self) -> bool {
+        match self {
+            StopRule::Time { start_time, limit } => start_time.elapsed() >= *limit,
+            StopRule::Iterations { done, limit } => {
+                if *done >= *limit { return true; }
+                *done += 1;
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(env: &[(&str, &str)], args: &[&str]) -> Result<(RunOptions, ExtraOptions), OptionsError> {
+        let env_value = |var: &str| env.iter().find(|(name, _)| *name == var).map(|(_, value)| value.to_string());
+        RunOptions::from_sources(env_value, args.iter().map(|arg| arg.to_string()), &["--heuristic"])
+    }
+
+    #[test]
+    fn arguments_override_environment() {
+        let (options, extra) = parse(&[("DOPT_SEED", "5"), ("DOPT_ITERATIONS", "10")], &["--seed", "7"]).unwrap();
+        assert_eq!((options.seed, options.iterations), (Some(7), Some(10)));
+        assert!(extra.is_empty());
+
+        let (options, _) = parse(&[("DOPT_DUMP_RNG", "0")], &[]).unwrap();
+        assert!(!options.dump_rng);
+        let (options, _) = parse(&[("DOPT_DUMP_RNG", "0")], &["--dump-rng"]).unwrap();
+        assert!(options.dump_rng);
+
+        // сохранённое состояние важнее зерна
+        let state = "0000000000000001-0000000000000002-0000000000000003-0000000000000004";
+        let (options, _) = parse(&[], &["--rng-state", state, "--seed", "7"]).unwrap();
+        assert_eq!(options.rng(42).state(), [1, 2, 3, 4]);
+        let (options, _) = parse(&[], &[]).unwrap();
+        assert_eq!(options.rng(42).state(), Xoshiro256ss::new(42).state());
+    }
+
+    #[test]
+    fn extra_options_keep_order() {
+        let (options, extra) = parse(&[], &["--heuristic", "ffd", "--iterations", "3", "--heuristic", "all"]).unwrap();
+        assert_eq!(options.iterations, Some(3));
+        assert_eq!(extra, [("--heuristic", "ffd".to_string()), ("--heuristic", "all".to_string())]);
+    }
+
+    #[test]
+    fn errors() {
+        let message = |env: &[(&str, &str)], args: &[&str]| parse(env, args).err().unwrap().to_string();
+        assert_eq!(message(&[], &["--unknown"]), "unknown argument '--unknown'");
+        assert_eq!(message(&[], &["--seed"]), "missing value for --seed");
+        assert_eq!(message(&[], &["--heuristic"]), "missing value for --heuristic");
+        assert_eq!(message(&[("DOPT_SEED", "x")], &[]), "invalid value 'x' for --seed");
+        assert_eq!(message(&[], &["--rng-state", "0-0-0-0"]), "invalid value '0-0-0-0' for --rng-state");
+    }
+
+    #[test]
+    fn stop_rule_budgets() {
+        let options = RunOptions { iterations: Some(3), ..RunOptions::default() };
+        let mut stop_rule = options.stop_rule(Duration::ZERO, Instant::now());
+        let runs = (0..10).take_while(|_| !stop_rule.should_stop()).count();
+        assert_eq!(runs, 3);
+        assert!(stop_rule.should_stop());
+
+        let mut stop_rule = RunOptions::default().stop_rule(Duration::ZERO, Instant::now());
+        assert!(stop_rule.should_stop());
+        let mut stop_rule = RunOptions::default().stop_rule(Duration::from_secs(3600), Instant::now());
+        assert!(!stop_rule.should_stop());
+    }
+}
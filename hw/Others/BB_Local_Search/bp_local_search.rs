@@ -1,8 +1,10 @@
-use io::Scanner;
-use rng::Xoshiro256ss;
+use dopt::io::{Scanner, ScanError};
+use dopt::rng::Xoshiro256ss;
+use dopt::run::RunOptions;
 use std::fmt;
 use std::fmt::Display;
 use std::time::{Instant, Duration};
+use std::io::BufRead;
 
 struct BPProblem {
     pub capacity: usize,
@@ -76,7 +78,7 @@ impl Display for Packing {
 }
 
 fn first_fit(problem: &BPProblem) -> Packing {
-    let mut indices: Vec<usize> = vec![std::usize::MAX; problem.weights.len()];
+    let mut indices: Vec<usize> = vec![usize::MAX; problem.weights.len()];
     let mut bin_spaces: Vec<usize> = Vec::with_capacity(indices.len());
 
     for (item_idx, weight) in problem.weights.iter().enumerate() {
@@ -94,7 +96,7 @@ fn first_fit(problem: &BPProblem) -> Packing {
                 })
             };
 
-        if bin == None {
+        if bin.is_none() {
             bin_spaces.push(problem.capacity - weight);
             indices[item_idx] = bin_spaces.len() - 1;
         }
@@ -127,7 +129,9 @@ fn rebalance_bins(
     if bin_weight > problem.capacity / 2 { // пытаемся дозаполнить контейнер
         let mut free_space = problem.capacity - bin_weight;
         for &other_bin_idx in other_bins_indices.iter() {
-            let bin_item_idx = rng.rand() as usize % packing.bins[other_bin_idx].len();
+            // контейнер мог опустеть на предыдущих шагах
+            if packing.bins[other_bin_idx].is_empty() { continue; }
+            let bin_item_idx = rng.gen_range(0..packing.bins[other_bin_idx].len());
             let item_idx = packing.bins[other_bin_idx].items[bin_item_idx];
             let item_weight = problem.weights[item_idx];
 
@@ -137,6 +141,7 @@ fn rebalance_bins(
                 packing.bins[bin_idx].items.push(item_idx);
             }
         }
+        packing.bins.retain(|bin| !bin.is_empty());
     } else { // пытаемся разгрузить контейнер
         for &other_bin_idx in other_bins_indices.iter() {
             let other_bin_weight = packing.bins[other_bin_idx].weight(problem);
@@ -183,9 +188,9 @@ fn disbalance_bins(
 
     // вычисляем свободное место в большем контейнере
     let free_space1 = problem.capacity - packing.bins[bin1_idx].weight(problem);
-    if free_space1 == 0 { return packing; }
+    if free_space1 == 0 || packing.bins[bin2_idx].is_empty() { return packing; }
     // выбираем случайный обьект из меньшего контейнера
-    let item_from2_idx = rng.rand() as usize % packing.bins[bin2_idx].len();
+    let item_from2_idx = rng.gen_range(0..packing.bins[bin2_idx].len());
     let item_from2 = packing.bins[bin2_idx].items[item_from2_idx];
     // пробуем переложить обьект
     if problem.weights[item_from2] < free_space1 {
@@ -220,7 +225,7 @@ fn disbalance_bins(
 
 fn local_search(packing: Packing, problem: &BPProblem, rng: &mut Xoshiro256ss) -> Packing {
     let n_bins = packing.num_bins();
-    let bin_idx_pair = (rng.rand() as usize % n_bins, rng.rand() as usize % n_bins);
+    let bin_idx_pair = (rng.gen_range(0..n_bins), rng.gen_range(0..n_bins));
 
     let strategy = match bin_idx_pair {
         (n, m) if n == m => SearchStrategy::Rebalance(n),
@@ -235,122 +240,84 @@ fn local_search(packing: Packing, problem: &BPProblem, rng: &mut Xoshiro256ss) -
 
 const TIME_LIMIT: u64 = 10_000;
 const STOP_TIME: Duration = Duration::from_millis(TIME_LIMIT - 1);
+const DEFAULT_SEED: u64 = 42;
 
-fn find_solution(problem: &BPProblem) -> Packing {
+fn find_solution(problem: &BPProblem, options: &RunOptions) -> Packing {
     let lower = lower_bound(problem);
     let mut packing = first_fit(problem);
 
-    let start_time = Instant::now();
-    let seed: u64 = 42;
-    let mut rng = Xoshiro256ss::new(seed);
-    loop {
-        if packing.num_bins() == lower { break; }
-        else {
-            let duration = start_time.elapsed();
-            if duration < STOP_TIME {
-                packing = local_search(packing, problem, &mut rng);
-            } else {
-                break;
-            }
-        }
+    let mut rng = options.rng(DEFAULT_SEED);
+    let mut stop_rule = options.stop_rule(STOP_TIME, Instant::now());
+    while packing.num_bins() != lower && !stop_rule.should_stop() {
+        packing = local_search(packing, problem, &mut rng);
     }
+    options.dump("end", &rng);
     packing
 }
 
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<BPProblem, ScanError> {
+    let n: usize = scan.token()?;
+    let capacity: usize = scan.token()?;
+    let weights = scan.tokens(n)?;
+    Ok(BPProblem { capacity, weights })
+}
+
 fn main() {
+    let options = RunOptions::from_env().unwrap_or_else(|err| err.exit());
+
     let stdin = std::io::stdin();
     let mut scan = Scanner::new(stdin.lock());
 
-    let n: usize = scan.token();
-    let capacity: usize = scan.token();
-    let weights: Vec<usize> = (0..n).map(|_| scan.token()).collect();
-    let problem = BPProblem { capacity, weights };
+    let problem = read_problem(&mut scan).unwrap_or_else(|err| err.exit());
 
-    let packing = find_solution(&problem);
+    let packing = find_solution(&problem, &options);
     println!("{}", packing);
 }
 
-mod io {
-    use std::io::BufRead;
-
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
-    }
-
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
-            }
-        }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
-                }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // все предметы на месте, контейнеры непусты и не переполнены
+    fn check_packing(problem: &BPProblem, packing: &Packing) {
+        let mut seen = vec![false; problem.weights.len()];
+        for bin in &packing.bins {
+            assert!(!bin.is_empty());
+            assert!(bin.weight(problem) <= problem.capacity);
+            for &item_idx in &bin.items {
+                assert!(!seen[item_idx]);
+                seen[item_idx] = true;
             }
         }
+        assert!(seen.iter().all(|&seen| seen));
     }
 
-}
-
-mod rng {
-
-    pub struct Xoshiro256ss {
-        state: [u64; 4],
-    }
-
-    impl Xoshiro256ss {
-        pub fn new(seed: u64) -> Self {
-            let mut state = [0; 4];
-            let mut split_mix = SplitMix64 { state: seed };
-            state[0] = split_mix.rand();
-            state[1] = split_mix.rand();
-            state[2] = split_mix.rand();
-            state[3] = split_mix.rand();
-            Self { state }
-        }
-
-        fn rol64(x: u64, k: i64) ->u64 {
-            (x << k) | (x >> (64 - k))
-        }
-
-        pub fn rand(&mut self) -> u64 {
-            let result = Self::rol64(self.state[1].wrapping_mul(5), 7).wrapping_mul(9);
-            let t = self.state[1] << 17;
-
-            self.state[2] ^= self.state[0];
-            self.state[3] ^= self.state[1];
-            self.state[1] ^= self.state[2];
-            self.state[0] ^= self.state[3];
-
-            self.state[2] ^= t;
-            self.state[3] = Self::rol64(self.state[3], 45);
-
-            return result;
+    // на этом входе контейнеры опустевали и gen_range(0..0) паниковал
+    #[test]
+    fn emptied_bins_are_closed() {
+        let problem = BPProblem { capacity: 11, weights: vec![4, 0, 2, 11, 5, 9, 10, 6, 6] };
+        let mut rng = Xoshiro256ss::new(DEFAULT_SEED);
+        let mut packing = first_fit(&problem);
+        for _ in 0..20_000 {
+            packing = local_search(packing, &problem, &mut rng);
+            check_packing(&problem, &packing);
         }
     }
 
-    struct SplitMix64 {
-        state: u64,
-    }
-
-    impl SplitMix64 {
-        fn rand(&mut self) -> u64 {
-            let mut result = self.state;
-            self.state = result.wrapping_add(0x9E3779B97f4A7C15);
-            result = (result ^ (result >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
-            result = (result ^ (result >> 27)).wrapping_mul(0x94D049BB133111EB);
-            return result ^ (result >> 31);
+    #[test]
+    fn local_search_keeps_packing_valid() {
+        let mut rng = Xoshiro256ss::new(7);
+        for _ in 0..200 {
+            let capacity = rng.gen_range(1..20);
+            let n_items = rng.gen_range(1..12);
+            let weights = (0..n_items).map(|_| rng.gen_range(0..capacity + 1)).collect();
+            let problem = BPProblem { capacity, weights };
+            let mut packing = first_fit(&problem);
+            check_packing(&problem, &packing);
+            for _ in 0..200 {
+                packing = local_search(packing, &problem, &mut rng);
+                check_packing(&problem, &packing);
+            }
         }
     }
 }
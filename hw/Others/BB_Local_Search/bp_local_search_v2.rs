@@ -1,8 +1,11 @@
-use io::Scanner;
-use rng::Xoshiro256ss;
+use dopt::bin_packing::{heuristic_packing, l3_bound, num_bins, reduce, Heuristic};
+use dopt::io::{Scanner, ScanError};
+use dopt::rng::Xoshiro256ss;
+use dopt::run::{ExtraOptions, OptionsError, RunOptions, StopRule};
 use std::fmt;
 use std::fmt::Display;
 use std::time::{Instant, Duration};
+use std::io::BufRead;
 
 struct BPProblem {
     pub capacity: usize,
@@ -12,19 +15,28 @@ struct BPProblem {
 #[derive(Default, Clone)]
 struct Bin {
     pub items: Vec<usize>,
+    pub load: usize, // вес предметов, меняется вместе с items
 }
 
 impl Bin {
-    pub fn weight(&self, problem: &BPProblem) -> usize {
-        self.items.iter().map(|item_idx| problem.weights[*item_idx]).sum()
-    }
-
-    pub fn len(&self) -> usize {
-        self.items.len()
+    fn take(&mut self, group: &Group, problem: &BPProblem) -> Vec<usize> {
+        let mut positions = group.positions[..group.len].to_vec();
+        // с конца, чтобы swap_remove не сдвигал оставшиеся позиции
+        positions.sort_unstable_by(|pos1, pos2| pos2.cmp(pos1));
+        positions.into_iter()
+            .map(|pos| {
+                let item_idx = self.items.swap_remove(pos);
+                self.load -= problem.weights[item_idx];
+                item_idx
+            })
+            .collect()
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
+    fn put(&mut self, items: Vec<usize>, problem: &BPProblem) {
+        for item_idx in items {
+            self.load += problem.weights[item_idx];
+            self.items.push(item_idx);
+        }
     }
 }
 
@@ -34,11 +46,11 @@ struct Packing {
 }
 
 impl Packing {
-    pub fn from_indices(indices: Vec<usize>) -> Self {
-        let n_bins = *indices.iter().max().unwrap_or(&0) + 1;
-        let mut bins = vec![Bin::default(); n_bins];
-        for (item_idx, bin_idx) in indices.iter().enumerate() {
-            bins[*bin_idx].items.push(item_idx);
+    pub fn from_indices(indices: Vec<usize>, problem: &BPProblem) -> Self {
+        let mut bins = vec![Bin::default(); num_bins(&indices)];
+        for (item_idx, &bin_idx) in indices.iter().enumerate() {
+            bins[bin_idx].items.push(item_idx);
+            bins[bin_idx].load += problem.weights[item_idx];
         }
         Self { bins }
     }
@@ -47,8 +59,33 @@ impl Packing {
         self.bins.len()
     }
 
-    pub fn len(&self) -> usize {
-        self.bins.len()
+    // контейнер каждого из n_items предметов
+    pub fn indices(&self, n_items: usize) -> Vec<usize> {
+        let mut indices = vec![0; n_items];
+        for (bin_idx, bin) in self.bins.iter().enumerate() {
+            for &item_idx in &bin.items {
+                indices[item_idx] = bin_idx;
+            }
+        }
+        indices
+    }
+
+    fn lightest_bin(&self) -> usize {
+        (0..self.bins.len()).min_by_key(|&bin_idx| self.bins[bin_idx].load).unwrap_or(0)
+    }
+
+    // опустевшие контейнеры закрываются
+    fn apply(&mut self, exchange: &Exchange, problem: &BPProblem) {
+        self.exchange(exchange, problem);
+        self.bins.retain(|bin| !bin.items.is_empty());
+    }
+
+    // обмен без закрытия опустевших контейнеров
+    fn exchange(&mut self, exchange: &Exchange, problem: &BPProblem) {
+        let out = self.bins[exchange.from].take(&exchange.out, problem);
+        let back = self.bins[exchange.to].take(&exchange.back, problem);
+        self.bins[exchange.to].put(out, problem);
+        self.bins[exchange.from].put(back, problem);
     }
 }
 
@@ -75,294 +112,355 @@ impl Display for Packing {
     }
 }
 
-fn first_fit(problem: &BPProblem) -> Packing {
-    let mut indices: Vec<usize> = vec![std::usize::MAX; problem.weights.len()];
-    let mut bin_spaces: Vec<usize> = Vec::with_capacity(indices.len());
-
-    for (item_idx, weight) in problem.weights.iter().enumerate() {
-        let bin = {
-                bin_spaces.iter_mut()
-                .enumerate()
-                .find_map(|(bin_idx, x)| {
-                    if *x > *weight {
-                        *x -= *weight;
-                        indices[item_idx] = bin_idx;
-                        Some(*x)
-                    } else {
-                        None
-                    }
-                })
-            };
+// Ноль, один или два предмета одного контейнера: позиции в items и вес
+#[derive(Clone, Copy, Default)]
+struct Group {
+    positions: [usize; 2],
+    len: usize,
+    weight: usize,
+}
 
-        if bin == None {
-            bin_spaces.push(problem.capacity - weight);
-            indices[item_idx] = bin_spaces.len() - 1;
-        }
-    }
-    Packing::from_indices(indices)
+// Группы каждого размера 0, 1 и 2 из предметов контейнера
+fn bin_groups(bin: &Bin, problem: &BPProblem) -> [Vec<Group>; 3] {
+    let weight = |pos: usize| problem.weights[bin.items[pos]];
+    let singles = (0..bin.items.len())
+        .map(|pos| Group { positions: [pos, 0], len: 1, weight: weight(pos) })
+        .collect();
+    let pairs = (0..bin.items.len())
+        .flat_map(|pos1| (pos1 + 1..bin.items.len()).map(move |pos2| (pos1, pos2)))
+        .map(|(pos1, pos2)| Group { positions: [pos1, pos2], len: 2, weight: weight(pos1) + weight(pos2) })
+        .collect();
+    [vec![Group::default()], singles, pairs]
 }
 
-fn lower_bound(problem: &BPProblem) -> usize {
-    let total_weight = problem.weights.iter().sum::<usize>() as f64;
-    (total_weight / problem.capacity as f64).ceil() as usize
+// Окрестности: (сколько предметов уходит из from, сколько приходит из to) -
+// перенос одного предмета и обмены 1-1, 1-2, 2-2. Обмен 2-1 - это 1-2
+// для пары контейнеров в обратном порядке.
+const NEIGHBOURHOODS: [(usize, usize); 4] = [(1, 0), (1, 1), (1, 2), (2, 2)];
+
+// Группа out переходит из контейнера from в to, группа back - обратно
+struct Exchange {
+    from: usize,
+    to: usize,
+    out: Group,
+    back: Group,
+    gain: i128, // прирост суммы квадратов загрузок
 }
 
-enum SearchStrategy {
-    Rebalance(usize), // пробуем перераспределить обьекты между контейнерами
-    Disbalance(usize, usize), // пробуем разгрузить один контейнер и загрузить другой
+#[derive(Clone, Copy, PartialEq)]
+enum Acceptance {
+    First, // первый улучшающий обмен
+    Best,  // лучший из всей окрестности
 }
 
-fn rebalance_bins(
-    bin_idx: usize,
-    mut packing: Packing,
+// Целевая функция - сумма квадратов загрузок (максимизируется): она растёт,
+// когда вес переходит в более полный контейнер, и лёгкие контейнеры
+// опустошаются. Если t - вес, ушедший из from в to, то прирост
+// (l_from - t)^2 + (l_to + t)^2 - l_from^2 - l_to^2 = 2t(t + l_to - l_from).
+// Контейнеры-источники перебираются от самого лёгкого; недопустимые
+// обмены не рассматриваются. groups[i] - группы контейнера i.
+fn improving_exchange(
+    packing: &Packing,
+    groups: &[[Vec<Group>; 3]],
     problem: &BPProblem,
-    rng: &mut Xoshiro256ss
-) -> Packing {
-    let bin_weight = packing.bins[bin_idx].weight(problem);
-
-    let other_bins_indices: Vec<usize> = (0..packing.len())
-        .filter(|x| *x != bin_idx)
-        .collect();
-
-    if bin_weight > problem.capacity / 2 { // пытаемся дозаполнить контейнер
-        let mut free_space = problem.capacity - bin_weight;
-        for &other_bin_idx in other_bins_indices.iter() {
-            let bin_item_idx = rng.rand() as usize % packing.bins[other_bin_idx].len();
-            let item_idx = packing.bins[other_bin_idx].items[bin_item_idx];
-            let item_weight = problem.weights[item_idx];
-
-            if item_weight < free_space {
-                free_space -= item_weight;
-                packing.bins[other_bin_idx].items.remove(bin_item_idx);
-                packing.bins[bin_idx].items.push(item_idx);
-            }
-        }
-    } else { // пытаемся разгрузить контейнер
-        for &other_bin_idx in other_bins_indices.iter() {
-            let other_bin_weight = packing.bins[other_bin_idx].weight(problem);
-            let mut other_free_space = problem.capacity - other_bin_weight;
-
-            // разделяем обьекты
-            let (bin_items, other_bin_items): (Vec<usize>, Vec<usize>) = packing.bins[bin_idx].items
-                .iter()
-                .partition(|&&item_idx| {
-                    let item_weight = problem.weights[item_idx];
-                    if item_weight > other_free_space {
-                        true
-                    } else {
-                        other_free_space -= item_weight;
-                        false
+    acceptance: Acceptance,
+) -> Option<Exchange> {
+    let mut sources: Vec<usize> = (0..packing.num_bins()).collect();
+    sources.sort_by_key(|&bin_idx| packing.bins[bin_idx].load);
+    let capacity = problem.capacity as i128;
+
+    let mut best: Option<Exchange> = None;
+    for &from in &sources {
+        for to in (0..packing.num_bins()).filter(|&to| to != from) {
+            let load_from = packing.bins[from].load as i128;
+            let load_to = packing.bins[to].load as i128;
+            for &(out_len, back_len) in &NEIGHBOURHOODS {
+                for out in &groups[from][out_len] {
+                    for back in &groups[to][back_len] {
+                        let moved = out.weight as i128 - back.weight as i128;
+                        if load_to + moved > capacity || load_from - moved > capacity { continue; }
+                        let gain = 2 * moved * (moved + load_to - load_from);
+                        if gain <= 0 || best.as_ref().is_some_and(|best| best.gain >= gain) { continue; }
+                        best = Some(Exchange { from, to, out: *out, back: *back, gain });
+                        if acceptance == Acceptance::First { return best; }
                     }
-                });
-            // распределяем обьекты по контейнерам
-            packing.bins[bin_idx].items = bin_items;
-            for item_idx in other_bin_items {
-                packing.bins[other_bin_idx].items.push(item_idx);
+                }
             }
         }
-        if packing.bins[bin_idx].is_empty() { packing.bins.remove(bin_idx); }
     }
-    packing
+    best
 }
 
-fn disbalance_bins(
-    bins_idx: (usize, usize),
-    mut packing: Packing,
-    problem: &BPProblem,
-    _rng: &mut Xoshiro256ss
-) -> Packing {
-    let (bin1_idx, bin2_idx) = bins_idx;
-    let weight1 = packing.bins[bin1_idx].weight(problem);
-    let weight2 = packing.bins[bin2_idx].weight(problem);
-
-    let (bin1_idx, bin2_idx) = if weight1 >= weight2 {
-        (bin1_idx, bin2_idx)
-    } else {
-        (bin2_idx, bin1_idx)
-    };
-
-    // вычисляем свободное место в большом контейнере
-    let mut free_space_big = (problem.capacity - packing.bins[bin1_idx].weight(problem)) as i64;
-    if free_space_big == 0 { return packing; }
-
-    // пробуем максимально заполнить большой контейнер
-    // набираем из маленького контейнера
-    let (from_small, small_bin): (Vec<usize>, Vec<usize>) = packing.bins[bin2_idx].items
-        .iter()
-        .partition(|&&item_idx| {
-            let item_weight = problem.weights[item_idx] as i64;
-            if item_weight < free_space_big {
-                free_space_big -= item_weight;
-                true
+// Спуск по улучшающим обменам до локального оптимума. Каждый обмен - шаг
+// stop_rule, иначе один спуск мог бы съесть весь бюджет. Группы пересчитываются
+// только у двух контейнеров обмена.
+fn descend(packing: &mut Packing, problem: &BPProblem, acceptance: Acceptance, stop_rule: &mut StopRule) {
+    let mut groups: Vec<[Vec<Group>; 3]> = packing.bins.iter().map(|bin| bin_groups(bin, problem)).collect();
+    while !stop_rule.should_stop() {
+        let exchange = match improving_exchange(packing, &groups, problem, acceptance) {
+            Some(exchange) => exchange,
+            None => return,
+        };
+        packing.exchange(&exchange, problem);
+        // с конца, чтобы удаление не сдвигало второй контейнер
+        for bin_idx in [exchange.from.max(exchange.to), exchange.from.min(exchange.to)] {
+            if packing.bins[bin_idx].items.is_empty() {
+                packing.bins.remove(bin_idx);
+                groups.remove(bin_idx);
             } else {
-                false
+                groups[bin_idx] = bin_groups(&packing.bins[bin_idx], problem);
             }
-        });
-    // если набрано слишком много предметов, то вынимаем из большого контейнера предметы
-    // стараемся минимизировать вес перекладываемых в малый контейнер обьектов
-    packing.bins[bin1_idx].items.sort_unstable_by(|&item1, &item2| {
-        let item1_weight = problem.weights[item1];
-        let item2_weight = problem.weights[item2];
-        item1_weight.cmp(&item2_weight)
-    });
-
-    let (from_big, big_bin): (Vec<usize>, Vec<usize>) = packing.bins[bin1_idx].items
-        .iter()
-        .partition(|&&item_idx| {
-            let item_weight = problem.weights[item_idx] as i64;
-            if free_space_big < 0 {
-                free_space_big += item_weight;
-                true
-            } else {
-                false
-            }
-        });
-
-    packing.bins[bin1_idx].items = big_bin.into_iter()
-        .chain(from_small.into_iter())
-        .collect();
-    packing.bins[bin2_idx].items = small_bin.into_iter()
-        .chain(from_big.into_iter())
-        .collect();
-    if packing.bins[bin2_idx].is_empty() { packing.bins.remove(bin2_idx); }
-
-    packing
+        }
+    }
 }
 
-fn local_search(packing: Packing, problem: &BPProblem, rng: &mut Xoshiro256ss) -> Packing {
-    let n_bins = packing.num_bins();
-    let bin_idx_pair = (rng.rand() as usize % n_bins, rng.rand() as usize % n_bins);
-
-    let strategy = match bin_idx_pair {
-        (n, m) if n == m => SearchStrategy::Rebalance(n),
-        (n, m) => SearchStrategy::Disbalance(n, m),
-    };
-
-    match strategy {
-        SearchStrategy::Rebalance(n) => rebalance_bins(n, packing, problem, rng),
-        SearchStrategy::Disbalance(n, m) => disbalance_bins((n, m), packing, problem, rng),
+// сколько случайных обменов делает встряска и сколько раз пробуется каждый
+const KICK_EXCHANGES: usize = 3;
+const KICK_ATTEMPTS: usize = 16;
+
+// Встряска из локального оптимума: несколько случайных допустимых обменов
+// 1-1, первый - между самым лёгким контейнером и другим
+fn kick(packing: &mut Packing, problem: &BPProblem, rng: &mut Xoshiro256ss) {
+    if packing.num_bins() < 2 { return; }
+    for exchange_idx in 0..KICK_EXCHANGES {
+        for _ in 0..KICK_ATTEMPTS {
+            let from = if exchange_idx == 0 { packing.lightest_bin() } else { rng.gen_range(0..packing.num_bins()) };
+            let to = rng.gen_range(0..packing.num_bins());
+            if to == from { continue; }
+            let single = |bin_idx: usize, rng: &mut Xoshiro256ss| {
+                let pos = rng.gen_range(0..packing.bins[bin_idx].items.len());
+                Group { positions: [pos, 0], len: 1, weight: problem.weights[packing.bins[bin_idx].items[pos]] }
+            };
+            let (out, back) = (single(from, rng), single(to, rng));
+            if out.weight == back.weight { continue; }
+            let moved = out.weight as isize - back.weight as isize;
+            let fits = |load: usize, delta: isize| load as isize + delta <= problem.capacity as isize;
+            if fits(packing.bins[to].load, moved) && fits(packing.bins[from].load, -moved) {
+                packing.apply(&Exchange { from, to, out, back, gain: 0 }, problem);
+                break;
+            }
+        }
     }
 }
 
 const TIME_LIMIT: u64 = 10_000;
 const STOP_TIME: Duration = Duration::from_millis(TIME_LIMIT - 1);
+const DEFAULT_SEED: u64 = 42;
+
+// Итерация: спуск до локального оптимума (или до остановки), затем
+// встряска. Упаковка всё время допустима, а число контейнеров не растёт.
+fn find_solution(problem: &BPProblem, lower: usize, search: &SearchOptions, options: &RunOptions) -> Packing {
+    let (_, indices) = heuristic_packing(problem.capacity, &problem.weights, search.heuristic);
+    let mut packing = Packing::from_indices(indices, problem);
+
+    let mut rng = options.rng(DEFAULT_SEED);
+    let mut stop_rule = options.stop_rule(STOP_TIME, Instant::now());
+    while packing.num_bins() > lower && !stop_rule.should_stop() {
+        descend(&mut packing, problem, search.acceptance, &mut stop_rule);
+        if packing.num_bins() > lower { kick(&mut packing, problem, &mut rng); }
+    }
+    options.dump("end", &rng);
+    packing
+}
 
-fn find_solution(problem: &BPProblem) -> Packing {
-    let lower = lower_bound(problem);
-    let mut packing = first_fit(problem);
-
-    let start_time = Instant::now();
-    let seed: u64 = 42;
-    let mut rng = Xoshiro256ss::new(seed);
-    loop {
-        if packing.num_bins() == lower { break; }
-        else {
-            let duration = start_time.elapsed();
-            if duration < STOP_TIME {
-                packing = local_search(packing, problem, &mut rng);
-            } else {
-                break;
-            }
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<BPProblem, ScanError> {
+    let n: usize = scan.token()?;
+    let capacity: usize = scan.token()?;
+    let weights = scan.tokens(n)?;
+    Ok(BPProblem { capacity, weights })
+}
+
+struct SearchOptions {
+    heuristic: Option<Heuristic>, // None - лучшая из всех эвристик
+    acceptance: Acceptance,
+}
+
+// --heuristic NAME|all - начальная упаковка (по умолчанию лучшая из всех);
+// --acceptance first|best - какой улучшающий обмен применять (по умолчанию first)
+fn read_search_options(extra: &ExtraOptions) -> Result<SearchOptions, OptionsError> {
+    let mut search = SearchOptions { heuristic: None, acceptance: Acceptance::First };
+    for (option, value) in extra {
+        let invalid = || OptionsError::Invalid { option, value: value.clone() };
+        match (*option, value.as_str()) {
+            ("--heuristic", "all") => search.heuristic = None,
+            ("--heuristic", name) => search.heuristic = Some(name.parse().map_err(|_: String| invalid())?),
+            ("--acceptance", "first") => search.acceptance = Acceptance::First,
+            ("--acceptance", "best") => search.acceptance = Acceptance::Best,
+            _ => return Err(invalid()),
         }
     }
-    packing
+    Ok(search)
 }
 
 fn main() {
+    let (options, extra) = RunOptions::from_env_with(&["--heuristic", "--acceptance"])
+        .unwrap_or_else(|err| err.exit());
+    let search = read_search_options(&extra).unwrap_or_else(|err| err.exit());
+
     let stdin = std::io::stdin();
     let mut scan = Scanner::new(stdin.lock());
 
-    let n: usize = scan.token();
-    let capacity: usize = scan.token();
-    let weights: Vec<usize> = (0..n).map(|_| scan.token()).collect();
-    let problem = BPProblem { capacity, weights };
+    let problem = read_problem(&mut scan).unwrap_or_else(|err| err.exit());
+    if let Some(item_idx) = problem.weights.iter().position(|&weight| weight > problem.capacity) {
+        eprintln!("Input error: item {} is heavier than the bin capacity", item_idx + 1);
+        std::process::exit(1)
+    }
 
-    let packing = find_solution(&problem);
-    // println!("{}", packing.num_bins());
+    // локальный поиск идёт только по предметам, не попавшим в контейнеры,
+    // зафиксированные редукцией
+    let reduction = reduce(problem.capacity, &problem.weights);
+    let residual = BPProblem {
+        capacity: problem.capacity,
+        weights: reduction.residual_weights(&problem.weights),
+    };
+    eprintln!("fixed by reduction: {} items in {} bins", reduction.fixed_items(), reduction.fixed.len());
+    let residual_lower = l3_bound(residual.capacity, &residual.weights);
+    let residual_packing = find_solution(&residual, residual_lower, &search, &options);
+    let indices = reduction.merge(&problem.weights, &residual_packing.indices(residual.weights.len()));
+    let packing = Packing::from_indices(indices, &problem);
+    let lower = reduction.fixed.len() + residual_lower;
+    // насколько найденная упаковка может быть хуже оптимальной
+    let bins = packing.num_bins();
+    eprintln!("bins: {}, lower bound: {}, gap: {}", bins, lower, bins.saturating_sub(lower));
     println!("{}", packing);
 }
 
-mod io {
-    use std::io::BufRead;
-
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
-    }
-
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
-            }
-        }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // случайная допустимая упаковка: предмет идёт в случайный контейнер,
+    // где помещается, или в новый
+    fn random_packing(rng: &mut Xoshiro256ss) -> (BPProblem, Packing) {
+        let capacity = rng.gen_range(1..20);
+        let n_items = rng.gen_range(0..9);
+        let weights: Vec<usize> = (0..n_items).map(|_| rng.gen_range(0..capacity + 1)).collect();
+        let mut loads: Vec<usize> = Vec::new();
+        let mut indices = Vec::with_capacity(n_items);
+        for &weight in &weights {
+            let bin_idx = rng.gen_range(0..loads.len() + 1);
+            let bin_idx = match loads.get(bin_idx) {
+                Some(&load) if load + weight <= capacity => bin_idx,
+                _ => {
+                    loads.push(0);
+                    loads.len() - 1
                 }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-            }
+            };
+            loads[bin_idx] += weight;
+            indices.push(bin_idx);
         }
+        let problem = BPProblem { capacity, weights };
+        let packing = Packing::from_indices(indices, &problem);
+        (problem, packing)
     }
 
-}
-
-mod rng {
+    fn all_groups(packing: &Packing, problem: &BPProblem) -> Vec<[Vec<Group>; 3]> {
+        packing.bins.iter().map(|bin| bin_groups(bin, problem)).collect()
+    }
 
-    pub struct Xoshiro256ss {
-        state: [u64; 4],
+    fn sum_of_squares(packing: &Packing) -> i128 {
+        packing.bins.iter().map(|bin| bin.load as i128 * bin.load as i128).sum()
     }
 
-    impl Xoshiro256ss {
-        pub fn new(seed: u64) -> Self {
-            let mut state = [0; 4];
-            let mut split_mix = SplitMix64 { state: seed };
-            state[0] = split_mix.rand();
-            state[1] = split_mix.rand();
-            state[2] = split_mix.rand();
-            state[3] = split_mix.rand();
-            Self { state }
+    fn check_packing(problem: &BPProblem, packing: &Packing) {
+        let mut seen = vec![false; problem.weights.len()];
+        for bin in &packing.bins {
+            assert!(!bin.items.is_empty());
+            assert_eq!(bin.load, bin.items.iter().map(|&item_idx| problem.weights[item_idx]).sum::<usize>());
+            assert!(bin.load <= problem.capacity);
+            for &item_idx in &bin.items {
+                assert!(!seen[item_idx]);
+                seen[item_idx] = true;
+            }
         }
+        assert!(seen.iter().all(|&seen| seen));
+    }
 
-        fn rol64(x: u64, k: i64) ->u64 {
-            (x << k) | (x >> (64 - k))
+    // наибольший прирост суммы квадратов по всем допустимым обменам
+    // окрестностей, посчитанный по новым загрузкам
+    fn best_gain(problem: &BPProblem, packing: &Packing) -> i128 {
+        // веса всех групп из size предметов контейнера
+        let subsets = |bin: &Bin, size: usize| -> Vec<usize> {
+            (0..1usize << bin.items.len())
+                .filter(|mask| mask.count_ones() as usize == size)
+                .map(|mask| bin.items.iter().enumerate().filter(move |(pos, _)| mask >> pos & 1 == 1))
+                .map(|group| group.map(|(_, &item_idx)| problem.weights[item_idx]).sum())
+                .collect()
+        };
+        let mut best = 0;
+        for (from, bin_from) in packing.bins.iter().enumerate() {
+            for (to, bin_to) in packing.bins.iter().enumerate() {
+                if to == from { continue; }
+                for &(out_len, back_len) in &NEIGHBOURHOODS {
+                    for out in subsets(bin_from, out_len) {
+                        for back in subsets(bin_to, back_len) {
+                            let load_from = bin_from.load - out + back;
+                            let load_to = bin_to.load + out - back;
+                            if load_from > problem.capacity || load_to > problem.capacity { continue; }
+                            let square = |load: usize| load as i128 * load as i128;
+                            let gain = square(load_from) + square(load_to) - square(bin_from.load) - square(bin_to.load);
+                            best = best.max(gain);
+                        }
+                    }
+                }
+            }
         }
+        best
+    }
 
-        pub fn rand(&mut self) -> u64 {
-            let result = Self::rol64(self.state[1].wrapping_mul(5), 7).wrapping_mul(9);
-            let t = self.state[1] << 17;
-
-            self.state[2] ^= self.state[0];
-            self.state[3] ^= self.state[1];
-            self.state[1] ^= self.state[2];
-            self.state[0] ^= self.state[3];
-
-            self.state[2] ^= t;
-            self.state[3] = Self::rol64(self.state[3], 45);
-
-            return result;
+    #[test]
+    fn exchanges_match_neighbourhoods() {
+        let mut rng = Xoshiro256ss::new(25);
+        for _ in 0..500 {
+            let (problem, packing) = random_packing(&mut rng);
+            check_packing(&problem, &packing);
+            let groups = all_groups(&packing, &problem);
+            let expected = best_gain(&problem, &packing);
+            for acceptance in [Acceptance::First, Acceptance::Best] {
+                let exchange = improving_exchange(&packing, &groups, &problem, acceptance);
+                assert_eq!(exchange.is_some(), expected > 0);
+                let exchange = match exchange {
+                    Some(exchange) => exchange,
+                    None => continue,
+                };
+                if acceptance == Acceptance::Best { assert_eq!(exchange.gain, expected); }
+                // прирост по формуле равен фактическому
+                let mut next = packing.clone();
+                next.apply(&exchange, &problem);
+                check_packing(&problem, &next);
+                assert_eq!(sum_of_squares(&next) - sum_of_squares(&packing), exchange.gain);
+            }
         }
     }
 
-    struct SplitMix64 {
-        state: u64,
+    #[test]
+    fn descent_reaches_local_optimum() {
+        let mut rng = Xoshiro256ss::new(26);
+        for _ in 0..500 {
+            let (problem, mut packing) = random_packing(&mut rng);
+            let bins = packing.num_bins();
+            let acceptance = if rng.gen_bool(0.5) { Acceptance::First } else { Acceptance::Best };
+            descend(&mut packing, &problem, acceptance, &mut StopRule::Iterations { done: 0, limit: u64::MAX });
+            check_packing(&problem, &packing);
+            assert!(packing.num_bins() <= bins);
+            assert!(improving_exchange(&packing, &all_groups(&packing, &problem), &problem, acceptance).is_none());
+        }
     }
 
-    impl SplitMix64 {
-        fn rand(&mut self) -> u64 {
-            let mut result = self.state;
-            self.state = result.wrapping_add(0x9E3779B97f4A7C15);
-            result = (result ^ (result >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
-            result = (result ^ (result >> 27)).wrapping_mul(0x94D049BB133111EB);
-            return result ^ (result >> 31);
+    // каждый обмен спуска - шаг правила остановки
+    #[test]
+    fn descent_respects_stop_rule() {
+        let mut rng = Xoshiro256ss::new(27);
+        for _ in 0..500 {
+            let (problem, packing) = random_packing(&mut rng);
+            let mut stopped = packing.clone();
+            descend(&mut stopped, &problem, Acceptance::First, &mut StopRule::Iterations { done: 0, limit: 0 });
+            assert_eq!(stopped.indices(problem.weights.len()), packing.indices(problem.weights.len()));
+
+            let mut one_step = packing.clone();
+            descend(&mut one_step, &problem, Acceptance::Best, &mut StopRule::Iterations { done: 0, limit: 1 });
+            check_packing(&problem, &one_step);
+            assert_eq!(sum_of_squares(&one_step) - sum_of_squares(&packing), best_gain(&problem, &packing));
         }
     }
 }
@@ -1,40 +1,7 @@
-use io::Scanner;
+use dopt::io::Scanner;
 
 
 fn main() {
     let stdin = std::io::stdin();
-    let mut scan = Scanner::new(stdin.lock());
+    let _scan = Scanner::new(stdin.lock());
 }
-
-mod io {
-    use std::io::BufRead;
-
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
-    }
-
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
-            }
-        }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
-                }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-            }
-        }
-    }
-
-}
\ No newline at end of file
@@ -1,8 +1,10 @@
-use io::Scanner;
+use dopt::distance::{DistanceOracle, PackedMatrix};
+use dopt::io::{Scanner, ScanError};
 use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::io::BufRead;
 
-type DMatrix = matrix::Matrix<f64>;
+type DMatrix = PackedMatrix<f64>;
 type Queue = VecDeque<f64>;
 
 fn distance(coord1: &(f64, f64), coord2: &(f64, f64)) -> f64 {
@@ -13,34 +15,36 @@ fn distance(coord1: &(f64, f64), coord2: &(f64, f64)) -> f64 {
 }
 
 fn build_dist_matrix(coordinates: &[(f64, f64)]) -> DMatrix {
-    let n_nodes = coordinates.len();
-    let mut matrix = DMatrix::empty_squared(n_nodes);
-    for i in 0..n_nodes {
-        for j in 0..n_nodes {
-            matrix[(i, j)] = distance(&coordinates[i], &coordinates[j]);
-        }
-    }
-    matrix
+    DMatrix::from_fn(coordinates.len(), |i, j| distance(&coordinates[i], &coordinates[j]))
 }
 
-fn main() {
-    let stdin = std::io::stdin();
-    let mut scan = Scanner::new(stdin.lock());
+type Islands = (f64, usize, f64, Vec<(f64, f64)>, Vec<f64>);
 
-    let n: usize = scan.token();
-    let p: f64 = scan.token();
-    let k: usize = scan.token();
-    let max_sum: f64 = scan.token();
+// p, k, max_sum, координаты островов и награды за них
+fn read_islands<B: BufRead>(scan: &mut Scanner<B>) -> Result<Islands, ScanError> {
+    let n: usize = scan.token()?;
+    let p: f64 = scan.token()?;
+    let k: usize = scan.token()?;
+    let max_sum: f64 = scan.token()?;
 
     let mut coordinates: Vec<(f64, f64)> = Vec::with_capacity(n);
     let mut rewards: Vec<f64> = Vec::with_capacity(n);
 
     for _ in 0..n {
-        coordinates.push((scan.token(), scan.token()));
-        rewards.push(scan.token());
+        coordinates.push((scan.token()?, scan.token()?));
+        rewards.push(scan.token()?);
     }
+    Ok((p, k, max_sum, coordinates, rewards))
+}
+
+fn main() {
+    let stdin = std::io::stdin();
+    let mut scan = Scanner::new(stdin.lock());
+
+    let (p, k, max_sum, coordinates, rewards) = read_islands(&mut scan).unwrap_or_else(|err| err.exit());
+    let n = rewards.len();
 
-    let max_reward: f64 = rewards.iter().sum();
+    let _max_reward: f64 = rewards.iter().sum();
     let dist_matrix = build_dist_matrix(&coordinates);
 
     let mut used = vec![false; n];
@@ -54,19 +58,18 @@ fn main() {
     let mut curr_idx = home_idx;
 
     // жадно ищем путь
-    while !used.iter().all(|&x| x == true) {
+    while !used.iter().all(|&x| x) {
         let best_next_island = rewards.iter()
             .enumerate()
             .filter(|(idx, &reward)| {
-                if *idx == curr_idx { false }
-                else if used[*idx] { false }
+                if *idx == curr_idx || used[*idx] { false }
                 else {
                     let sum: f64 = queue.iter().skip(1).sum();
                     sum + reward < max_sum
                 }
             })
             .map(|(idx, &reward)| {
-                let dist = dist_matrix[(curr_idx, idx)];
+                let dist = dist_matrix.distance(curr_idx, idx);
                 let travel_cost = p * dist;
                 let income_reward = reward - travel_cost;
                 let profit_rate = income_reward / dist.powf(0.8);
@@ -118,83 +121,3 @@ fn main() {
 
     println!("{}", result);
 }
-
-mod io {
-    use std::io::BufRead;
-
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
-    }
-
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
-            }
-        }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
-                }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-            }
-        }
-    }
-
-}
-
-mod matrix {
-    use std::ops::{Index, IndexMut};
-
-    pub struct Matrix<T: Default + Clone> {
-        rows: usize,
-        columns: usize,
-        pub elements: Vec<T>,
-    }
-
-    impl<T: Default + Clone> Matrix<T> {
-        pub fn new(rows: usize, columns: usize, elements: Vec<T>) -> Self {
-            Self { rows, columns, elements }
-        }
-
-        pub fn empty(rows: usize, columns: usize) -> Self {
-            let elements = vec![T::default(); rows * columns];
-            Self::new(rows, columns, elements)
-        }
-
-        pub fn empty_squared(size: usize) -> Self {
-            Self::empty(size, size)
-        }
-
-        pub fn shape(&self) -> (usize, usize) {
-            (self.rows, self.columns)
-        }
-    }
-
-    impl<T: Default + Clone> Index<(usize, usize)> for Matrix<T> {
-        type Output = T;
-
-        fn index(&self, index2d: (usize, usize)) -> &Self::Output {
-            let (row_idx, col_idx) = index2d;
-            let element_idx = row_idx * self.columns + col_idx;
-            &self.elements[element_idx]
-        }
-    }
-
-    impl<T: Default + Clone> IndexMut<(usize, usize)> for Matrix<T> {
-        fn index_mut(&mut self, index2d: (usize, usize)) -> &mut T {
-            let (row_idx, col_idx) = index2d;
-            let element_idx = row_idx * self.columns + col_idx;
-            &mut self.elements[element_idx]
-        }
-    }
-}
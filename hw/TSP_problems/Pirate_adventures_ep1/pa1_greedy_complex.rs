@@ -1,17 +1,19 @@
-use io::Scanner;
-use rng::Xoshiro256ss;
+use dopt::distance::{DistanceOracle, PackedMatrix};
+use dopt::io::{Scanner, ScanError};
+use dopt::run::RunOptions;
 use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::io::BufRead;
 use std::time::{Duration, Instant};
 
-type DMatrix = matrix::Matrix<f64>;
+type DMatrix = PackedMatrix<f64>;
 type Queue = VecDeque<f64>;
 
-struct PAProblem {
+struct PAProblem<D: DistanceOracle<Weight = f64>> {
     pub p: f64,
     pub k: usize,
     pub max_sum: f64,
-    pub dist_matrix: DMatrix,
+    pub dist_matrix: D,
     pub rewards: Vec<f64>,
 }
 
@@ -23,17 +25,10 @@ fn distance(coord1: &(f64, f64), coord2: &(f64, f64)) -> f64 {
 }
 
 fn build_dist_matrix(coordinates: &[(f64, f64)]) -> DMatrix {
-    let n_nodes = coordinates.len();
-    let mut matrix = DMatrix::empty_squared(n_nodes);
-    for i in 0..n_nodes {
-        for j in 0..n_nodes {
-            matrix[(i, j)] = distance(&coordinates[i], &coordinates[j]);
-        }
-    }
-    matrix
+    DMatrix::from_fn(coordinates.len(), |i, j| distance(&coordinates[i], &coordinates[j]))
 }
 
-fn greedy_search(pa_problem: &PAProblem) -> Vec<usize> {
+fn greedy_search<D: DistanceOracle<Weight = f64>>(pa_problem: &PAProblem<D>) -> Vec<usize> {
     let n = pa_problem.rewards.len();
     let mut used = vec![false; n];
     let mut path = Vec::with_capacity(n);
@@ -46,19 +41,18 @@ fn greedy_search(pa_problem: &PAProblem) -> Vec<usize> {
     let mut curr_idx = home_idx;
 
     // жадно ищем путь
-    while !used.iter().all(|&x| x == true) {
+    while !used.iter().all(|&x| x) {
         let best_next_island = pa_problem.rewards.iter()
             .enumerate()
             .filter(|(idx, &reward)| {
-                if *idx == curr_idx { false }
-                else if used[*idx] { false }
+                if *idx == curr_idx || used[*idx] { false }
                 else {
                     let sum: f64 = queue.iter().skip(1).sum();
                     sum + reward < pa_problem.max_sum
                 }
             })
             .map(|(idx, &reward)| {
-                let dist = pa_problem.dist_matrix[(curr_idx, idx)];
+                let dist = pa_problem.dist_matrix.distance(curr_idx, idx);
                 let travel_cost = pa_problem.p * dist;
                 let income_reward = reward - travel_cost;
                 let profit_rate = income_reward / dist.powf(0.8);
@@ -101,7 +95,7 @@ fn greedy_search(pa_problem: &PAProblem) -> Vec<usize> {
     path
 }
 
-fn is_correct_path(path: &[usize], pa_problem: &PAProblem) -> bool {
+fn is_correct_path<D: DistanceOracle<Weight = f64>>(path: &[usize], pa_problem: &PAProblem<D>) -> bool {
     let k = pa_problem.k;
     let max_sum = pa_problem.max_sum;
     let rewards = &pa_problem.rewards;
@@ -113,19 +107,19 @@ fn is_correct_path(path: &[usize], pa_problem: &PAProblem) -> bool {
     true
 }
 
-fn total_reward(path: &[usize], pa_problem: &PAProblem) -> f64 {
+fn total_reward<D: DistanceOracle<Weight = f64>>(path: &[usize], pa_problem: &PAProblem<D>) -> f64 {
     let mut total_reward = 0.;
     let mut prev_isl: usize = 0;
     for &isl_idx in path.iter().skip(1) {
         let isl_reward = pa_problem.rewards[isl_idx];
-        let travel_cost = pa_problem.p * pa_problem.dist_matrix[(prev_isl, isl_idx)];
+        let travel_cost = pa_problem.p * pa_problem.dist_matrix.distance(prev_isl, isl_idx);
         total_reward += isl_reward - travel_cost;
         prev_isl = isl_idx;
     }
     total_reward
 }
 
-fn local_search_3opt(mut path: Vec<usize>, pa_problem: &PAProblem, split: [usize; 3]) -> (f64, Vec<usize>) {
+fn local_search_3opt<D: DistanceOracle<Weight = f64>>(mut path: Vec<usize>, pa_problem: &PAProblem<D>, split: [usize; 3]) -> (f64, Vec<usize>) {
     let dist_matrix = &pa_problem.dist_matrix;
     let [node1, node2, node3] = split;
 
@@ -138,11 +132,11 @@ fn local_search_3opt(mut path: Vec<usize>, pa_problem: &PAProblem, split: [usize
     let f = path[node3];
 
     // различные способы соединить эти сегменты
-    let d0 = dist_matrix[(a, b)] + dist_matrix[(c, d)] + dist_matrix[(e, f)];
-    let d1 = dist_matrix[(a, c)] + dist_matrix[(b, d)] + dist_matrix[(e, f)];
-    let d2 = dist_matrix[(a, b)] + dist_matrix[(c, e)] + dist_matrix[(d, f)];
-    let d3 = dist_matrix[(a, d)] + dist_matrix[(e, b)] + dist_matrix[(c, f)];
-    let d4 = dist_matrix[(f, b)] + dist_matrix[(c, d)] + dist_matrix[(e, a)];
+    let d0 = dist_matrix.distance(a, b) + dist_matrix.distance(c, d) + dist_matrix.distance(e, f);
+    let d1 = dist_matrix.distance(a, c) + dist_matrix.distance(b, d) + dist_matrix.distance(e, f);
+    let d2 = dist_matrix.distance(a, b) + dist_matrix.distance(c, e) + dist_matrix.distance(d, f);
+    let d3 = dist_matrix.distance(a, d) + dist_matrix.distance(e, b) + dist_matrix.distance(c, f);
+    let d4 = dist_matrix.distance(f, b) + dist_matrix.distance(c, d) + dist_matrix.distance(e, a);
 
     if d0 > d1 {
         path[node1..node2].reverse();
@@ -162,7 +156,7 @@ fn local_search_3opt(mut path: Vec<usize>, pa_problem: &PAProblem, split: [usize
     }
 }
 
-fn local_search_add(mut path: Vec<usize>, pa_problem: &PAProblem) -> Vec<usize> {
+fn local_search_add<D: DistanceOracle<Weight = f64>>(mut path: Vec<usize>, pa_problem: &PAProblem<D>) -> Vec<usize> {
     let p = pa_problem.p;
     let dist_matrix = &pa_problem.dist_matrix;
     let rewards = &pa_problem.rewards;
@@ -182,12 +176,12 @@ fn local_search_add(mut path: Vec<usize>, pa_problem: &PAProblem) -> Vec<usize>
                     [idx1, idx2] => (idx1, idx2),
                     _ => unreachable!(),
                 };
-                let travel_cost_to = p * dist_matrix[(*isl1_idx, curr_idx)];
-                let travel_cost_from = p * dist_matrix[(curr_idx, *isl2_idx)];
+                let travel_cost_to = p * dist_matrix.distance(*isl1_idx, curr_idx);
+                let travel_cost_from = p * dist_matrix.distance(curr_idx, *isl2_idx);
                 let income_reward = rewards[curr_idx] + rewards[*isl2_idx] - travel_cost_to - travel_cost_from;
                 (path_idx, income_reward)
             })
-            .max_by(|(_, ir1), (_, ir2)| match ir1.partial_cmp(&ir2) {
+            .max_by(|(_, ir1), (_, ir2)| match ir1.partial_cmp(ir2) {
                 Some(ord) => ord,
                 None => Ordering::Greater,
             })
@@ -205,61 +199,64 @@ fn local_search_add(mut path: Vec<usize>, pa_problem: &PAProblem) -> Vec<usize>
 }
 
 const TIME: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_SEED: u64 = 42;
 
-fn local_search(mut path: Vec<usize>, pa_problem: &PAProblem) -> Vec<usize> {
+fn local_search<D: DistanceOracle<Weight = f64>>(mut path: Vec<usize>, pa_problem: &PAProblem<D>, options: &RunOptions) -> Vec<usize> {
     path = local_search_add(path, pa_problem);
 
-    let seed: u64 = 42;
-    let mut rng = Xoshiro256ss::new(seed);
+    let mut rng = options.rng(DEFAULT_SEED);
 
     let path_len = path.len();
-    let start_time = Instant::now();
-    loop {
-        if start_time.elapsed() < TIME {
-            let mut split = [
-                (rng.rand() as usize % (path_len - 2)) + 1,
-                (rng.rand() as usize % (path_len - 2)) + 1,
-                (rng.rand() as usize % (path_len - 2)) + 1
-            ];
-            split.sort();
-
-            let (_, path_mut) = local_search_3opt(path.clone(), pa_problem, split);
-
-            if is_correct_path(&path_mut, pa_problem) {
-                path = path_mut;
-            }
-        } else {
-            break;
+    if path_len <= 2 { return path; }
+    let mut stop_rule = options.stop_rule(TIME, Instant::now());
+    while !stop_rule.should_stop() {
+        let mut split = [
+            rng.gen_range(1..path_len - 1),
+            rng.gen_range(1..path_len - 1),
+            rng.gen_range(1..path_len - 1)
+        ];
+        split.sort();
+
+        let (_, path_mut) = local_search_3opt(path.clone(), pa_problem, split);
+
+        if is_correct_path(&path_mut, pa_problem) {
+            path = path_mut;
         }
     }
+    options.dump("end", &rng);
     path
 }
 
-fn main() {
-    let stdin = std::io::stdin();
-    let mut scan = Scanner::new(stdin.lock());
-
-    let n: usize = scan.token();
-    let p: f64 = scan.token();
-    let k: usize = scan.token();
-    let max_sum: f64 = scan.token();
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<PAProblem<DMatrix>, ScanError> {
+    let n: usize = scan.token()?;
+    let p: f64 = scan.token()?;
+    let k: usize = scan.token()?;
+    let max_sum: f64 = scan.token()?;
 
     let mut coordinates: Vec<(f64, f64)> = Vec::with_capacity(n);
     let mut rewards: Vec<f64> = Vec::with_capacity(n);
 
     for _ in 0..n {
-        coordinates.push((scan.token(), scan.token()));
-        rewards.push(scan.token());
+        coordinates.push((scan.token()?, scan.token()?));
+        rewards.push(scan.token()?);
     }
 
     let dist_matrix = build_dist_matrix(&coordinates);
+    Ok(PAProblem { p, k, max_sum, dist_matrix, rewards })
+}
+
+fn main() {
+    let options = RunOptions::from_env().unwrap_or_else(|err| err.exit());
 
-    let pa_problem = PAProblem { p, k, max_sum, dist_matrix, rewards };
+    let stdin = std::io::stdin();
+    let mut scan = Scanner::new(stdin.lock());
+
+    let pa_problem = read_problem(&mut scan).unwrap_or_else(|err| err.exit());
     let max_reward: f64 = pa_problem.rewards.iter().sum();
 
     // поиск решения
     let mut path = greedy_search(&pa_problem);
-    path = local_search(path, &pa_problem);
+    path = local_search(path, &pa_problem, &options);
 
     let total_reward = total_reward(&path, &pa_problem);
 
@@ -276,130 +273,3 @@ fn main() {
 
     println!("{}", result);
 }
-
-mod io {
-    use std::io::BufRead;
-
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
-    }
-
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
-            }
-        }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
-                }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-            }
-        }
-    }
-
-}
-
-mod matrix {
-    use std::ops::{Index, IndexMut};
-
-    pub struct Matrix<T: Default + Clone> {
-        columns: usize,
-        pub elements: Vec<T>,
-    }
-
-    impl<T: Default + Clone> Matrix<T> {
-        pub fn new(_rows: usize, columns: usize, elements: Vec<T>) -> Self {
-            Self { columns, elements }
-        }
-
-        pub fn empty(rows: usize, columns: usize) -> Self {
-            let elements = vec![T::default(); rows * columns];
-            Self::new(rows, columns, elements)
-        }
-
-        pub fn empty_squared(size: usize) -> Self {
-            Self::empty(size, size)
-        }
-    }
-
-    impl<T: Default + Clone> Index<(usize, usize)> for Matrix<T> {
-        type Output = T;
-
-        fn index(&self, index2d: (usize, usize)) -> &Self::Output {
-            let (row_idx, col_idx) = index2d;
-            let element_idx = row_idx * self.columns + col_idx;
-            &self.elements[element_idx]
-        }
-    }
-
-    impl<T: Default + Clone> IndexMut<(usize, usize)> for Matrix<T> {
-        fn index_mut(&mut self, index2d: (usize, usize)) -> &mut T {
-            let (row_idx, col_idx) = index2d;
-            let element_idx = row_idx * self.columns + col_idx;
-            &mut self.elements[element_idx]
-        }
-    }
-}
-
-mod rng {
-
-    pub struct Xoshiro256ss {
-        state: [u64; 4],
-    }
-
-    impl Xoshiro256ss {
-        pub fn new(seed: u64) -> Self {
-            let mut state = [0; 4];
-            let mut split_mix = SplitMix64 { state: seed };
-            state[0] = split_mix.rand();
-            state[1] = split_mix.rand();
-            state[2] = split_mix.rand();
-            state[3] = split_mix.rand();
-            Self { state }
-        }
-
-        fn rol64(x: u64, k: i64) ->u64 {
-            (x << k) | (x >> (64 - k))
-        }
-
-        pub fn rand(&mut self) -> u64 {
-            let result = Self::rol64(self.state[1].wrapping_mul(5), 7).wrapping_mul(9);
-            let t = self.state[1] << 17;
-
-            self.state[2] ^= self.state[0];
-            self.state[3] ^= self.state[1];
-            self.state[1] ^= self.state[2];
-            self.state[0] ^= self.state[3];
-
-            self.state[2] ^= t;
-            self.state[3] = Self::rol64(self.state[3], 45);
-
-            return result;
-        }
-    }
-
-    struct SplitMix64 {
-        state: u64,
-    }
-
-    impl SplitMix64 {
-        fn rand(&mut self) -> u64 {
-            let mut result = self.state;
-            self.state = result.wrapping_add(0x9E3779B97f4A7C15);
-            result = (result ^ (result >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
-            result = (result ^ (result >> 27)).wrapping_mul(0x94D049BB133111EB);
-            return result ^ (result >> 31);
-        }
-    }
-}
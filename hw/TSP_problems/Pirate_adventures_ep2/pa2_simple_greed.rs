@@ -1,5 +1,6 @@
-use std::io::{BufRead};
+use dopt::io::{Scanner, ScanError};
 use std::collections::{HashMap, VecDeque};
+use std::io::BufRead;
 
 type Island = (i64, i64, i64);
 
@@ -34,8 +35,8 @@ impl TravelInfo {
 
     fn cost(&self, isl1: &Island, isl2: &Island) -> i64 {
         let d = dist(isl1, isl2);
-        let score = isl2.2 - self.dist_cost * d;
-        score
+        
+        isl2.2 - self.dist_cost * d
     }
 }
 
@@ -47,28 +48,37 @@ fn dist(isl1: &Island, isl2: &Island) -> i64 {
     (d2 as f64).sqrt().round() as i64
 }
 
+// p, k, m и острова, первый из них - дом
+fn read_islands<B: BufRead>(scan: &mut Scanner<B>) -> Result<(i64, usize, i64, Vec<Island>), ScanError> {
+    let n: usize = scan.token()?;
+    let p: i64 = scan.token()?;
+    let k: usize = scan.token()?;
+    let m: i64 = scan.token()?;
+    let islands = (0..n)
+        .map(|_| Ok((scan.token()?, scan.token()?, scan.token()?)))
+        .collect::<Result<_, ScanError>>()?;
+    Ok((p, k, m, islands))
+}
+
 fn main() {
     let stdin = std::io::stdin();
     let mut scan = Scanner::new(stdin.lock());
 
-    let n: usize = scan.token();
-    let p: i64 = scan.token();
-    let k: usize = scan.token();
-    let m: i64 = scan.token();
+    let (p, k, m, all_islands) = read_islands(&mut scan).unwrap_or_else(|err| err.exit());
+    let n = all_islands.len();
 
     let mut islands: HashMap<usize, Island> = HashMap::with_capacity(n);
     let mut attended_islands: HashMap<usize, Island> = HashMap::with_capacity(n);
 
     let home_idx = 0;
-    let home: Island = (scan.token(), scan.token(), scan.token());
+    let home: Island = all_islands[home_idx];
 
     attended_islands.insert(home_idx, home);
     let mut current_position: Island = home;
 
     let mut travel_info = TravelInfo::new(p, k, m);
 
-    for idx in 1..n {
-        let island = (scan.token(), scan.token(), scan.token());
+    for (idx, island) in all_islands.into_iter().enumerate().skip(1) {
         islands.insert(idx, island);
     }
 
@@ -115,31 +125,3 @@ fn main() {
 
     print!("{}", result);
 }
-
-struct Scanner<B> {
-    reader: B,
-    buf_str: String,
-    buf_iter: std::str::SplitWhitespace<'static>,
-}
-
-impl<B: BufRead> Scanner<B> {
-    pub fn new(reader: B) -> Self {
-        Self {
-            reader,
-            buf_str: String::new(),
-            buf_iter: "".split_whitespace(),
-        }
-    }
-    pub fn token<T: std::str::FromStr>(&mut self) -> T {
-        loop {
-            if let Some(token) = self.buf_iter.next() {
-                return token.parse().ok().expect("Failed parse");
-            }
-            self.buf_str.clear();
-            self.reader
-                .read_line(&mut self.buf_str)
-                .expect("Failed read");
-            self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-        }
-    }
-}
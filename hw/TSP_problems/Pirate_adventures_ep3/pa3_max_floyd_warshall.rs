@@ -1,24 +1,34 @@
-use std::io::{BufRead};
-use std::ops::{Index, IndexMut};
+use dopt::graph::Graph;
+use dopt::io::{Scanner, ScanError};
+use dopt::matrix::Matrix;
+use dopt::paths::{floyd_warshall, MaxSum};
+use std::io::BufRead;
+
+type Island = (i64, i64, i64);
+
+// p и острова (x, y, m); k и M в этой задаче не используются
+fn read_islands<B: BufRead>(scan: &mut Scanner<B>) -> Result<(i64, Vec<Island>), ScanError> {
+    let n: usize = scan.token()?;
+    let p: i64 = scan.token()?;
+    let _k: usize = scan.token()?;
+    let _m: i64 = scan.token()?;
+    let islands = (0..n)
+        .map(|_| Ok((scan.token()?, scan.token()?, scan.token()?)))
+        .collect::<Result<_, ScanError>>()?;
+    Ok((p, islands))
+}
 
 fn main() {
     let stdin = std::io::stdin();
     let mut scan = Scanner::new(stdin.lock());
 
-    let n: usize = scan.token();
-    let p: i64 = scan.token();
-    let _k: usize = scan.token();
-    let _M: i64 = scan.token();
-
-    let mut islands: Vec<(i64, i64, i64)> = Vec::with_capacity(n);
-    for _ in 0..n {
-        islands.push((scan.token(), scan.token(), scan.token()));
-    }
+    let (p, islands) = read_islands(&mut scan).unwrap_or_else(|err| err.exit());
+    let n = islands.len();
 
-    let mut adj_matrix = Matrix::new(n, n);
+    let mut adj_matrix = Matrix::<i64>::empty_squared(n);
     for i in 0..n {
         for j in 0..n {
-            let (x1, y1, m1) = islands[i];
+            let (x1, y1, _m1) = islands[i];
             let (x2, y2, m2) = islands[j];
 
             let d2 = (x2 - x1).pow(2) + (y2 - y1).pow(2);
@@ -29,6 +39,18 @@ fn main() {
         }
     }
 
+    // при цикле с положительной прибылью лучшие пути не определены:
+    // значения ниже растут с числом проходов, а не сходятся
+    let mut graph: Graph<Island, i64> = Graph::from_nodes(islands.clone());
+    for i in 0..n {
+        for j in 0..n {
+            graph.add_edge(i, j, adj_matrix[(i, j)]);
+        }
+    }
+    if floyd_warshall(&graph, MaxSum).has_improving_cycle(MaxSum) {
+        eprintln!("Warning: there is a cycle with positive profit, the profits below are unbounded");
+    }
+
     // Floyd-Warshall maximize
     for k in 0..n {
         for i in 0..n {
@@ -42,68 +64,4 @@ fn main() {
         }
         println!("{}", adj_matrix[(0, 0)]);
     }
-    // println!("{:?}", islands);
-}
-
-pub struct Scanner<B> {
-    reader: B,
-    buf_str: String,
-    buf_iter: std::str::SplitWhitespace<'static>,
-}
-
-impl<B: BufRead> Scanner<B> {
-    pub fn new(reader: B) -> Self {
-        Self {
-            reader,
-            buf_str: String::new(),
-            buf_iter: "".split_whitespace(),
-        }
-    }
-    pub fn token<T: std::str::FromStr>(&mut self) -> T {
-        loop {
-            if let Some(token) = self.buf_iter.next() {
-                return token.parse().ok().expect("Failed parse");
-            }
-            self.buf_str.clear();
-            self.reader
-                .read_line(&mut self.buf_str)
-                .expect("Failed read");
-            self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-        }
-    }
-}
-
-struct Matrix {
-    rows: usize,
-    columns: usize,
-    field: Vec<i64>,
-}
-
-impl Matrix {
-    pub fn new(n: usize, m: usize) -> Matrix {
-        let size = n * m;
-        Matrix {
-            rows: n,
-            columns: m,
-            field: vec![0; size]
-        }
-    }
-}
-
-impl Index<(usize, usize)> for Matrix {
-    type Output = i64;
-
-    fn index(&self, idx: (usize, usize)) -> &Self::Output {
-        let (n, m) = idx;
-        let idx = n * self.columns + m;
-        &self.field[idx]
-    }
-}
-
-impl IndexMut<(usize, usize)> for Matrix {
-    fn index_mut(&mut self, idx: (usize, usize)) -> &mut Self::Output {
-        let (n, m) = idx;
-        let idx = n * self.columns + m;
-        &mut self.field[idx]
-    }
 }
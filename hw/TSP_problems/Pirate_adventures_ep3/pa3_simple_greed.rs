@@ -1,5 +1,6 @@
-use std::io::{BufRead};
+use dopt::io::{Scanner, ScanError};
 use std::collections::HashMap;
+use std::io::BufRead;
 
 type Island = (i64, i64, i64);
 
@@ -15,22 +16,31 @@ fn cost(p: i64, isl1: &Island, isl2: &Island) -> i64 {
     isl2.2 - p * d
 }
 
+// p и острова (x, y, m); k и M в этой задаче не используются
+fn read_islands<B: BufRead>(scan: &mut Scanner<B>) -> Result<(i64, Vec<Island>), ScanError> {
+    let n: usize = scan.token()?;
+    let p: i64 = scan.token()?;
+    let _k: usize = scan.token()?;
+    let _m: i64 = scan.token()?;
+    let islands = (0..n)
+        .map(|_| Ok((scan.token()?, scan.token()?, scan.token()?)))
+        .collect::<Result<_, ScanError>>()?;
+    Ok((p, islands))
+}
+
 fn main() {
     let stdin = std::io::stdin();
     let mut scan = Scanner::new(stdin.lock());
 
-    let n: usize = scan.token();
-    let p: i64 = scan.token();
-    let _k: usize = scan.token();
-    let _m: i64 = scan.token();
+    let (p, all_islands) = read_islands(&mut scan).unwrap_or_else(|err| err.exit());
+    let n = all_islands.len();
 
     let home_idx = 0;
-    let home: Island = (scan.token(), scan.token(), scan.token());
+    let home: Island = all_islands[home_idx];
     let mut current_position: Island = home;
 
     let mut islands: HashMap<usize, Island> = HashMap::with_capacity(n);
-    for idx in 1..n {
-        let island = (scan.token(), scan.token(), scan.token());
+    for (idx, island) in all_islands.into_iter().enumerate().skip(1) {
         islands.insert(idx, island);
     }
 
@@ -70,31 +80,3 @@ fn main() {
 
     print!("{}", result);
 }
-
-pub struct Scanner<B> {
-    reader: B,
-    buf_str: String,
-    buf_iter: std::str::SplitWhitespace<'static>,
-}
-
-impl<B: BufRead> Scanner<B> {
-    pub fn new(reader: B) -> Self {
-        Self {
-            reader,
-            buf_str: String::new(),
-            buf_iter: "".split_whitespace(),
-        }
-    }
-    pub fn token<T: std::str::FromStr>(&mut self) -> T {
-        loop {
-            if let Some(token) = self.buf_iter.next() {
-                return token.parse().ok().expect("Failed parse");
-            }
-            self.buf_str.clear();
-            self.reader
-                .read_line(&mut self.buf_str)
-                .expect("Failed read");
-            self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-        }
-    }
-}
@@ -1,6 +1,7 @@
-use io::Scanner;
+use dopt::io::{Scanner, ScanError};
 use std::fmt;
 use std::fmt::Display;
+use std::io::BufRead;
 // use std::io::Write;
 
 struct BBProblem {
@@ -56,7 +57,7 @@ impl Display for Packing {
 }
 
 fn first_fit(problem: &BBProblem) -> Packing {
-    let mut indices: Vec<usize> = vec![std::usize::MAX; problem.weights.len()];
+    let mut indices: Vec<usize> = vec![usize::MAX; problem.weights.len()];
     let mut bin_spaces: Vec<usize> = Vec::with_capacity(indices.len());
 
     for (item_idx, weight) in problem.weights.iter().enumerate() {
@@ -74,7 +75,7 @@ fn first_fit(problem: &BBProblem) -> Packing {
                 })
             };
 
-        if bin == None {
+        if bin.is_none() {
             bin_spaces.push(problem.capacity - weight);
             indices[item_idx] = bin_spaces.len() - 1;
         }
@@ -94,9 +95,10 @@ fn bin_packing_impl(
     problem: &BBProblem
 ) {
     if best_packing.num_bins() == lower_bound { return; }
-    else if !curr_packing.is_correct_packing(problem) { return; }
-    else if curr_packing.num_bins() >= best_packing.num_bins() { return;}
-    else if curr_packing.len() == problem.weights.len() { *best_packing = curr_packing.clone(); }
+    if !curr_packing.is_correct_packing(problem) { return; }
+    if curr_packing.num_bins() >= best_packing.num_bins() { return; }
+
+    if curr_packing.len() == problem.weights.len() { *best_packing = curr_packing.clone(); }
     else {
         let packing_len = curr_packing.len();
         for bin_idx in 0..best_packing.num_bins() {
@@ -136,6 +138,13 @@ fn print_bins(packing: &Packing, problem: &BBProblem) {
 
 }
 
+fn read_problem<B: BufRead>(scan: &mut Scanner<B>) -> Result<BBProblem, ScanError> {
+    let n: usize = scan.token()?;
+    let capacity: usize = scan.token()?;
+    let weights = scan.tokens(n)?;
+    Ok(BBProblem { capacity, weights })
+}
+
 fn main() {
     let stdin = std::io::stdin();
     let mut scan = Scanner::new(stdin.lock());
@@ -143,45 +152,9 @@ fn main() {
     // let stdout = std::io::stdout();
     // let mut writer = std::io::BufWriter::new(stdout.lock());
 
-    let n: usize = scan.token();
-    let capacity: usize = scan.token();
-    let weights: Vec<usize> = (0..n).map(|_| scan.token()).collect();
-    let problem = BBProblem { capacity, weights };
+    let problem = read_problem(&mut scan).unwrap_or_else(|err| err.exit());
 
     let packing = bin_packing(&problem);
     // println!("{}", packing);
     print_bins(&packing, &problem);
 }
-
-mod io {
-    use std::io::BufRead;
-
-    pub struct Scanner<B> {
-        reader: B,
-        buf_str: String,
-        buf_iter: std::str::SplitWhitespace<'static>,
-    }
-
-    impl<B: BufRead> Scanner<B> {
-        pub fn new(reader: B) -> Self {
-            Self {
-                reader,
-                buf_str: String::new(),
-                buf_iter: "".split_whitespace(),
-            }
-        }
-        pub fn token<T: std::str::FromStr>(&mut self) -> T {
-            loop {
-                if let Some(token) = self.buf_iter.next() {
-                    return token.parse().ok().expect("Failed parse");
-                }
-                self.buf_str.clear();
-                self.reader
-                    .read_line(&mut self.buf_str)
-                    .expect("Failed read");
-                self.buf_iter = unsafe { std::mem::transmute(self.buf_str.split_whitespace()) };
-            }
-        }
-    }
-
-}
\ No newline at end of file
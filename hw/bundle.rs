@@ -0,0 +1,108 @@
+// Собирает решение в один файл для отправки в тестирующую систему.
+//
+// Ищет в файле решения пути вида `dopt::<module>`, заменяет `dopt::` на
+// `crate::` и дописывает в конец файла используемые модули библиотеки
+// (вместе с их зависимостями) в виде `mod <module> { ... }`.
+//
+// cargo run -q --bin bundle -- hw/Branch_and_bound/Knapsack/knapsack.rs > submit.rs
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LIB_CRATE: &str = "dopt::";
+
+fn lib_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("Lib")
+}
+
+// модули библиотеки в порядке объявления в lib.rs
+fn lib_modules(lib_dir: &Path) -> Vec<String> {
+    let lib_rs = fs::read_to_string(lib_dir.join("lib.rs")).expect("Failed to read lib.rs");
+    lib_rs.lines()
+        .filter_map(|line| line.trim().strip_prefix("pub mod "))
+        .filter_map(|line| line.strip_suffix(';'))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn ident_prefix(text: &str) -> &str {
+    let end = text.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(text.len());
+    &text[..end]
+}
+
+// имена модулей, на которые ссылается код через `prefix`
+// (`prefix::module::...` или `prefix::{module1::..., module2::...}`)
+fn referenced_modules(source: &str, prefix: &str) -> BTreeSet<String> {
+    let mut modules = BTreeSet::new();
+    for (pos, _) in source.match_indices(prefix) {
+        let rest = &source[pos + prefix.len()..];
+        if let Some(group) = rest.strip_prefix('{') {
+            let group = &group[..group.find('}').unwrap_or(group.len())];
+            for path in group.split(',') {
+                let name = ident_prefix(path.trim());
+                if !name.is_empty() { modules.insert(name.to_string()); }
+            }
+        } else {
+            let name = ident_prefix(rest);
+            if !name.is_empty() { modules.insert(name.to_string()); }
+        }
+    }
+    modules
+}
+
+fn module_source(lib_dir: &Path, module: &str) -> String {
+    let path = lib_dir.join(format!("{}.rs", module));
+    fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", path.display(), err))
+}
+
+// модуль вместе со всеми модулями, от которых он зависит через `crate::`
+fn collect_dependencies(lib_dir: &Path, known: &[String], roots: BTreeSet<String>) -> BTreeSet<String> {
+    let mut used = BTreeSet::new();
+    let mut stack: Vec<String> = roots.into_iter().collect();
+    while let Some(module) = stack.pop() {
+        if !known.contains(&module) || !used.insert(module.clone()) { continue; }
+        let source = module_source(lib_dir, &module);
+        stack.extend(referenced_modules(&source, "crate::"));
+    }
+    used
+}
+
+fn rewrite_solution(source: &str, modules: &[String]) -> String {
+    source.replace(LIB_CRATE, "crate::")
+        .lines()
+        // `use crate::module;` в корне крейта конфликтует с самим модулем
+        .filter(|line| {
+            let path = line.trim()
+                .strip_prefix("use crate::")
+                .and_then(|path| path.strip_suffix(';'));
+            !matches!(path, Some(path) if modules.iter().any(|module| module == path))
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn indent(source: &str) -> String {
+    source.lines()
+        .map(|line| if line.is_empty() { "\n".to_string() } else { format!("    {}\n", line) })
+        .collect()
+}
+
+fn main() {
+    let solution_path = std::env::args().nth(1).expect("Usage: bundle <solution.rs>");
+    let solution = fs::read_to_string(&solution_path)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", solution_path, err));
+
+    let lib_dir = lib_dir();
+    let modules = lib_modules(&lib_dir);
+    let used = collect_dependencies(&lib_dir, &modules, referenced_modules(&solution, LIB_CRATE));
+
+    let mut bundle = rewrite_solution(&solution, &modules);
+    for module in modules.iter().filter(|module| used.contains(*module)) {
+        bundle.push_str(&format!("\nmod {} {{\n", module));
+        bundle.push_str(&indent(&module_source(&lib_dir, module)));
+        bundle.push_str("}\n");
+    }
+    print!("{}", bundle);
+}